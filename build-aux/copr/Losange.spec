@@ -33,6 +33,10 @@ mkdir -p %{buildroot}/usr/share/metainfo
 install -Dm644 data/xyz.timtimtim.Losange.metainfo.xml %{buildroot}/usr/share/metainfo/
 mkdir -p %{buildroot}/usr/share/glib-2.0/schemas
 install -Dm644 data/xyz.timtimtim.Losange.gschema.xml %{buildroot}/usr/share/glib-2.0/schemas/
+mkdir -p %{buildroot}/usr/share/gnome-shell/search-providers
+install -Dm644 data/xyz.timtimtim.Losange.search-provider.ini %{buildroot}/usr/share/gnome-shell/search-providers/
+mkdir -p %{buildroot}/usr/share/dbus-1/services
+install -Dm644 data/xyz.timtimtim.Losange.service %{buildroot}/usr/share/dbus-1/services/
 
 %files
 /usr/bin/losange
@@ -41,6 +45,8 @@ install -Dm644 data/xyz.timtimtim.Losange.gschema.xml %{buildroot}/usr/share/gli
 /usr/share/applications/xyz.timtimtim.Losange.desktop
 /usr/share/metainfo/xyz.timtimtim.Losange.metainfo.xml
 /usr/share/glib-2.0/schemas/xyz.timtimtim.Losange.gschema.xml
+/usr/share/gnome-shell/search-providers/xyz.timtimtim.Losange.search-provider.ini
+/usr/share/dbus-1/services/xyz.timtimtim.Losange.service
 
 %changelog
 * Sat Jul 12 2026 Tim Dusser-Jolly <tymmesyde@gmail.com> - 0.10.2-1
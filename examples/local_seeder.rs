@@ -0,0 +1,77 @@
+//! Local torrent seeder, useful to test the streaming engine without public peers.
+//!
+//! Usage:
+//!   cargo run --example local_seeder -- <file or directory>
+//!
+//! It creates a torrent from the given path, seeds it on localhost and prints the engine URL to
+//! open in the player, with the seeder passed as a `peer:` source so that no tracker is needed.
+
+use std::{env, net::Ipv4Addr, path::PathBuf};
+
+use anyhow::Context;
+use librqbit::{
+    create_torrent, AddTorrent, AddTorrentOptions, CreateTorrentOptions, Session, SessionOptions,
+};
+
+const ENGINE_PORT: u16 = 11470;
+const SEEDER_PORTS: std::ops::Range<u16> = 6881..6891;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let path = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .context("Usage: local_seeder <file or directory>")?
+        .canonicalize()
+        .context("Failed to resolve the path to seed")?;
+
+    let output_folder = path
+        .parent()
+        .context("Cannot seed the root directory")?
+        .to_owned();
+
+    let torrent = create_torrent(&path, CreateTorrentOptions::default())
+        .await
+        .context("Failed to create torrent")?;
+
+    let session = Session::new_with_opts(
+        output_folder.to_owned(),
+        SessionOptions {
+            disable_dht: true,
+            listen_port_range: Some(SEEDER_PORTS),
+            ..Default::default()
+        },
+    )
+    .await
+    .context("Failed to create torrent session")?;
+
+    let options = AddTorrentOptions {
+        output_folder: Some(output_folder.to_string_lossy().into_owned()),
+        overwrite: true,
+        ..Default::default()
+    };
+
+    let handle = session
+        .add_torrent(AddTorrent::from_bytes(torrent.as_bytes()?), Some(options))
+        .await?
+        .into_handle()
+        .context("Torrent was not added")?;
+
+    handle.wait_until_initialized().await?;
+
+    let info_hash = handle.info_hash().as_string();
+    let port = session
+        .tcp_listen_port()
+        .context("Seeder is not listening")?;
+
+    println!("Seeding {} ({info_hash})", path.display());
+    println!(
+        "http://{}:{ENGINE_PORT}/{info_hash}/-1?tr=peer:{}:{port}",
+        Ipv4Addr::LOCALHOST,
+        Ipv4Addr::LOCALHOST,
+    );
+
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
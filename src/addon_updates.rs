@@ -0,0 +1,223 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::future::join_all;
+use itertools::Itertools;
+use relm4::SharedState;
+use stremio_core_losange::{
+    addon_config, in_process_addon,
+    models::{self, ctx::CTX_STATE},
+    stremio_core::types::addon::{Descriptor, Manifest},
+};
+use tokio::time::{interval_at, Instant};
+use tracing::{info, warn};
+use url::Url;
+
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestPart {
+    Resource,
+    Type,
+    Catalog,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddonChange {
+    Version(String, String),
+    Added(ManifestPart, String),
+    Removed(ManifestPart, String),
+}
+
+#[derive(Debug, Clone)]
+pub struct AddonUpdate {
+    pub descriptor: Descriptor,
+    pub changes: Vec<AddonChange>,
+}
+
+#[derive(Default, Debug)]
+pub struct AddonUpdatesState {
+    pub updates: HashMap<Url, AddonUpdate>,
+}
+
+pub static ADDON_UPDATES_STATE: SharedState<AddonUpdatesState> = SharedState::new();
+
+pub fn start_monitor() {
+    let (sender, receiver) = relm4::channel::<()>();
+    CTX_STATE.subscribe(&sender, |_| ());
+
+    relm4::spawn(async move {
+        while receiver.recv().await.is_some() {
+            prune();
+        }
+    });
+
+    relm4::spawn(async {
+        let mut interval = interval_at(Instant::now() + STARTUP_DELAY, CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            // Protected addons cannot be reinstalled and in-process addons are always current
+            let installed = CTX_STATE
+                .read_inner()
+                .addons
+                .iter()
+                .filter(|addon| !addon.flags.protected)
+                .filter(|addon| !in_process_addon::is_in_process(&addon.transport_url))
+                .cloned()
+                .collect_vec();
+
+            let updates = join_all(installed.iter().map(check)).await;
+            let updates = installed
+                .iter()
+                .map(|addon| addon.transport_url.to_owned())
+                .zip(updates)
+                .filter_map(|(url, update)| update.map(|update| (url, update)))
+                .collect::<HashMap<_, _>>();
+
+            info!("{} installed addons have an update", updates.len());
+
+            ADDON_UPDATES_STATE.write().updates = updates;
+        }
+    });
+}
+
+/// Installs the latest descriptor of the addon in place of the installed one
+pub fn upgrade(transport_url: &Url) -> bool {
+    let Some(update) = ADDON_UPDATES_STATE.write().updates.remove(transport_url) else {
+        return false;
+    };
+
+    let Some(flags) = CTX_STATE
+        .read_inner()
+        .addons
+        .iter()
+        .find(|addon| addon.transport_url == *transport_url)
+        .map(|addon| addon.flags.to_owned())
+    else {
+        return false;
+    };
+
+    // Installing with the same transport url replaces the addon where it is
+    models::ctx::install_addon(Descriptor {
+        transport_url: transport_url.to_owned(),
+        flags,
+        ..update.descriptor
+    });
+
+    true
+}
+
+/// Drops the updates of addons that were uninstalled or upgraded since the last check
+fn prune() {
+    let stale = {
+        let ctx = CTX_STATE.read_inner();
+        let state = ADDON_UPDATES_STATE.read_inner();
+
+        state
+            .updates
+            .iter()
+            .filter(|(url, update)| {
+                ctx.addons
+                    .iter()
+                    .find(|addon| addon.transport_url == **url)
+                    .is_none_or(|addon| {
+                        changes(&addon.manifest, &update.descriptor.manifest).is_empty()
+                    })
+            })
+            .map(|(url, _)| url.to_owned())
+            .collect_vec()
+    };
+
+    if !stale.is_empty() {
+        let mut state = ADDON_UPDATES_STATE.write();
+        stale.iter().for_each(|url| {
+            state.updates.remove(url);
+        });
+    }
+}
+
+async fn check(installed: &Descriptor) -> Option<AddonUpdate> {
+    let descriptor = addon_config::fetch_descriptor(&installed.transport_url)
+        .await
+        .map_err(|e| {
+            warn!(
+                "Failed to check {} for updates: {e}",
+                installed.transport_url
+            )
+        })
+        .ok()?;
+
+    let changes = changes(&installed.manifest, &descriptor.manifest);
+
+    match changes.is_empty() {
+        true => None,
+        false => Some(AddonUpdate {
+            descriptor,
+            changes,
+        }),
+    }
+}
+
+fn changes(installed: &Manifest, latest: &Manifest) -> Vec<AddonChange> {
+    let mut changes = vec![];
+
+    if installed.version != latest.version {
+        changes.push(AddonChange::Version(
+            installed.version.to_string(),
+            latest.version.to_string(),
+        ));
+    }
+
+    let resources = |manifest: &Manifest| {
+        manifest
+            .resources
+            .iter()
+            .map(|resource| resource.name().to_owned())
+            .collect_vec()
+    };
+
+    let catalogs = |manifest: &Manifest| {
+        manifest
+            .catalogs
+            .iter()
+            .map(|catalog| {
+                let name = catalog.name.as_deref().unwrap_or(&catalog.id);
+                format!("{name} ({})", catalog.r#type)
+            })
+            .collect_vec()
+    };
+
+    let parts = [
+        (
+            ManifestPart::Resource,
+            resources(installed),
+            resources(latest),
+        ),
+        (
+            ManifestPart::Type,
+            installed.types.to_owned(),
+            latest.types.to_owned(),
+        ),
+        (ManifestPart::Catalog, catalogs(installed), catalogs(latest)),
+    ];
+
+    for (part, installed, latest) in parts {
+        changes.extend(
+            latest
+                .iter()
+                .filter(|value| !installed.contains(value))
+                .map(|value| AddonChange::Added(part, value.to_owned())),
+        );
+
+        changes.extend(
+            installed
+                .iter()
+                .filter(|value| !latest.contains(value))
+                .map(|value| AddonChange::Removed(part, value.to_owned())),
+        );
+    }
+
+    changes
+}
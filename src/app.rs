@@ -1,4 +1,4 @@
-use std::{path::Path, process::Child};
+use std::path::{Path, PathBuf};
 
 use adw::prelude::*;
 use ashpd::{
@@ -24,34 +24,55 @@ use relm4::{
 use rust_i18n::t;
 use shellexpand::tilde;
 use stremio_core_losange::{
-    core, models, stremio_core::types::addon::ResourceRequest, types::stream::Stream,
+    cast::Renderer,
+    core, in_process_addon,
+    local_addon::{self, LocalAddon},
+    local_streams::LocalStreamsAddon,
+    models::{self, ctx::CTX_STATE},
+    stremio_core::types::{addon::ResourceRequest, resource::StreamSource},
+    types::stream::Stream,
+    watch_party::{PartyEvent, PartyMessage, WatchParty},
 };
+use tokio::sync::{mpsc::UnboundedReceiver, OnceCell};
 use tracing::error;
 use url::Url;
 
 use crate::{
+    addon_updates,
+    common::media,
     components::{header_menu::HeaderMenu, spinner::Spinner},
     constants::{APP_ID, APP_NAME},
+    dbus, deep_link,
     dialogs::{
         about::AboutDialog,
+        cast::{CastDialog, CastDialogInput},
         login::{LoginDialog, LoginDialogInput},
+        open::{OpenDialog, OpenDialogInput},
         preferences::{PreferencesDialog, PreferencesDialogInput},
         shortcuts::ShortcutsDialog,
+        torrent_files::{TorrentFilesDialog, TorrentFilesDialogInput},
+        watch_party::{WatchPartyDialog, WatchPartyDialogInput},
     },
+    downloads,
     mpris::MPris,
     pages::{
         addon::{AddonPage, AddonPageInput},
         addons::Addons,
         details::{DetailsPage, DetailsPageInput},
         discover::{DiscoverPage, DiscoverPageInput},
+        downloads::Downloads,
         home::HomePage,
         library::LibraryPage,
         player::{Player, PlayerInput},
         search::{SearchPage, SearchPageInput},
     },
-    server, Args, APP_BROKER,
+    remote::RemoteControl,
+    server::{self, Server, ServerProcessState, SERVER_PROCESS_STATE},
+    server_urls, APP_BROKER,
 };
 
+static CORE_INITIALIZED: OnceCell<()> = OnceCell::const_new();
+
 #[derive(Debug)]
 pub enum AppMsg {
     Toast((String, u32)),
@@ -59,14 +80,35 @@ pub enum AppMsg {
     OpenSearch(Option<String>),
     OpenDiscover(Option<ResourceRequest>),
     OpenDetails((String, String)),
+    OpenVideo((String, String, String)),
+    OpenLibrary,
     OpenAddons,
+    OpenDownloads,
     OpenAddon(Url),
     OpenStream(Box<Stream>),
+    OpenMedia(String),
+    OpenLocation(String),
     OpenPreferences(Option<&'static str>),
     NavigateBack,
     MediaStatus(bool),
     MediaMetadata((String, Option<Url>)),
     OpenExternal(String),
+    RemoteControlChanged,
+    OpenWatchParty,
+    HostWatchParty,
+    JoinWatchParty(String),
+    LeaveWatchParty,
+    WatchPartyEvent(PartyEvent),
+    WatchPartySend(PartyMessage),
+    OpenCast(Option<Box<Stream>>),
+    Cast((Option<Box<Stream>>, Box<Renderer>)),
+    OpenExternalPlayer(Box<Stream>),
+    OpenPlaylist((Vec<Stream>, usize)),
+    RestartServer,
+    ReloadServer,
+    NodeRuntimeChanged(Option<String>),
+    ServerFailover(Url),
+    ScanLocalFiles,
 }
 
 pub struct App {
@@ -81,12 +123,21 @@ pub struct App {
     details_page: AsyncController<DetailsPage>,
     addons_page: AsyncController<Addons>,
     addon_page: Controller<AddonPage>,
+    downloads_page: Controller<Downloads>,
     player_page: Controller<Player>,
     login_dialog: Controller<LoginDialog>,
+    open_dialog: Controller<OpenDialog>,
     preferences_dialog: Controller<PreferencesDialog>,
     about_dialog: Controller<AboutDialog>,
     shortcuts_dialog: Controller<ShortcutsDialog>,
-    server_process: Option<Child>,
+    watch_party_dialog: Controller<WatchPartyDialog>,
+    cast_dialog: Controller<CastDialog>,
+    torrent_files_dialog: Controller<TorrentFilesDialog>,
+    watch_party: Option<WatchParty>,
+    server: Option<Server>,
+    node_error: Option<String>,
+    settings: gio::Settings,
+    remote_control: RemoteControl,
     mpris: MPris,
     inhibit_request: Option<Request<()>>,
 }
@@ -95,13 +146,14 @@ relm4::new_action_group!(pub(super) WindowActionGroup, "win");
 relm4::new_stateless_action!(pub(super) LoginAction, WindowActionGroup, "login");
 relm4::new_stateless_action!(pub(super) LogoutAction, WindowActionGroup, "logout");
 relm4::new_stateless_action!(pub(super) SearchAction, WindowActionGroup, "search");
+relm4::new_stateless_action!(pub(super) OpenAction, WindowActionGroup, "open");
 relm4::new_stateless_action!(pub(super) PreferencesAction, WindowActionGroup, "preferences");
 relm4::new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "shortcuts");
 relm4::new_stateless_action!(pub(super) AboutAction, WindowActionGroup, "about");
 
 #[relm4::component(async pub)]
 impl AsyncComponent for App {
-    type Init = Args;
+    type Init = ();
     type Input = AppMsg;
     type Output = ();
     type CommandOutput = ();
@@ -166,6 +218,16 @@ impl AsyncComponent for App {
                             set_content = &gtk::Box {
                                 set_orientation: gtk::Orientation::Vertical,
 
+                                adw::Banner {
+                                    set_button_label: Some(&t!("choose_node")),
+                                    connect_button_clicked => AppMsg::OpenPreferences(Some("server")),
+
+                                    #[watch]
+                                    set_title: model.node_error.as_deref().unwrap_or_default(),
+                                    #[watch]
+                                    set_revealed: model.node_error.is_some(),
+                                },
+
                                 #[local_ref]
                                 view_stack -> adw::ViewStack {
                                     add_titled_with_icon[Some("home"), &t!("home"), "go-home-symbolic"] = model.home_page.widget(),
@@ -185,6 +247,7 @@ impl AsyncComponent for App {
                     add = model.details_page.widget(),
                     add = model.addons_page.widget(),
                     add = model.addon_page.widget(),
+                    add = model.downloads_page.widget(),
                     add = model.player_page.widget(),
                 }
             }
@@ -216,12 +279,12 @@ impl AsyncComponent for App {
     }
 
     async fn init(
-        args: Self::Init,
+        _init: Self::Init,
         root: Self::Root,
         sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self> {
         Self::initialize_core().await;
-        let server_process = Self::initialize_server().await;
+        let server = Self::initialize_server().await;
 
         models::ctx::sync_with_api();
 
@@ -237,12 +300,29 @@ impl AsyncComponent for App {
         let details_page = DetailsPage::builder().launch(()).detach();
         let addons_page = Addons::builder().launch(()).detach();
         let addon_page = AddonPage::builder().launch(()).detach();
+        let downloads_page = Downloads::builder().launch(()).detach();
         let player_page = Player::builder().launch(()).detach();
 
         let login_dialog = LoginDialog::builder().launch(()).detach();
+        let open_dialog = OpenDialog::builder().launch(()).detach();
         let preferences_dialog = PreferencesDialog::builder().launch(()).detach();
         let about_dialog = AboutDialog::builder().launch(()).detach();
         let shortcuts_dialog = ShortcutsDialog::builder().launch(()).detach();
+        let watch_party_dialog = WatchPartyDialog::builder().launch(()).detach();
+        let cast_dialog = CastDialog::builder().launch(()).detach();
+        let torrent_files_dialog = TorrentFilesDialog::builder().launch(()).detach();
+
+        let settings = gio::Settings::new(APP_ID);
+        let mut remote_control = RemoteControl::new(player_page.sender().clone());
+        remote_control.restart();
+
+        server_urls::start_monitor();
+        addon_updates::start_monitor();
+
+        let node_error = Self::node_error(&SERVER_PROCESS_STATE.read_inner());
+        SERVER_PROCESS_STATE.subscribe(sender.input_sender(), |state| {
+            AppMsg::NodeRuntimeChanged(Self::node_error(state))
+        });
 
         let mpris = MPris::new(APP_ID, APP_NAME).await;
         let player_sender = player_page.sender().clone();
@@ -268,12 +348,21 @@ impl AsyncComponent for App {
             details_page,
             addons_page,
             addon_page,
+            downloads_page,
             player_page,
             login_dialog,
+            open_dialog,
             preferences_dialog,
             about_dialog,
             shortcuts_dialog,
-            server_process,
+            watch_party_dialog,
+            cast_dialog,
+            torrent_files_dialog,
+            watch_party: None,
+            server,
+            node_error,
+            settings,
+            remote_control,
             mpris,
             inhibit_request: None,
         };
@@ -304,6 +393,13 @@ impl AsyncComponent for App {
             })
         };
 
+        let open_action = {
+            let sender = model.open_dialog.sender().clone();
+            RelmAction::<OpenAction>::new_stateless(move |_| {
+                sender.emit(OpenDialogInput::Open);
+            })
+        };
+
         let preferences_action = {
             let sender = model.preferences_dialog.sender().clone();
             RelmAction::<PreferencesAction>::new_stateless(move |_| {
@@ -328,6 +424,7 @@ impl AsyncComponent for App {
         actions.add_action(login_action);
         actions.add_action(logout_action);
         actions.add_action(search_action);
+        actions.add_action(open_action);
         actions.add_action(preferences_action);
         actions.add_action(shortcuts_action);
         actions.add_action(about_action);
@@ -335,24 +432,34 @@ impl AsyncComponent for App {
 
         let app = relm4::main_application();
         app.set_accelerators_for_action::<SearchAction>(&["<Control>F"]);
+        app.set_accelerators_for_action::<OpenAction>(&["<Control>O"]);
         app.set_accelerators_for_action::<PreferencesAction>(&["<Control>comma"]);
         app.set_accelerators_for_action::<ShortcutsAction>(&["<Control>question"]);
 
         widgets.load_window_state();
 
-        if let Some(manifest_url) = args.open {
-            if let Ok(transport_url) = Url::parse(&manifest_url.replace("stremio://", "https://")) {
-                sender.input_sender().emit(AppMsg::OpenAddon(transport_url));
-            }
+        {
+            let sender = sender.input_sender().clone();
+            model.settings.connect_changed(None, move |_, key| {
+                if key.starts_with("remote-control") {
+                    sender.emit(AppMsg::RemoteControlChanged);
+                }
+            });
+        }
+
+        if let Some(connection) = relm4::main_application().dbus_connection() {
+            dbus::register(&connection, model.player_page.sender().clone());
         }
 
+        sender.input(AppMsg::ScanLocalFiles);
+
         AsyncComponentParts { model, widgets }
     }
 
     async fn update(
         &mut self,
         message: Self::Input,
-        _sender: AsyncComponentSender<Self>,
+        sender: AsyncComponentSender<Self>,
         root: &Self::Root,
     ) {
         match message {
@@ -382,17 +489,51 @@ impl AsyncComponent for App {
                 self.details_page.emit(DetailsPageInput::Load(item));
                 self.navigate("details");
             }
+            AppMsg::OpenVideo(video) => {
+                self.details_page.emit(DetailsPageInput::LoadVideo(video));
+                self.navigate("details");
+            }
+            AppMsg::OpenLibrary => {
+                self.navigate_tab("library");
+            }
             AppMsg::OpenAddons => {
                 self.navigate("addons");
             }
+            AppMsg::OpenDownloads => {
+                self.navigate("downloads");
+            }
             AppMsg::OpenAddon(transport_url) => {
                 self.addon_page.emit(AddonPageInput::Load(transport_url));
                 self.navigate("addon");
             }
-            AppMsg::OpenStream(stream) => {
-                self.player_page.emit(PlayerInput::Load(stream));
-                self.navigate("player");
+            AppMsg::OpenStream(stream) => match stream.source {
+                // The dialog closes itself and plays the torrent when it has a single video
+                StreamSource::Torrent { file_idx: None, .. } => {
+                    self.torrent_files_dialog
+                        .emit(TorrentFilesDialogInput::Open(stream));
+                }
+                _ => {
+                    self.player_page.emit(PlayerInput::Load(stream));
+                    self.navigate("player");
+                }
+            },
+            AppMsg::OpenLocation(location) => {
+                let message = match Url::parse(&location) {
+                    Ok(url) if url.scheme() == "stremio" => deep_link::route(&url),
+                    _ => Some(AppMsg::OpenMedia(location)),
+                };
+
+                if let Some(message) = message {
+                    sender.input(message);
+                }
             }
+            AppMsg::OpenMedia(location) => match media::resolve(&location) {
+                Some(stream) => sender.input(AppMsg::OpenStream(Box::new(stream))),
+                None => {
+                    error!("Failed to open {location}");
+                    sender.input(AppMsg::Toast((t!("error_open").to_string(), 3)));
+                }
+            },
             AppMsg::OpenPreferences(name) => {
                 self.preferences_dialog
                     .emit(PreferencesDialogInput::Open(name));
@@ -415,6 +556,106 @@ impl AsyncComponent for App {
             AppMsg::OpenExternal(url) => {
                 Self::open_external(root, url).await;
             }
+            AppMsg::RemoteControlChanged => {
+                self.remote_control.restart();
+            }
+            AppMsg::OpenWatchParty => {
+                self.watch_party_dialog.emit(WatchPartyDialogInput::Open);
+            }
+            AppMsg::HostWatchParty => {
+                self.watch_party_dialog
+                    .emit(WatchPartyDialogInput::Connecting);
+
+                let result = relm4::spawn(WatchParty::host()).await;
+                self.start_watch_party(result.unwrap_or_else(|e| Err(std::io::Error::other(e))));
+            }
+            AppMsg::JoinWatchParty(code) => {
+                self.watch_party_dialog
+                    .emit(WatchPartyDialogInput::Connecting);
+
+                let result = relm4::spawn(async move { WatchParty::join(&code).await }).await;
+                self.start_watch_party(result.unwrap_or_else(|e| Err(std::io::Error::other(e))));
+            }
+            AppMsg::LeaveWatchParty => {
+                self.watch_party = None;
+                self.watch_party_dialog
+                    .emit(WatchPartyDialogInput::Update(None));
+            }
+            AppMsg::WatchPartyEvent(event) => match event {
+                PartyEvent::Message(PartyMessage::Stream { stream }) => {
+                    self.player_page.emit(PlayerInput::SyncStream(stream));
+                    self.navigate("player");
+                }
+                PartyEvent::Message(PartyMessage::Playback { paused, time, rate }) => {
+                    self.player_page
+                        .emit(PlayerInput::SyncPlayback { paused, time, rate });
+                }
+                PartyEvent::Message(_) => {}
+                PartyEvent::PeersChanged(peers) => {
+                    if let Some(party) = &self.watch_party {
+                        self.watch_party_dialog
+                            .emit(WatchPartyDialogInput::Update(Some((
+                                party.code.to_owned(),
+                                peers,
+                            ))));
+
+                        // Bring newcomers up to speed with what is currently playing
+                        self.player_page.emit(PlayerInput::ShareState);
+                    }
+                }
+                PartyEvent::Closed => {
+                    if self.watch_party.take().is_some() {
+                        self.watch_party_dialog
+                            .emit(WatchPartyDialogInput::Update(None));
+                        sender.input(AppMsg::Toast((t!("watch_party_closed").to_string(), 3)));
+                    }
+                }
+            },
+            AppMsg::WatchPartySend(message) => {
+                if let Some(party) = &self.watch_party {
+                    party.send(message);
+                }
+            }
+            AppMsg::OpenCast(stream) => {
+                self.cast_dialog.emit(CastDialogInput::Open(stream));
+            }
+            AppMsg::Cast((stream, renderer)) => {
+                if let Some(stream) = stream {
+                    self.player_page.emit(PlayerInput::Load(stream));
+                }
+
+                self.player_page.emit(PlayerInput::Cast(renderer));
+                self.navigate("player");
+            }
+            AppMsg::OpenExternalPlayer(stream) => {
+                self.player_page.emit(PlayerInput::LoadExternal(stream));
+            }
+            AppMsg::OpenPlaylist((streams, index)) => {
+                self.player_page
+                    .emit(PlayerInput::LoadPlaylist((streams, index)));
+                self.navigate("player");
+            }
+            AppMsg::RestartServer => match &self.server {
+                Some(server) => server.restart(),
+                None => self.server = Self::start_server().await,
+            },
+            AppMsg::ReloadServer => {
+                self.server = None;
+                self.server = Self::start_server().await;
+            }
+            AppMsg::NodeRuntimeChanged(error) => {
+                self.node_error = error;
+            }
+            AppMsg::ServerFailover(url) => {
+                if self.settings.boolean("server-failover") {
+                    let message = t!("server_failover_switched", url = url.as_str()).to_string();
+                    server_urls::select(url);
+                    sender.input(AppMsg::Toast((message, 3)));
+                }
+            }
+            AppMsg::ScanLocalFiles => {
+                Self::scan_local_files();
+            }
         }
     }
 
@@ -423,36 +664,140 @@ impl AsyncComponent for App {
             .save_window_state()
             .expect("Failed to save window state");
 
-        if let Some(mut process) = self.server_process.take() {
-            let _ = process.kill();
-        }
+        self.remote_control.stop();
+
+        self.server = None;
     }
 }
 
 impl App {
-    async fn initialize_core() {
+    /// Only the first call initializes, the search provider can need the core before the window
+    pub async fn initialize_core() {
+        CORE_INITIALIZED
+            .get_or_init(|| async {
+                let settings = gio::Settings::new(APP_ID);
+                let storage_location = settings.string("storage-location");
+
+                let expanded_path = tilde(&storage_location).to_string();
+                let data_location = Path::new(&expanded_path);
+
+                core::initialize(data_location).await;
+                in_process_addon::register(LocalAddon);
+                in_process_addon::register(LocalStreamsAddon);
+
+                let disabled_addons = settings
+                    .strv("disabled-addons")
+                    .iter()
+                    .filter_map(|url| Url::parse(url.as_str()).ok())
+                    .collect();
+
+                models::ctx::set_disabled_addons(disabled_addons);
+                downloads::initialize(data_location);
+            })
+            .await;
+    }
+
+    async fn initialize_server() -> Option<Server> {
+        let settings = gio::Settings::new(APP_ID);
+        let autostart = settings.boolean("autostart-server");
+
+        if autostart {
+            return Self::start_server().await;
+        }
+
+        None
+    }
+
+    async fn start_server() -> Option<Server> {
         let settings = gio::Settings::new(APP_ID);
         let storage_location = settings.string("storage-location");
 
         let expanded_path = tilde(&storage_location).to_string();
         let data_location = Path::new(&expanded_path);
 
-        core::initialize(data_location).await;
+        if settings.boolean("native-engine") {
+            return Some(Server::start_native(data_location));
+        }
+
+        let node_path = settings.string("node-path");
+
+        server::initialize(data_location, &node_path)
+            .await
+            .map_err(|e| error!("Failed to initialize server: {e}"))
+            .ok()
     }
 
-    async fn initialize_server() -> Option<Child> {
+    fn scan_local_files() {
         let settings = gio::Settings::new(APP_ID);
-        let autostart = settings.boolean("autostart-server");
         let storage_location = settings.string("storage-location");
+        let data_location = PathBuf::from(tilde(&storage_location).to_string());
 
-        let expanded_path = tilde(&storage_location).to_string();
-        let data_location = Path::new(&expanded_path);
+        let folders = settings
+            .strv("local-folders")
+            .iter()
+            .map(|folder| PathBuf::from(tilde(folder.as_str()).to_string()))
+            .collect::<Vec<_>>();
 
-        if autostart {
-            return server::initialize(data_location).await.ok();
+        relm4::spawn(async move {
+            let enabled = !folders.is_empty();
+
+            if let Err(e) = local_addon::scan(&data_location, folders).await {
+                error!("Failed to scan local folders: {e}");
+                return;
+            }
+
+            let descriptor = in_process_addon::descriptor(&LocalAddon);
+            let installed = CTX_STATE
+                .read_inner()
+                .addons
+                .iter()
+                .find(|addon| addon.transport_url == descriptor.transport_url)
+                .cloned();
+
+            // The addon is kept out of the synced profile, see in_process_addon::strip_synced
+            match (enabled, installed) {
+                (true, None) => models::ctx::install_addon(descriptor),
+                (false, Some(installed)) => models::ctx::uninstall_addon(installed),
+                _ => {}
+            }
+        });
+    }
+
+    fn node_error(state: &ServerProcessState) -> Option<String> {
+        match &state.runtime {
+            Some(Err(e)) => Some(e.to_string()),
+            _ => None,
         }
+    }
 
-        None
+    fn start_watch_party(
+        &mut self,
+        result: std::io::Result<(WatchParty, UnboundedReceiver<PartyEvent>)>,
+    ) {
+        match result {
+            Ok((party, mut events)) => {
+                let peers = usize::from(!party.hosting);
+                self.watch_party_dialog
+                    .emit(WatchPartyDialogInput::Update(Some((
+                        party.code.to_owned(),
+                        peers,
+                    ))));
+
+                self.watch_party = Some(party);
+
+                relm4::spawn(async move {
+                    while let Some(event) = events.recv().await {
+                        APP_BROKER.send(AppMsg::WatchPartyEvent(event));
+                    }
+                });
+            }
+            Err(e) => {
+                error!("Failed to start watch party: {e}");
+                self.watch_party_dialog
+                    .emit(WatchPartyDialogInput::Update(None));
+                APP_BROKER.send(AppMsg::Toast((t!("error_watch_party").to_string(), 3)));
+            }
+        }
     }
 
     fn navigate(&self, tag: &str) {
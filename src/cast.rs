@@ -0,0 +1,172 @@
+use std::{future::Future, io, sync::Arc, time::Duration};
+
+use relm4::{JoinHandle, SharedState};
+use rust_i18n::t;
+use stremio_core_losange::cast::{Renderer, TransportState};
+use tracing::{debug, error};
+use url::Url;
+
+use crate::{app::AppMsg, common::net, APP_BROKER};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default, Debug)]
+pub struct CastState {
+    pub renderer: Option<String>,
+    pub paused: bool,
+    pub time: f64,
+    pub duration: f64,
+}
+
+pub static CAST_STATE: SharedState<CastState> = SharedState::new();
+
+pub struct CastSession {
+    renderer: Arc<Renderer>,
+    task: JoinHandle<()>,
+    stopped: bool,
+}
+
+impl CastSession {
+    /// The previous session is stopped before loading, a renderer shared by both sessions would
+    /// otherwise receive the stop after the new media
+    pub fn start(
+        previous: Option<CastSession>,
+        renderer: Renderer,
+        url: Url,
+        title: String,
+        start_time: f64,
+    ) -> Self {
+        let stopping = previous.and_then(|mut previous| previous.stop());
+
+        {
+            let mut state = CAST_STATE.write();
+            state.renderer = Some(renderer.name.to_owned());
+            state.paused = false;
+            state.time = start_time;
+            state.duration = 0.0;
+        }
+
+        let renderer = Arc::new(renderer);
+        let url = reachable_url(url);
+
+        let task = relm4::spawn({
+            let renderer = renderer.clone();
+
+            async move {
+                if let Some(stopping) = stopping {
+                    let _ = stopping.await;
+                }
+
+                let result = async {
+                    renderer.load(&url, &title).await?;
+                    renderer.play().await
+                };
+
+                if let Err(e) = result.await {
+                    error!("Failed to cast to {}: {e}", renderer.name);
+                    CAST_STATE.write().renderer = None;
+                    APP_BROKER.send(AppMsg::Toast((t!("error_cast").to_string(), 3)));
+                    return;
+                }
+
+                // Not every renderer accepts a seek before the media is loaded
+                if start_time > 0.0 {
+                    if let Err(e) = renderer.seek(start_time).await {
+                        debug!("Failed to restore position on {}: {e}", renderer.name);
+                    }
+                }
+
+                let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+                loop {
+                    interval.tick().await;
+
+                    match renderer.playback_state().await {
+                        Ok(playback) => {
+                            let mut state = CAST_STATE.write();
+                            state.paused = playback.state != TransportState::Playing;
+                            state.time = playback.time;
+                            state.duration = playback.duration;
+                        }
+                        Err(e) => debug!("Failed to get playback state of {}: {e}", renderer.name),
+                    }
+                }
+            }
+        });
+
+        Self {
+            renderer,
+            task,
+            stopped: false,
+        }
+    }
+
+    pub fn play(&self) {
+        CAST_STATE.write().paused = false;
+        self.command(|renderer| async move { renderer.play().await });
+    }
+
+    pub fn pause(&self) {
+        CAST_STATE.write().paused = true;
+        self.command(|renderer| async move { renderer.pause().await });
+    }
+
+    pub fn seek(&self, time: f64) {
+        CAST_STATE.write().time = time;
+        self.command(move |renderer| async move { renderer.seek(time).await });
+    }
+
+    fn command<F, T>(&self, command: F)
+    where
+        F: FnOnce(Arc<Renderer>) -> T + Send + 'static,
+        T: Future<Output = io::Result<()>> + Send,
+    {
+        let renderer = self.renderer.clone();
+
+        relm4::spawn(async move {
+            let name = renderer.name.to_owned();
+
+            if let Err(e) = command(renderer).await {
+                error!("Failed to control {name}: {e}");
+            }
+        });
+    }
+
+    fn stop(&mut self) -> Option<JoinHandle<()>> {
+        if std::mem::replace(&mut self.stopped, true) {
+            return None;
+        }
+
+        self.task.abort();
+        CAST_STATE.write().renderer = None;
+
+        let renderer = self.renderer.clone();
+        Some(relm4::spawn(async move {
+            if let Err(e) = renderer.stop().await {
+                debug!("Failed to stop {}: {e}", renderer.name);
+            }
+        }))
+    }
+}
+
+impl Drop for CastSession {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn reachable_url(mut url: Url) -> Url {
+    // The streaming server usually listens on localhost, which means nothing to the renderer
+    let loopback = match url.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(address)) => address.is_loopback(),
+        Some(url::Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    };
+
+    if loopback {
+        let _ = url.set_ip_host(net::local_ip());
+    }
+
+    url
+}
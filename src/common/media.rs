@@ -0,0 +1,35 @@
+use std::{fs, path::PathBuf};
+
+use shellexpand::tilde;
+use stremio_core_losange::types::stream::Stream;
+use url::Url;
+
+pub fn resolve(input: &str) -> Option<Stream> {
+    let input = input.trim();
+    let url = Url::parse(input).ok();
+
+    let path = match &url {
+        Some(url) if url.scheme() == "file" => url.to_file_path().ok(),
+        Some(_) => None,
+        None => Some(PathBuf::from(tilde(input).to_string())),
+    };
+
+    if let Some(path) = path.and_then(|path| fs::canonicalize(path).ok()) {
+        if !path.is_file() {
+            return None;
+        }
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "torrent")
+        {
+            let bytes = fs::read(&path).ok()?;
+            return Stream::from_torrent_file(&bytes);
+        }
+
+        let url = Url::from_file_path(&path).ok()?;
+        return Stream::from_url(&url);
+    }
+
+    url.and_then(|url| Stream::from_url(&url))
+}
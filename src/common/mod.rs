@@ -2,6 +2,7 @@ pub mod format;
 pub mod image;
 pub mod language;
 pub mod layout;
+pub mod media;
 pub mod net;
 pub mod style;
 pub mod translate;
@@ -1,4 +1,9 @@
-use std::{path::Path, time::Duration};
+use std::{
+    io::ErrorKind,
+    net::{IpAddr, Ipv4Addr, UdpSocket},
+    path::Path,
+    time::Duration,
+};
 
 use http_cache_reqwest::{CACacheManager, Cache, CacheMode, HttpCache, HttpCacheOptions};
 use relm4::{
@@ -8,11 +13,27 @@ use relm4::{
 use reqwest::{Client, Response};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use shellexpand::tilde;
+use tokio::net::TcpListener;
 use url::Url;
 
 use crate::constants::APP_ID;
 
+const BIND_ATTEMPTS: u32 = 10;
+const BIND_RETRY_DELAY: Duration = Duration::from_millis(100);
+
 static CLIENT: OnceCell<ClientWithMiddleware> = OnceCell::new();
+static STREAMING_CLIENT: OnceCell<Client> = OnceCell::new();
+
+/// Client for long transfers, without the cache and the overall timeout of `fetch`
+pub fn streaming_client() -> &'static Client {
+    STREAMING_CLIENT.get_or_init(|| {
+        Client::builder()
+            .use_rustls_tls()
+            .connect_timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default()
+    })
+}
 
 pub async fn fetch(url: Url) -> anyhow::Result<Response> {
     let client = CLIENT.get_or_init(|| {
@@ -46,3 +67,30 @@ pub async fn fetch(url: Url) -> anyhow::Result<Response> {
 
     Ok(response)
 }
+
+pub fn local_ip() -> IpAddr {
+    // Connecting a UDP socket does not send anything, it only resolves the outgoing interface
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket
+                .connect((Ipv4Addr::new(192, 0, 2, 1), 80))
+                .map(|_| socket)
+        })
+        .and_then(|socket| socket.local_addr())
+        .map_or(IpAddr::V4(Ipv4Addr::LOCALHOST), |address| address.ip())
+}
+
+pub async fn bind(address: IpAddr, port: u16) -> std::io::Result<TcpListener> {
+    let mut attempts = 1;
+
+    // The previous listener may still be shutting down after a restart
+    loop {
+        match TcpListener::bind((address, port)).await {
+            Err(e) if e.kind() == ErrorKind::AddrInUse && attempts < BIND_ATTEMPTS => {
+                attempts += 1;
+                tokio::time::sleep(BIND_RETRY_DELAY).await;
+            }
+            result => return result,
+        }
+    }
+}
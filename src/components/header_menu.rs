@@ -5,7 +5,10 @@ use rust_i18n::t;
 use stremio_core_losange::models::ctx::CTX_STATE;
 
 use crate::{
-    app::{AboutAction, AppMsg, LoginAction, LogoutAction, PreferencesAction, ShortcutsAction},
+    app::{
+        AboutAction, AppMsg, LoginAction, LogoutAction, OpenAction, PreferencesAction,
+        ShortcutsAction,
+    },
     constants::APP_NAME,
     APP_BROKER,
 };
@@ -30,6 +33,9 @@ impl SimpleComponent for HeaderMenu {
             section! {
                 &t!("menu_login") => LoginAction,
             },
+            section! {
+                &t!("menu_open") => OpenAction,
+            },
             section! {
                 &t!("menu_preferences") => PreferencesAction,
                 &t!("menu_shortcuts") => ShortcutsAction,
@@ -50,6 +56,14 @@ impl SimpleComponent for HeaderMenu {
                 },
             },
 
+            gtk::Button {
+                set_icon_name: "folder-download-symbolic",
+                set_tooltip_text: Some(&t!("downloads")),
+                connect_clicked => move |_| {
+                    APP_BROKER.send(AppMsg::OpenDownloads);
+                },
+            },
+
             #[local_ref]
             menu_button -> gtk::MenuButton {
                 set_icon_name: "open-menu-symbolic",
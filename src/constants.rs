@@ -7,6 +7,9 @@ pub const CATALOG_ICON_SIZE: i32 = 24;
 pub const DETAILS_LOGO_SIZE: (i32, i32) = (80, 160);
 pub const SERVER_DOWNLOAD_ENDPOINT: &str = "https://dl.strem.io/server/vVERSION/desktop/server.js";
 pub const SERVER_VERSION: &str = "4.20.18";
+pub const NODE_MIN_VERSION: u32 = 10;
+// SHA-256 of server.js per version, versions missing here are refused
+pub const SERVER_CHECKSUMS: &[(&str, &str)] = &[];
 pub const COMMUNITY_MANIFESTS: &[&str] = &[
     "https://v3-cinemeta.strem.io/manifest.json",
     "https://stremio-addons.com/manifest.json",
@@ -15,6 +18,8 @@ pub const SUBTITLES_MIN_SIZE: u8 = 25;
 pub const SUBTITLES_MAX_SIZE: u8 = 175;
 pub const SUBTITLES_MIN_OFFSET: u8 = 0;
 pub const SUBTITLES_MAX_OFFSET: u8 = 100;
+pub const SEEK_MIN_DURATION: u8 = 1;
+pub const SEEK_MAX_DURATION: u8 = 60;
 pub const VOLUME_DEFAULT: f64 = 50.0;
 pub const VOLUME_MAX: f64 = 150.0;
 pub const VOLUME_STEP: f64 = 10.0;
@@ -0,0 +1,67 @@
+use relm4::{gtk::gio, Sender};
+use tracing::error;
+
+use crate::{app::AppMsg, pages::player::PlayerInput, APP_BROKER};
+
+const OBJECT_PATH: &str = "/xyz/timtimtim/Losange";
+const INTERFACE_NAME: &str = "xyz.timtimtim.Losange.Remote";
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="xyz.timtimtim.Losange.Remote">
+    <method name="OpenDetails">
+      <arg type="s" name="type" direction="in"/>
+      <arg type="s" name="id" direction="in"/>
+    </method>
+    <method name="Search">
+      <arg type="s" name="query" direction="in"/>
+    </method>
+    <method name="PlayUrl">
+      <arg type="s" name="url" direction="in"/>
+    </method>
+    <method name="PlayPause"/>
+    <method name="Seek">
+      <arg type="d" name="offset" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+pub fn register(connection: &gio::DBusConnection, player: Sender<PlayerInput>) {
+    let interface = gio::DBusNodeInfo::for_xml(INTERFACE_XML)
+        .ok()
+        .and_then(|node| node.lookup_interface(INTERFACE_NAME))
+        .expect("Failed to parse D-Bus interface");
+
+    let result = connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(move |_, _, _, _, method, parameters, invocation| {
+            let result = match method {
+                "OpenDetails" => parameters
+                    .get::<(String, String)>()
+                    .map(|(r#type, id)| APP_BROKER.send(AppMsg::OpenDetails((id, r#type)))),
+                "Search" => parameters
+                    .get::<(String,)>()
+                    .map(|(query,)| APP_BROKER.send(AppMsg::OpenSearch(Some(query)))),
+                "PlayUrl" => parameters
+                    .get::<(String,)>()
+                    .map(|(url,)| APP_BROKER.send(AppMsg::OpenLocation(url))),
+                "PlayPause" => parameters
+                    .get::<()>()
+                    .map(|_| player.emit(PlayerInput::PlayPause)),
+                "Seek" => parameters
+                    .get::<(f64,)>()
+                    .map(|(offset,)| player.emit(PlayerInput::SeekBy(offset * 1000.0))),
+                _ => None,
+            };
+
+            match result {
+                Some(()) => invocation.return_value(None),
+                None => invocation.return_error(gio::DBusError::InvalidArgs, method),
+            }
+        })
+        .build();
+
+    if let Err(e) = result {
+        error!("Failed to register D-Bus interface: {e}");
+    }
+}
@@ -0,0 +1,192 @@
+use percent_encoding::percent_decode_str;
+use stremio_core_losange::stremio_core::{
+    constants::CATALOG_RESOURCE_NAME,
+    types::addon::{ExtraValue, ResourcePath, ResourceRequest},
+};
+use url::{form_urlencoded, Url};
+
+use crate::app::AppMsg;
+
+const SCHEME: &str = "stremio";
+
+pub fn route(url: &Url) -> Option<AppMsg> {
+    if url.scheme() != SCHEME {
+        return None;
+    }
+
+    if url.path().ends_with("manifest.json") {
+        let transport_url = url.as_str().replacen("stremio://", "https://", 1);
+        return Url::parse(&transport_url).ok().map(AppMsg::OpenAddon);
+    }
+
+    // Stremio Web links carry the route in the fragment: stremio://#/detail/...
+    let path = url
+        .fragment()
+        .filter(|fragment| fragment.starts_with('/'))
+        .map(|fragment| fragment.to_owned())
+        .unwrap_or(url.path().to_owned());
+
+    let (path, query) = path.split_once('?').unwrap_or((&path, ""));
+
+    let query = url
+        .query_pairs()
+        .chain(form_urlencoded::parse(query.as_bytes()))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<Vec<_>>();
+
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .filter_map(|segment| percent_decode_str(segment).decode_utf8().ok())
+        .map(|segment| segment.to_string())
+        .collect::<Vec<_>>();
+
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [] | ["board"] => Some(AppMsg::OpenHome),
+        ["library"] => Some(AppMsg::OpenLibrary),
+        ["addons", ..] => Some(AppMsg::OpenAddons),
+        ["search"] => {
+            let search = query
+                .iter()
+                .find(|(key, _)| key == "search" || key == "query")
+                .map(|(_, value)| value.to_owned());
+
+            Some(AppMsg::OpenSearch(search))
+        }
+        ["discover"] => Some(AppMsg::OpenDiscover(None)),
+        ["discover", transport_url, r#type, id] => {
+            let base = Url::parse(transport_url).ok()?;
+
+            let extra = query
+                .iter()
+                .map(|(name, value)| ExtraValue {
+                    name: name.to_owned(),
+                    value: value.to_owned(),
+                })
+                .collect();
+
+            let path = ResourcePath {
+                resource: CATALOG_RESOURCE_NAME.to_owned(),
+                r#type: r#type.to_string(),
+                id: id.to_string(),
+                extra,
+            };
+
+            Some(AppMsg::OpenDiscover(Some(ResourceRequest::new(base, path))))
+        }
+        ["detail", r#type, id] => Some(AppMsg::OpenDetails((id.to_string(), r#type.to_string()))),
+        ["detail", r#type, id, video_id] => Some(AppMsg::OpenVideo((
+            id.to_string(),
+            r#type.to_string(),
+            video_id.to_string(),
+        ))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::route;
+    use crate::app::AppMsg;
+
+    fn route_str(url: &str) -> Option<AppMsg> {
+        route(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn details() {
+        assert!(matches!(
+            route_str("stremio:///detail/movie/tt0032138"),
+            Some(AppMsg::OpenDetails((id, r#type))) if id == "tt0032138" && r#type == "movie"
+        ));
+        assert!(matches!(
+            route_str("stremio://#/detail/movie/tt0032138"),
+            Some(AppMsg::OpenDetails((id, _))) if id == "tt0032138"
+        ));
+    }
+
+    #[test]
+    fn video() {
+        assert!(matches!(
+            route_str("stremio:///detail/series/tt0108778/tt0108778%3A1%3A2"),
+            Some(AppMsg::OpenVideo((id, r#type, video_id)))
+                if id == "tt0108778" && r#type == "series" && video_id == "tt0108778:1:2"
+        ));
+    }
+
+    #[test]
+    fn search() {
+        assert!(matches!(
+            route_str("stremio:///search?search=the%20wizard"),
+            Some(AppMsg::OpenSearch(Some(search))) if search == "the wizard"
+        ));
+        assert!(matches!(
+            route_str("stremio://#/search?query=oz"),
+            Some(AppMsg::OpenSearch(Some(search))) if search == "oz"
+        ));
+        assert!(matches!(
+            route_str("stremio:///search"),
+            Some(AppMsg::OpenSearch(None))
+        ));
+    }
+
+    #[test]
+    fn discover() {
+        assert!(matches!(
+            route_str("stremio:///discover"),
+            Some(AppMsg::OpenDiscover(None))
+        ));
+
+        let url = "stremio:///discover/https%3A%2F%2Fv3-cinemeta.strem.io%2Fmanifest.json/movie/top?genre=Drama";
+        let Some(AppMsg::OpenDiscover(Some(request))) = route_str(url) else {
+            panic!("Expected a discover request");
+        };
+        assert_eq!(
+            request.base.as_str(),
+            "https://v3-cinemeta.strem.io/manifest.json"
+        );
+        assert_eq!(request.path.resource, "catalog");
+        assert_eq!(request.path.r#type, "movie");
+        assert_eq!(request.path.id, "top");
+        assert!(matches!(
+            &request.path.extra[..],
+            [extra] if extra.name == "genre" && extra.value == "Drama"
+        ));
+    }
+
+    #[test]
+    fn sections() {
+        assert!(matches!(
+            route_str("stremio:///library"),
+            Some(AppMsg::OpenLibrary)
+        ));
+        assert!(matches!(
+            route_str("stremio:///addons"),
+            Some(AppMsg::OpenAddons)
+        ));
+        assert!(matches!(
+            route_str("stremio://#/addons/movie"),
+            Some(AppMsg::OpenAddons)
+        ));
+        assert!(matches!(route_str("stremio:///"), Some(AppMsg::OpenHome)));
+    }
+
+    #[test]
+    fn manifest() {
+        assert!(matches!(
+            route_str("stremio://v3-cinemeta.strem.io/manifest.json"),
+            Some(AppMsg::OpenAddon(url)) if url.as_str() == "https://v3-cinemeta.strem.io/manifest.json"
+        ));
+    }
+
+    #[test]
+    fn unknown() {
+        assert!(route_str("stremio:///settings/unknown").is_none());
+        assert!(route_str("stremio:///detail/movie").is_none());
+        assert!(route_str("https://www.strem.io/detail/movie/tt0032138").is_none());
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use relm4::{adw, css, gtk, Component, ComponentParts, ComponentSender, RelmWidgetExt};
+use rust_i18n::t;
+use stremio_core_losange::{cast::Renderer, types::stream::Stream};
+use tracing::error;
+
+use crate::{app::AppMsg, components::spinner::Spinner, APP_BROKER};
+
+const DISCOVERY_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Debug)]
+pub enum CastDialogInput {
+    Open(Option<Box<Stream>>),
+    Refresh,
+    Select(usize),
+}
+
+pub struct CastDialog {
+    stream: Option<Box<Stream>>,
+    renderers: Vec<Renderer>,
+    loading: bool,
+    list: gtk::ListBox,
+}
+
+#[relm4::component(pub)]
+impl Component for CastDialog {
+    type Init = ();
+    type Input = CastDialogInput;
+    type Output = ();
+    type CommandOutput = Vec<Renderer>;
+
+    view! {
+        adw::Dialog {
+            set_content_width: 400,
+            set_content_height: 400,
+            set_title: &t!("cast_to"),
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    pack_start = &gtk::Button {
+                        set_icon_name: "view-refresh-symbolic",
+                        set_tooltip_text: Some(&t!("refresh")),
+
+                        #[watch]
+                        set_sensitive: !model.loading,
+
+                        connect_clicked => CastDialogInput::Refresh,
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+
+                    #[transition = "Crossfade"]
+                    if model.loading {
+                        #[template]
+                        Spinner {}
+                    } else if model.renderers.is_empty() {
+                        adw::StatusPage {
+                            set_vexpand: true,
+                            set_icon_name: Some("video-display-symbolic"),
+                            set_title: &t!("cast_no_renderers"),
+                            set_description: Some(&t!("cast_no_renderers_description")),
+                        }
+                    } else {
+                        #[local_ref]
+                        list -> gtk::ListBox {
+                            add_css_class: css::classes::BOXED_LIST,
+                            set_valign: gtk::Align::Start,
+                            set_selection_mode: gtk::SelectionMode::None,
+
+                            connect_row_activated[sender] => move |_, row| {
+                                sender.input(CastDialogInput::Select(row.index() as usize));
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            stream: None,
+            renderers: vec![],
+            loading: false,
+            list: gtk::ListBox::default(),
+        };
+
+        let list = &model.list;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            CastDialogInput::Open(stream) => {
+                self.stream = stream;
+
+                let window = relm4::main_application().active_window();
+                root.present(window.as_ref());
+
+                sender.input(CastDialogInput::Refresh);
+            }
+            CastDialogInput::Refresh => {
+                self.loading = true;
+
+                sender.oneshot_command(async {
+                    stremio_core_losange::cast::discover(DISCOVERY_DURATION)
+                        .await
+                        .map_err(|e| error!("Failed to discover renderers: {e}"))
+                        .unwrap_or_default()
+                });
+            }
+            CastDialogInput::Select(index) => {
+                if let Some(renderer) = self.renderers.get(index) {
+                    let renderer = Box::new(renderer.to_owned());
+                    APP_BROKER.send(AppMsg::Cast((self.stream.take(), renderer)));
+                    root.close();
+                }
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        renderers: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        self.list.remove_all();
+
+        for renderer in &renderers {
+            let row = adw::ActionRow::builder()
+                .title(&renderer.name)
+                .subtitle(renderer.location.host_str().unwrap_or_default())
+                .activatable(true)
+                .build();
+
+            row.add_prefix(&gtk::Image::from_icon_name("video-display-symbolic"));
+            self.list.append(&row);
+        }
+
+        self.renderers = renderers;
+        self.loading = false;
+    }
+}
@@ -1,4 +1,8 @@
 pub mod about;
+pub mod cast;
 pub mod login;
+pub mod open;
 pub mod preferences;
 pub mod shortcuts;
+pub mod torrent_files;
+pub mod watch_party;
@@ -0,0 +1,136 @@
+use adw::prelude::*;
+use gtk::gio;
+use relm4::{adw, css, gtk, Component, ComponentParts, ComponentSender, RelmWidgetExt};
+use rust_i18n::t;
+
+use crate::{app::AppMsg, APP_BROKER};
+
+#[derive(Debug)]
+pub enum OpenDialogInput {
+    Open,
+    ChooseFile,
+    FileChosen(String),
+    Submit,
+}
+
+pub struct OpenDialog {
+    location: adw::EntryRow,
+    file_dialog: gtk::FileDialog,
+}
+
+#[relm4::component(pub)]
+impl Component for OpenDialog {
+    type Init = ();
+    type Input = OpenDialogInput;
+    type Output = ();
+    type CommandOutput = ();
+
+    view! {
+        adw::Dialog {
+            set_content_width: 450,
+            set_title: &t!("open"),
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+                    set_spacing: 24,
+
+                    gtk::ListBox {
+                        add_css_class: css::classes::BOXED_LIST,
+
+                        #[local_ref]
+                        location -> adw::EntryRow {
+                            set_title: &t!("open_location"),
+                            set_activates_default: false,
+                            connect_entry_activated => OpenDialogInput::Submit,
+
+                            add_suffix = &gtk::Button {
+                                add_css_class: css::classes::FLAT,
+                                set_valign: gtk::Align::Center,
+                                set_icon_name: "document-open-symbolic",
+                                set_tooltip_text: Some(&t!("open_file")),
+                                connect_clicked => OpenDialogInput::ChooseFile,
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        add_css_class: css::classes::DIM_LABEL,
+                        set_label: &t!("open_description"),
+                        set_wrap: true,
+                    },
+
+                    gtk::Button {
+                        set_css_classes: &[css::classes::PILL, css::classes::SUGGESTED_ACTION],
+                        set_halign: gtk::Align::Center,
+                        set_label: &t!("play"),
+
+                        connect_clicked => OpenDialogInput::Submit,
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let location = adw::EntryRow::default();
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(t!("open_file"))
+            .modal(true)
+            .build();
+
+        let model = Self {
+            location,
+            file_dialog,
+        };
+
+        let location = &model.location;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            OpenDialogInput::Open => {
+                self.location.set_text("");
+
+                let window = relm4::main_application().active_window();
+                root.present(window.as_ref());
+            }
+            OpenDialogInput::ChooseFile => {
+                let window = relm4::main_application().active_window();
+
+                self.file_dialog
+                    .open(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                        if let Some(path) = result.ok().and_then(|file| file.path()) {
+                            let path = path.to_string_lossy().to_string();
+                            sender.input(OpenDialogInput::FileChosen(path));
+                        }
+                    });
+            }
+            OpenDialogInput::FileChosen(path) => {
+                self.location.set_text(&path);
+                sender.input(OpenDialogInput::Submit);
+            }
+            OpenDialogInput::Submit => {
+                let location = self.location.text().trim().to_owned();
+
+                if !location.is_empty() {
+                    APP_BROKER.send(AppMsg::OpenMedia(location));
+                    root.close();
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,31 @@
 use crate::{
+    app::AppMsg,
     common::style::ColorHexExt,
     constants::{
-        APP_ID, SUBTITLES_MAX_OFFSET, SUBTITLES_MAX_SIZE, SUBTITLES_MIN_OFFSET, SUBTITLES_MIN_SIZE,
+        APP_ID, SEEK_MAX_DURATION, SEEK_MIN_DURATION, SUBTITLES_MAX_OFFSET, SUBTITLES_MAX_SIZE,
+        SUBTITLES_MIN_OFFSET, SUBTITLES_MIN_SIZE,
     },
+    remote,
+    server::{ServerStatus, SERVER_PROCESS_STATE},
+    server_urls::{self, ServerUrlStatus, SERVER_URLS_STATE},
+    APP_BROKER,
 };
 use adw::prelude::*;
 use gtk::gio;
 use relm4::{
     adw, css,
-    gtk::{self, gdk},
+    gtk::{self, gdk, glib},
     Component, ComponentParts, ComponentSender,
 };
 use rust_i18n::t;
-use stremio_core_losange::models::{self, ctx::CTX_STATE, server::SERVER_STATE};
+use stremio_core_losange::{
+    models::{
+        self,
+        ctx::CTX_STATE,
+        server::{ServerState, TorrentProfile, SERVER_STATE},
+    },
+    stremio_core::types::streaming_server::Settings,
+};
 use url::Url;
 
 #[derive(Debug)]
@@ -25,18 +38,60 @@ pub enum PreferencesDialogInput {
     DetailsContentColorsChanged(bool),
     DetailsContentLogoChanged(bool),
     PlayerResizeWindow(bool),
+    PlayerExternalCommandChanged(String),
+    LocalFolderChoose,
+    LocalFolderAdd(String),
+    LocalFolderRemove(String),
     PlayerSubtitlesSizeChanged(f64),
     PlayerSubtitlesOffsetChanged(f64),
     PlayerSubtitlesColor(String),
     PlayerSubtitlesOultineColor(String),
     PlayerAutoPlayChanged(bool),
-    ServerUrlChanged(String),
+    PlayerPrebufferNextChanged(bool),
+    PlayerSeekDurationChanged(f64),
+    PlayerSeekShortDurationChanged(f64),
+    ServerUrlAdd(String),
+    ServerUrlRemove(Url),
+    ServerUrlSelect(Url),
+    ServerFailoverChanged(bool),
     ServerEnabledChanged(bool),
+    ServerRestart,
+    ServerNativeEngineChanged(bool),
+    NodeChoose,
+    NodePathChanged(String),
+    ServerCacheSizeChanged(u32),
+    ServerCacheRootChanged(String),
+    ServerTorrentProfileChanged(u32),
+    ServerTranscodeProfileChanged(u32),
+    ServerProxyStreamsChanged(bool),
+    RemoteControlChanged(bool),
+    RemoteControlPortChanged(f64),
+    RemoteControlCopyAddress,
+    RemoteControlResetToken,
 }
 
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+const CACHE_SIZES: &[Option<f64>] = &[
+    Some(0.0),
+    Some(2.0 * GB),
+    Some(5.0 * GB),
+    Some(10.0 * GB),
+    None,
+];
+
 pub struct PreferencesDialog {
     settings: gio::Settings,
     subtitles_color_dialog: gtk::ColorDialog,
+    remote_address: String,
+    cache_root: String,
+    cache_root_row: adw::EntryRow,
+    transcode_profiles: Vec<String>,
+    transcode_row: adw::ComboRow,
+    transcode_handler: glib::SignalHandlerId,
+    server_list: gtk::ListBox,
+    server_add_row: adw::EntryRow,
+    server_rows: Vec<(Url, bool, Option<ServerUrlStatus>)>,
+    local_folders_list: gtk::ListBox,
 }
 
 #[relm4::component(pub)]
@@ -122,7 +177,26 @@ impl Component for PreferencesDialog {
                             sender.input(PreferencesDialogInput::PlayerResizeWindow(value));
                         }
                     },
-                }
+                    adw::EntryRow {
+                        set_title: &t!("external_player_command"),
+                        set_text: &model.settings.string("external-player"),
+                        connect_text_notify[sender] => move |row| {
+                            let value = row.text().to_string();
+                            sender.input(PreferencesDialogInput::PlayerExternalCommandChanged(value));
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &t!("local_files"),
+                    set_description: Some(&t!("local_files_description")),
+
+                    #[local_ref]
+                    local_folders_list -> gtk::ListBox {
+                        add_css_class: css::classes::BOXED_LIST,
+                        set_selection_mode: gtk::SelectionMode::None,
+                    },
+                },
             },
 
             add = &adw::PreferencesPage {
@@ -209,6 +283,41 @@ impl Component for PreferencesDialog {
                             sender.input(PreferencesDialogInput::PlayerAutoPlayChanged(value));
                         }
                     },
+                    adw::SwitchRow {
+                        set_title: &t!("prebuffer_next"),
+                        set_subtitle: &t!("prebuffer_next_description"),
+                        set_active: model.settings.boolean("prebuffer-next"),
+                        connect_active_notify[sender] => move |row| {
+                            let value = row.is_active();
+                            sender.input(PreferencesDialogInput::PlayerPrebufferNextChanged(value));
+                        }
+                    },
+                    adw::SpinRow::with_range(SEEK_MIN_DURATION as f64, SEEK_MAX_DURATION as f64, 1.0) {
+                        set_title: &t!("seek_duration"),
+                        set_subtitle: &t!("seek_duration_description"),
+
+                        #[watch]
+                        #[block_signal(seek_duration_handler)]
+                        set_value: ctx.settings.seek_time_duration as f64 / 1000.0,
+
+                        connect_value_notify[sender] => move |row| {
+                            let value = row.value();
+                            sender.input(PreferencesDialogInput::PlayerSeekDurationChanged(value));
+                        } @seek_duration_handler,
+                    },
+                    adw::SpinRow::with_range(SEEK_MIN_DURATION as f64, SEEK_MAX_DURATION as f64, 1.0) {
+                        set_title: &t!("seek_short_duration"),
+                        set_subtitle: &t!("seek_short_duration_description"),
+
+                        #[watch]
+                        #[block_signal(seek_short_duration_handler)]
+                        set_value: ctx.settings.seek_short_time_duration as f64 / 1000.0,
+
+                        connect_value_notify[sender] => move |row| {
+                            let value = row.value();
+                            sender.input(PreferencesDialogInput::PlayerSeekShortDurationChanged(value));
+                        } @seek_short_duration_handler,
+                    },
                 }
             },
 
@@ -221,6 +330,20 @@ impl Component for PreferencesDialog {
                 add = &adw::PreferencesGroup {
                     adw::ActionRow {
                         set_title: &t!("status"),
+
+                        #[watch]
+                        set_subtitle: &match process.status {
+                            ServerStatus::Stopped => t!("server_stopped"),
+                            ServerStatus::Starting => t!("server_starting"),
+                            ServerStatus::Running => t!("server_running"),
+                            ServerStatus::Restarting => {
+                                t!("server_restarting", count = process.restarts)
+                            }
+                            ServerStatus::Failed => t!("server_failed"),
+                            ServerStatus::Invalid => t!("server_invalid"),
+                            ServerStatus::NoRuntime => t!("server_no_runtime"),
+                        },
+
                         add_suffix = &gtk::Label {
 
                             #[watch]
@@ -232,14 +355,47 @@ impl Component for PreferencesDialog {
                         }
                     },
 
-                    adw::EntryRow {
-                        set_title: &t!("url"),
-                        set_text: &ctx.settings.streaming_server_url.as_str(),
-                        connect_text_notify[sender] => move |row| {
-                            let value = row.text().to_string();
-                            sender.input(PreferencesDialogInput::ServerUrlChanged(value));
+                    adw::SwitchRow {
+                        set_title: &t!("native_engine"),
+                        set_subtitle: &t!("native_engine_description"),
+                        set_active: model.settings.boolean("native-engine"),
+                        connect_active_notify[sender] => move |row| {
+                            let value = row.is_active();
+                            sender.input(PreferencesDialogInput::ServerNativeEngineChanged(value));
                         },
                     },
+
+                    adw::ActionRow {
+                        set_title: &t!("node_runtime"),
+                        #[watch]
+                        set_visible: !model.settings.boolean("native-engine"),
+
+                        #[watch]
+                        set_subtitle: &match &process.runtime {
+                            Some(Ok(runtime)) => format!("{} ({})", runtime.path.display(), runtime.version),
+                            Some(Err(e)) => e.to_string(),
+                            None => t!("node_auto_detect").to_string(),
+                        },
+
+                        add_suffix = &gtk::Button {
+                            set_icon_name: "edit-undo-symbolic",
+                            set_tooltip_text: Some(&t!("node_auto_detect")),
+                            set_valign: gtk::Align::Center,
+                            add_css_class: css::classes::FLAT,
+                            #[watch]
+                            set_visible: !model.settings.string("node-path").is_empty(),
+                            connect_clicked => PreferencesDialogInput::NodePathChanged(String::new()),
+                        },
+
+                        add_suffix = &gtk::Button {
+                            set_icon_name: "document-open-symbolic",
+                            set_tooltip_text: Some(&t!("choose_node")),
+                            set_valign: gtk::Align::Center,
+                            add_css_class: css::classes::FLAT,
+                            connect_clicked => PreferencesDialogInput::NodeChoose,
+                        },
+                    },
+
                     adw::SwitchRow {
                         set_title: &t!("autostart"),
                         set_active: model.settings.boolean("autostart-server"),
@@ -247,7 +403,151 @@ impl Component for PreferencesDialog {
                             let value = row.is_active();
                             sender.input(PreferencesDialogInput::ServerEnabledChanged(value));
                         },
-                    }
+                    },
+                    adw::ButtonRow {
+                        set_title: &t!("restart_server"),
+                        set_start_icon_name: Some("view-refresh-symbolic"),
+                        connect_activated => PreferencesDialogInput::ServerRestart,
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &t!("servers"),
+                    set_description: Some(&t!("servers_description")),
+
+                    #[local_ref]
+                    server_list -> gtk::ListBox {
+                        add_css_class: css::classes::BOXED_LIST,
+                        set_selection_mode: gtk::SelectionMode::None,
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    adw::SwitchRow {
+                        set_title: &t!("server_failover"),
+                        set_subtitle: &t!("server_failover_description"),
+                        set_active: model.settings.boolean("server-failover"),
+                        connect_active_notify[sender] => move |row| {
+                            let value = row.is_active();
+                            sender.input(PreferencesDialogInput::ServerFailoverChanged(value));
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &t!("server_settings"),
+
+                    #[watch]
+                    set_sensitive: server.settings.is_some(),
+
+                    adw::ComboRow {
+                        set_title: &t!("cache_size"),
+                        set_model: Some(&cache_sizes),
+
+                        #[watch]
+                        #[block_signal(cache_size_handler)]
+                        set_selected: Self::cache_size_position(&server),
+
+                        connect_selected_notify[sender] => move |row| {
+                            let value = row.selected();
+                            sender.input(PreferencesDialogInput::ServerCacheSizeChanged(value));
+                        } @cache_size_handler,
+                    },
+                    #[local_ref]
+                    cache_root_row -> adw::EntryRow {
+                        set_title: &t!("cache_location"),
+                        set_show_apply_button: true,
+                        connect_apply[sender] => move |row| {
+                            let value = row.text().to_string();
+                            sender.input(PreferencesDialogInput::ServerCacheRootChanged(value));
+                        },
+                    },
+                    adw::ComboRow {
+                        set_title: &t!("torrent_profile"),
+                        set_model: Some(&torrent_profiles),
+
+                        #[watch]
+                        #[block_signal(torrent_profile_handler)]
+                        set_selected: Self::torrent_profile_position(&server),
+
+                        connect_selected_notify[sender] => move |row| {
+                            let value = row.selected();
+                            sender.input(PreferencesDialogInput::ServerTorrentProfileChanged(value));
+                        } @torrent_profile_handler,
+                    },
+                    #[local_ref]
+                    transcode_row -> adw::ComboRow {
+                        set_title: &t!("transcode_profile"),
+                    },
+                    adw::SwitchRow {
+                        set_title: &t!("proxy_streams"),
+                        set_subtitle: &t!("proxy_streams_description"),
+
+                        #[watch]
+                        #[block_signal(proxy_streams_handler)]
+                        set_active: server
+                            .settings
+                            .as_ref()
+                            .is_some_and(|settings| settings.proxy_streams_enabled),
+
+                        connect_active_notify[sender] => move |row| {
+                            let value = row.is_active();
+                            sender.input(PreferencesDialogInput::ServerProxyStreamsChanged(value));
+                        } @proxy_streams_handler,
+                    },
+                },
+            },
+
+            add = &adw::PreferencesPage {
+                set_name: Some("remote"),
+                set_title: &t!("remote_control"),
+                set_icon_name: Some("network-wireless-symbolic"),
+                set_margin_bottom: 26,
+
+                add = &adw::PreferencesGroup {
+                    set_description: Some(&t!("remote_control_description")),
+
+                    adw::SwitchRow {
+                        set_title: &t!("enabled"),
+                        set_active: model.settings.boolean("remote-control"),
+                        connect_active_notify[sender] => move |row| {
+                            let value = row.is_active();
+                            sender.input(PreferencesDialogInput::RemoteControlChanged(value));
+                        },
+                    },
+                    adw::SpinRow::with_range(1024.0, 65535.0, 1.0) {
+                        set_title: &t!("port"),
+                        set_value: model.settings.uint("remote-control-port") as f64,
+                        connect_value_notify[sender] => move |row| {
+                            let value = row.value();
+                            sender.input(PreferencesDialogInput::RemoteControlPortChanged(value));
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &t!("pairing"),
+                    set_description: Some(&t!("pairing_description")),
+
+                    adw::ActionRow {
+                        set_title: &t!("pairing_address"),
+                        set_subtitle_selectable: true,
+
+                        #[watch]
+                        set_subtitle: &model.remote_address,
+
+                        add_suffix = &gtk::Button {
+                            add_css_class: css::classes::FLAT,
+                            set_valign: gtk::Align::Center,
+                            set_icon_name: "edit-copy-symbolic",
+                            set_tooltip_text: Some(&t!("copy")),
+                            connect_clicked => PreferencesDialogInput::RemoteControlCopyAddress,
+                        },
+                    },
+                    adw::ButtonRow {
+                        set_title: &t!("reset_pairing_token"),
+                        connect_activated => PreferencesDialogInput::RemoteControlResetToken,
+                    },
                 },
             }
         }
@@ -260,19 +560,80 @@ impl Component for PreferencesDialog {
     ) -> ComponentParts<Self> {
         let ctx = CTX_STATE.read_inner();
         let server = SERVER_STATE.read_inner();
+        let process = SERVER_PROCESS_STATE.read_inner();
 
         CTX_STATE.subscribe(sender.input_sender(), |_| PreferencesDialogInput::Update);
         SERVER_STATE.subscribe(sender.input_sender(), |_| PreferencesDialogInput::Update);
+        SERVER_PROCESS_STATE.subscribe(sender.input_sender(), |_| PreferencesDialogInput::Update);
+        SERVER_URLS_STATE.subscribe(sender.input_sender(), |_| PreferencesDialogInput::Update);
 
         let settings = gio::Settings::new(APP_ID);
 
         let subtitles_color_dialog = gtk::ColorDialog::builder().build();
+        let remote_address = remote::pairing_url(&settings);
+
+        let cache_sizes = gtk::StringList::default();
+        for size in CACHE_SIZES {
+            cache_sizes.append(&match size {
+                Some(size) if *size == 0.0 => t!("cache_disabled").to_string(),
+                Some(size) => format!("{} GB", size / GB),
+                None => t!("cache_unlimited").to_string(),
+            });
+        }
+
+        let torrent_profiles = gtk::StringList::default();
+        for profile in TorrentProfile::ALL {
+            torrent_profiles.append(&match profile {
+                TorrentProfile::Default => t!("torrent_profile_default"),
+                TorrentProfile::Soft => t!("torrent_profile_soft"),
+                TorrentProfile::Fast => t!("torrent_profile_fast"),
+            });
+        }
+
+        let transcode_row = adw::ComboRow::default();
+        let transcode_handler = transcode_row.connect_selected_notify({
+            let sender = sender.input_sender().clone();
+            move |row| {
+                let value = row.selected();
+                sender.emit(PreferencesDialogInput::ServerTranscodeProfileChanged(value));
+            }
+        });
+
+        let server_add_row = adw::EntryRow::builder()
+            .title(t!("add_server").as_ref())
+            .show_apply_button(true)
+            .build();
 
-        let model = Self {
+        server_add_row.connect_apply({
+            let sender = sender.input_sender().clone();
+            move |row| {
+                sender.emit(PreferencesDialogInput::ServerUrlAdd(row.text().to_string()));
+                row.set_text("");
+            }
+        });
+
+        let mut model = Self {
             settings,
             subtitles_color_dialog,
+            remote_address,
+            cache_root: String::new(),
+            cache_root_row: adw::EntryRow::default(),
+            transcode_profiles: vec![],
+            transcode_row,
+            transcode_handler,
+            server_list: gtk::ListBox::default(),
+            server_add_row,
+            server_rows: vec![],
+            local_folders_list: gtk::ListBox::default(),
         };
 
+        model.update_server_list(&sender);
+        model.update_local_folders(&sender);
+
+        let cache_root_row = &model.cache_root_row;
+        let transcode_row = &model.transcode_row;
+        let server_list = &model.server_list;
+        let local_folders_list = &model.local_folders_list;
         let widgets = view_output!();
 
         ComponentParts { model, widgets }
@@ -281,20 +642,27 @@ impl Component for PreferencesDialog {
     fn pre_view() {
         let ctx = CTX_STATE.read_inner();
         let server = SERVER_STATE.read_inner();
+        let process = SERVER_PROCESS_STATE.read_inner();
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>, root: &Self::Root) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
         match message {
             PreferencesDialogInput::Open(name) => {
                 let window = relm4::main_application().active_window();
                 root.present(window.as_ref());
                 models::server::reload();
 
+                self.remote_address = remote::pairing_url(&self.settings);
+
                 if let Some(name) = name {
                     root.set_visible_page_name(name);
                 }
             }
-            PreferencesDialogInput::Update => {}
+            PreferencesDialogInput::Update => {
+                let server = SERVER_STATE.read_inner();
+                self.update_server_settings(&server);
+                self.update_server_list(&sender);
+            }
             PreferencesDialogInput::CatalogsIconChanged(value) => {
                 let _ = self.settings.set_boolean("catalog-addon-icon", value);
             }
@@ -313,6 +681,36 @@ impl Component for PreferencesDialog {
             PreferencesDialogInput::PlayerResizeWindow(value) => {
                 let _ = self.settings.set_boolean("player-resize-window", value);
             }
+            PreferencesDialogInput::PlayerExternalCommandChanged(value) => {
+                let _ = self.settings.set_string("external-player", &value);
+            }
+            PreferencesDialogInput::LocalFolderChoose => {
+                let window = relm4::main_application().active_window();
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(t!("add_folder"))
+                    .modal(true)
+                    .build();
+
+                file_dialog.select_folder(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                        let path = path.to_string_lossy().to_string();
+                        sender.input(PreferencesDialogInput::LocalFolderAdd(path));
+                    }
+                });
+            }
+            PreferencesDialogInput::LocalFolderAdd(folder) => {
+                let mut folders = self.local_folders();
+
+                if !folders.contains(&folder) {
+                    folders.push(folder);
+                    self.set_local_folders(&folders, &sender);
+                }
+            }
+            PreferencesDialogInput::LocalFolderRemove(folder) => {
+                let mut folders = self.local_folders();
+                folders.retain(|f| *f != folder);
+                self.set_local_folders(&folders, &sender);
+            }
             PreferencesDialogInput::PlayerSubtitlesSizeChanged(value) => {
                 models::ctx::update_settings(|mut settings| {
                     settings.subtitles_size = value as u8;
@@ -337,23 +735,302 @@ impl Component for PreferencesDialog {
                     settings
                 });
             }
+            PreferencesDialogInput::PlayerPrebufferNextChanged(value) => {
+                let _ = self.settings.set_boolean("prebuffer-next", value);
+            }
             PreferencesDialogInput::PlayerAutoPlayChanged(value) => {
                 models::ctx::update_settings(|mut settings| {
                     settings.binge_watching = value;
                     settings
                 });
             }
-            PreferencesDialogInput::ServerUrlChanged(value) => {
-                if let Ok(url) = Url::parse(&value) {
-                    models::ctx::update_settings(|mut settings| {
-                        settings.streaming_server_url = url;
+            PreferencesDialogInput::PlayerSeekDurationChanged(value) => {
+                models::ctx::update_settings(|mut settings| {
+                    settings.seek_time_duration = (value * 1000.0) as u32;
+                    settings
+                });
+            }
+            PreferencesDialogInput::PlayerSeekShortDurationChanged(value) => {
+                models::ctx::update_settings(|mut settings| {
+                    settings.seek_short_time_duration = (value * 1000.0) as u32;
+                    settings
+                });
+            }
+            PreferencesDialogInput::ServerUrlAdd(value) => match Url::parse(value.trim()) {
+                Ok(url) => models::ctx::add_server_url(url),
+                Err(_) => root.add_toast(adw::Toast::new(&t!("invalid_url"))),
+            },
+            PreferencesDialogInput::ServerUrlRemove(url) => {
+                models::ctx::delete_server_url(url);
+            }
+            PreferencesDialogInput::ServerUrlSelect(url) => {
+                server_urls::select(url);
+            }
+            PreferencesDialogInput::ServerFailoverChanged(value) => {
+                let _ = self.settings.set_boolean("server-failover", value);
+            }
+            PreferencesDialogInput::ServerEnabledChanged(value) => {
+                let _ = self.settings.set_boolean("autostart-server", value);
+            }
+            PreferencesDialogInput::ServerNativeEngineChanged(value) => {
+                let _ = self.settings.set_boolean("native-engine", value);
+                APP_BROKER.send(AppMsg::ReloadServer);
+            }
+            PreferencesDialogInput::NodeChoose => {
+                let window = relm4::main_application().active_window();
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(t!("choose_node"))
+                    .modal(true)
+                    .build();
+
+                file_dialog.open(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                        let path = path.to_string_lossy().to_string();
+                        sender.input(PreferencesDialogInput::NodePathChanged(path));
+                    }
+                });
+            }
+            PreferencesDialogInput::NodePathChanged(path) => {
+                let _ = self.settings.set_string("node-path", &path);
+                APP_BROKER.send(AppMsg::ReloadServer);
+            }
+            PreferencesDialogInput::ServerRestart => {
+                APP_BROKER.send(AppMsg::RestartServer);
+            }
+            PreferencesDialogInput::ServerCacheSizeChanged(position) => {
+                if let Some(cache_size) = CACHE_SIZES.get(position as usize) {
+                    models::server::update_settings(|mut settings| {
+                        settings.cache_size = *cache_size;
                         settings
                     });
                 }
             }
-            PreferencesDialogInput::ServerEnabledChanged(value) => {
-                let _ = self.settings.set_boolean("autostart-server", value);
+            PreferencesDialogInput::ServerCacheRootChanged(value) => {
+                models::server::update_settings(|mut settings| {
+                    settings.cache_root = value;
+                    settings
+                });
+            }
+            PreferencesDialogInput::ServerTorrentProfileChanged(position) => {
+                if let Some(profile) = TorrentProfile::ALL.get(position as usize) {
+                    models::server::update_settings(|mut settings| {
+                        profile.apply(&mut settings);
+                        settings
+                    });
+                }
+            }
+            PreferencesDialogInput::ServerTranscodeProfileChanged(position) => {
+                // The first entry disables transcoding
+                let profile = match position {
+                    0 => None,
+                    _ => self.transcode_profiles.get(position as usize - 1).cloned(),
+                };
+
+                models::server::update_settings(|mut settings| {
+                    settings.transcode_profile = profile;
+                    settings
+                });
+            }
+            PreferencesDialogInput::ServerProxyStreamsChanged(value) => {
+                models::server::update_settings(|mut settings| {
+                    settings.proxy_streams_enabled = value;
+                    settings
+                });
+            }
+            PreferencesDialogInput::RemoteControlChanged(value) => {
+                let _ = self.settings.set_boolean("remote-control", value);
+            }
+            PreferencesDialogInput::RemoteControlPortChanged(value) => {
+                let _ = self.settings.set_uint("remote-control-port", value as u32);
+                self.remote_address = remote::pairing_url(&self.settings);
+            }
+            PreferencesDialogInput::RemoteControlCopyAddress => {
+                root.clipboard().set_text(&self.remote_address);
+                root.add_toast(adw::Toast::new(&t!("copied")));
+            }
+            PreferencesDialogInput::RemoteControlResetToken => {
+                remote::reset_token(&self.settings);
+                self.remote_address = remote::pairing_url(&self.settings);
             }
         }
     }
 }
+
+impl PreferencesDialog {
+    fn cache_size_position(server: &ServerState) -> u32 {
+        server
+            .settings
+            .as_ref()
+            .and_then(|settings| {
+                CACHE_SIZES
+                    .iter()
+                    .position(|size| *size == settings.cache_size)
+            })
+            .map_or(gtk::INVALID_LIST_POSITION, |position| position as u32)
+    }
+
+    fn torrent_profile_position(server: &ServerState) -> u32 {
+        server
+            .settings
+            .as_ref()
+            .and_then(TorrentProfile::from_settings)
+            .and_then(|profile| TorrentProfile::ALL.iter().position(|p| *p == profile))
+            .map_or(gtk::INVALID_LIST_POSITION, |position| position as u32)
+    }
+
+    fn update_server_settings(&mut self, server: &ServerState) {
+        let Some(settings) = &server.settings else {
+            return;
+        };
+
+        if self.cache_root != settings.cache_root {
+            self.cache_root = settings.cache_root.to_owned();
+            self.cache_root_row.set_text(&self.cache_root);
+        }
+
+        self.transcode_row.block_signal(&self.transcode_handler);
+
+        if self.transcode_profiles != server.transcode_profiles {
+            self.transcode_profiles = server.transcode_profiles.to_owned();
+
+            let profiles = gtk::StringList::new(&[t!("transcode_disabled").as_ref()]);
+            for profile in &self.transcode_profiles {
+                profiles.append(profile);
+            }
+
+            self.transcode_row.set_model(Some(&profiles));
+        }
+
+        self.transcode_row
+            .set_selected(Self::transcode_position(&self.transcode_profiles, settings));
+
+        self.transcode_row.unblock_signal(&self.transcode_handler);
+    }
+
+    fn update_server_list(&mut self, sender: &ComponentSender<Self>) {
+        let ctx = CTX_STATE.read_inner();
+        let statuses = SERVER_URLS_STATE.read_inner();
+        let selected = &ctx.settings.streaming_server_url;
+
+        let rows = ctx
+            .server_urls
+            .iter()
+            .map(|url| {
+                let status = statuses.statuses.get(url).copied();
+                (url.to_owned(), url == selected, status)
+            })
+            .collect::<Vec<_>>();
+
+        if rows == self.server_rows {
+            return;
+        }
+
+        self.server_list.remove_all();
+
+        for (url, selected, status) in &rows {
+            let row = adw::ActionRow::builder()
+                .title(url.as_str())
+                .subtitle(match status {
+                    Some(ServerUrlStatus::Online(latency)) => {
+                        t!("server_latency", ms = latency.as_millis()).to_string()
+                    }
+                    Some(ServerUrlStatus::Offline) => t!("offline").to_string(),
+                    None => t!("server_checking").to_string(),
+                })
+                .activatable(!selected)
+                .build();
+
+            let check = gtk::Image::from_icon_name("object-select-symbolic");
+            check.set_opacity(if *selected { 1.0 } else { 0.0 });
+            row.add_prefix(&check);
+
+            let remove = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(t!("remove").as_ref())
+                .valign(gtk::Align::Center)
+                .sensitive(!selected)
+                .build();
+
+            remove.add_css_class(css::classes::FLAT);
+
+            remove.connect_clicked({
+                let sender = sender.input_sender().clone();
+                let url = url.to_owned();
+                move |_| sender.emit(PreferencesDialogInput::ServerUrlRemove(url.to_owned()))
+            });
+
+            row.connect_activated({
+                let sender = sender.input_sender().clone();
+                let url = url.to_owned();
+                move |_| sender.emit(PreferencesDialogInput::ServerUrlSelect(url.to_owned()))
+            });
+
+            row.add_suffix(&remove);
+            self.server_list.append(&row);
+        }
+
+        self.server_list.append(&self.server_add_row);
+        self.server_rows = rows;
+    }
+
+    fn local_folders(&self) -> Vec<String> {
+        self.settings
+            .strv("local-folders")
+            .iter()
+            .map(|folder| folder.to_string())
+            .collect()
+    }
+
+    fn set_local_folders(&mut self, folders: &[String], sender: &ComponentSender<Self>) {
+        let _ = self.settings.set_strv("local-folders", folders);
+        self.update_local_folders(sender);
+
+        APP_BROKER.send(AppMsg::ScanLocalFiles);
+    }
+
+    fn update_local_folders(&mut self, sender: &ComponentSender<Self>) {
+        self.local_folders_list.remove_all();
+
+        for folder in self.local_folders() {
+            let row = adw::ActionRow::builder().title(folder.as_str()).build();
+
+            let remove = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(t!("remove").as_ref())
+                .valign(gtk::Align::Center)
+                .build();
+
+            remove.add_css_class(css::classes::FLAT);
+
+            remove.connect_clicked({
+                let sender = sender.input_sender().clone();
+                move |_| sender.emit(PreferencesDialogInput::LocalFolderRemove(folder.to_owned()))
+            });
+
+            row.add_suffix(&remove);
+            self.local_folders_list.append(&row);
+        }
+
+        let add_row = adw::ButtonRow::builder()
+            .title(t!("add_folder").as_ref())
+            .start_icon_name("list-add-symbolic")
+            .build();
+
+        add_row.connect_activated({
+            let sender = sender.input_sender().clone();
+            move |_| sender.emit(PreferencesDialogInput::LocalFolderChoose)
+        });
+
+        self.local_folders_list.append(&add_row);
+    }
+
+    fn transcode_position(profiles: &[String], settings: &Settings) -> u32 {
+        match &settings.transcode_profile {
+            None => 0,
+            Some(profile) => profiles
+                .iter()
+                .position(|p| p == profile)
+                .map_or(gtk::INVALID_LIST_POSITION, |position| position as u32 + 1),
+        }
+    }
+}
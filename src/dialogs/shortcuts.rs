@@ -28,6 +28,7 @@ impl SimpleComponent for ShortcutsDialog {
             &t!("shortcut_search"),
             "<Control>F",
         ));
+        general_section.add(adw::ShortcutsItem::new(&t!("shortcut_open"), "<Control>O"));
         general_section.add(adw::ShortcutsItem::new(
             &t!("shortcut_preferences"),
             "<Control>comma",
@@ -44,6 +45,22 @@ impl SimpleComponent for ShortcutsDialog {
             &t!("shortcut_seek_forward"),
             "Right",
         ));
+        player_section.add(adw::ShortcutsItem::new(
+            &t!("shortcut_seek_backward_short"),
+            "<Shift>Left",
+        ));
+        player_section.add(adw::ShortcutsItem::new(
+            &t!("shortcut_seek_forward_short"),
+            "<Shift>Right",
+        ));
+        player_section.add(adw::ShortcutsItem::new(
+            &t!("shortcut_previous_frame"),
+            "comma",
+        ));
+        player_section.add(adw::ShortcutsItem::new(
+            &t!("shortcut_next_frame"),
+            "period",
+        ));
         player_section.add(adw::ShortcutsItem::new(
             &t!("shortcut_increase_volume"),
             "Up",
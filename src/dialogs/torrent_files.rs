@@ -0,0 +1,188 @@
+use adw::prelude::*;
+use itertools::Itertools;
+use relm4::{
+    adw, css,
+    gtk::{self, glib},
+    Component, ComponentParts, ComponentSender, RelmWidgetExt,
+};
+use rust_i18n::t;
+use stremio_core_losange::{
+    models::ctx::CTX_STATE,
+    stremio_core::types::resource::StreamSource,
+    torrent::{self, TorrentFile},
+    types::stream::Stream,
+};
+use tracing::error;
+
+use crate::{app::AppMsg, components::spinner::Spinner, APP_BROKER};
+
+#[derive(Debug)]
+pub enum TorrentFilesDialogInput {
+    Open(Box<Stream>),
+    Select(usize),
+    Closed,
+}
+
+pub struct TorrentFilesDialog {
+    stream: Option<Box<Stream>>,
+    playlist: Vec<Stream>,
+    loading: bool,
+    list: gtk::ListBox,
+}
+
+#[relm4::component(pub)]
+impl Component for TorrentFilesDialog {
+    type Init = ();
+    type Input = TorrentFilesDialogInput;
+    type Output = ();
+    type CommandOutput = Vec<TorrentFile>;
+
+    view! {
+        adw::Dialog {
+            set_content_width: 500,
+            set_content_height: 500,
+            set_title: &t!("torrent_files"),
+
+            connect_closed => TorrentFilesDialogInput::Closed,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+
+                #[wrap(Some)]
+                set_content = &gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_margin_all: 24,
+
+                        #[transition = "Crossfade"]
+                        if model.loading {
+                            #[template]
+                            Spinner {}
+                        } else {
+                            #[local_ref]
+                            list -> gtk::ListBox {
+                                add_css_class: css::classes::BOXED_LIST,
+                                set_valign: gtk::Align::Start,
+                                set_selection_mode: gtk::SelectionMode::None,
+
+                                connect_row_activated[sender] => move |_, row| {
+                                    sender.input(TorrentFilesDialogInput::Select(row.index() as usize));
+                                },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            stream: None,
+            playlist: vec![],
+            loading: false,
+            list: gtk::ListBox::default(),
+        };
+
+        let list = &model.list;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            TorrentFilesDialogInput::Open(stream) => {
+                let StreamSource::Torrent {
+                    info_hash,
+                    announce,
+                    ..
+                } = stream.source.to_owned()
+                else {
+                    return;
+                };
+
+                let server_url = CTX_STATE
+                    .read_inner()
+                    .settings
+                    .streaming_server_url
+                    .to_owned();
+
+                self.stream = Some(stream);
+                self.playlist.clear();
+                self.list.remove_all();
+                self.loading = true;
+
+                let window = relm4::main_application().active_window();
+                root.present(window.as_ref());
+
+                sender.oneshot_command(async move {
+                    torrent::fetch_files(&server_url, &info_hash, &announce)
+                        .await
+                        .map_err(|e| error!("Failed to fetch torrent files: {e}"))
+                        .unwrap_or_default()
+                });
+            }
+            TorrentFilesDialogInput::Select(index) => {
+                if index < self.playlist.len() {
+                    let playlist = std::mem::take(&mut self.playlist);
+                    APP_BROKER.send(AppMsg::OpenPlaylist((playlist, index)));
+                    root.close();
+                }
+            }
+            TorrentFilesDialogInput::Closed => {
+                self.stream = None;
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        files: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        self.loading = false;
+
+        let Some(stream) = self.stream.take() else {
+            return;
+        };
+
+        let files = files
+            .iter()
+            .filter(|file| file.is_video())
+            .sorted_by(|a, b| a.path.cmp(&b.path).then(a.name.cmp(&b.name)))
+            .collect_vec();
+
+        self.playlist = files
+            .iter()
+            .filter_map(|file| stream.with_file(file))
+            .collect_vec();
+
+        // Nothing to pick from, the streaming server selects the largest video with -1
+        if self.playlist.len() <= 1 {
+            self.playlist.clear();
+            APP_BROKER.send(AppMsg::OpenPlaylist((vec![*stream], 0)));
+            root.close();
+            return;
+        }
+
+        for file in files {
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&file.name))
+                .subtitle(glib::format_size(file.length))
+                .activatable(true)
+                .build();
+
+            row.add_prefix(&gtk::Image::from_icon_name("video-x-generic-symbolic"));
+            self.list.append(&row);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use adw::prelude::*;
+use relm4::{adw, css, gtk, Component, ComponentParts, ComponentSender, RelmWidgetExt};
+use rust_i18n::t;
+
+use crate::{app::AppMsg, APP_BROKER};
+
+#[derive(Debug)]
+pub enum WatchPartyDialogInput {
+    Open,
+    Host,
+    Join,
+    Leave,
+    Connecting,
+    Update(Option<(String, usize)>),
+}
+
+pub struct WatchPartyDialog {
+    code: adw::EntryRow,
+    connecting: bool,
+    party: Option<(String, usize)>,
+}
+
+#[relm4::component(pub)]
+impl Component for WatchPartyDialog {
+    type Init = ();
+    type Input = WatchPartyDialogInput;
+    type Output = ();
+    type CommandOutput = ();
+
+    view! {
+        adw::Dialog {
+            set_content_width: 400,
+            set_title: &t!("watch_party"),
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+                    set_spacing: 24,
+
+                    gtk::Label {
+                        add_css_class: css::classes::DIM_LABEL,
+                        set_label: &t!("watch_party_description"),
+                        set_wrap: true,
+                        set_justify: gtk::Justification::Center,
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 24,
+
+                        #[watch]
+                        set_visible: model.party.is_none(),
+
+                        #[watch]
+                        set_sensitive: !model.connecting,
+
+                        gtk::Button {
+                            set_css_classes: &[css::classes::PILL, css::classes::SUGGESTED_ACTION],
+                            set_halign: gtk::Align::Center,
+                            set_label: &t!("watch_party_host"),
+
+                            connect_clicked => WatchPartyDialogInput::Host,
+                        },
+
+                        gtk::ListBox {
+                            add_css_class: css::classes::BOXED_LIST,
+
+                            #[local_ref]
+                            code -> adw::EntryRow {
+                                set_title: &t!("watch_party_code"),
+                                set_activates_default: false,
+                                connect_entry_activated => WatchPartyDialogInput::Join,
+
+                                add_suffix = &gtk::Button {
+                                    add_css_class: css::classes::FLAT,
+                                    set_valign: gtk::Align::Center,
+                                    set_label: &t!("watch_party_join"),
+                                    connect_clicked => WatchPartyDialogInput::Join,
+                                },
+                            },
+                        },
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 12,
+
+                        #[watch]
+                        set_visible: model.party.is_some(),
+
+                        gtk::Label {
+                            add_css_class: css::classes::TITLE_1,
+                            set_selectable: true,
+
+                            #[watch]
+                            set_label: model.party.as_ref().map_or("", |(code, _)| code),
+                        },
+
+                        gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+
+                            #[watch]
+                            set_label: &t!(
+                                "watch_party_peers",
+                                count = model.party.as_ref().map_or(0, |(_, peers)| *peers)
+                            ),
+                        },
+
+                        gtk::Button {
+                            set_css_classes: &[css::classes::PILL, css::classes::DESTRUCTIVE_ACTION],
+                            set_halign: gtk::Align::Center,
+                            set_margin_top: 12,
+                            set_label: &t!("watch_party_leave"),
+
+                            connect_clicked => WatchPartyDialogInput::Leave,
+                        },
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            code: adw::EntryRow::default(),
+            connecting: false,
+            party: None,
+        };
+
+        let code = &model.code;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            WatchPartyDialogInput::Open => {
+                let window = relm4::main_application().active_window();
+                root.present(window.as_ref());
+            }
+            WatchPartyDialogInput::Host => {
+                APP_BROKER.send(AppMsg::HostWatchParty);
+            }
+            WatchPartyDialogInput::Join => {
+                let code = self.code.text().trim().to_uppercase();
+
+                if !code.is_empty() {
+                    APP_BROKER.send(AppMsg::JoinWatchParty(code));
+                }
+            }
+            WatchPartyDialogInput::Leave => {
+                APP_BROKER.send(AppMsg::LeaveWatchParty);
+            }
+            WatchPartyDialogInput::Connecting => {
+                self.connecting = true;
+            }
+            WatchPartyDialogInput::Update(party) => {
+                self.connecting = false;
+                self.party = party;
+            }
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    ffi::CString,
+    fs,
+    mem::MaybeUninit,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use gtk::{gio, glib, prelude::SettingsExt};
+use relm4::{gtk, JoinHandle, SharedState};
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use shellexpand::tilde;
+use stremio_core_losange::{
+    models::ctx::CTX_STATE,
+    stremio_core::types::resource::{Stream as CoreStream, StreamSource},
+    torrent,
+    types::stream::Stream,
+};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use tracing::{error, info};
+use url::Url;
+
+use crate::{
+    common::net,
+    constants::{APP_ID, APP_NAME},
+};
+
+const STORE_FILE: &str = "downloads.json";
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_EXTENSION: &str = "mkv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DownloadStatus {
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Download {
+    pub id: String,
+    pub title: String,
+    pub image: Option<Url>,
+    pub stream: Stream,
+    pub path: PathBuf,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub status: DownloadStatus,
+}
+
+impl Download {
+    pub fn progress(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| self.downloaded as f64 / total as f64)
+    }
+
+    /// The original stream pointing at the file on disk, so playback progress still syncs to the library item
+    pub fn local_stream(&self) -> Option<Stream> {
+        let url = Url::from_file_path(&self.path).ok()?;
+
+        Some(Stream {
+            source: StreamSource::Url { url },
+            ..self.stream.to_owned()
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct DownloadsState {
+    pub downloads: Vec<Download>,
+}
+
+pub static DOWNLOADS_STATE: SharedState<DownloadsState> = SharedState::new();
+
+static STORE: OnceLock<PathBuf> = OnceLock::new();
+static TASKS: Mutex<Vec<(String, JoinHandle<()>)>> = Mutex::new(Vec::new());
+
+pub fn initialize(data_location: &Path) {
+    let store = STORE.get_or_init(|| data_location.join(STORE_FILE));
+
+    let downloads = fs::read(store)
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<Vec<Download>>(&contents).ok())
+        .unwrap_or_default();
+
+    let interrupted = downloads
+        .iter()
+        .filter(|download| download.status == DownloadStatus::Downloading)
+        .map(|download| download.id.to_owned())
+        .collect::<Vec<_>>();
+
+    DOWNLOADS_STATE.write().downloads = downloads;
+
+    for id in interrupted {
+        resume(&id);
+    }
+}
+
+pub fn location() -> PathBuf {
+    let settings = gio::Settings::new(APP_ID);
+    let location = settings.string("downloads-location");
+
+    match location.is_empty() {
+        true => glib::user_special_dir(glib::UserDirectory::Videos)
+            .unwrap_or_else(glib::home_dir)
+            .join(APP_NAME),
+        false => PathBuf::from(tilde(&location).to_string()),
+    }
+}
+
+pub fn start(stream: Stream, title: String, image: Option<Url>) -> anyhow::Result<()> {
+    let already_downloading = DOWNLOADS_STATE
+        .read_inner()
+        .downloads
+        .iter()
+        .any(|download| download.stream == stream);
+
+    if already_downloading {
+        return Ok(());
+    }
+
+    let location = location();
+    fs::create_dir_all(&location).context("Failed to create downloads directory")?;
+
+    // Files of downloads that did not start yet do not exist on the disk
+    let taken = DOWNLOADS_STATE
+        .read_inner()
+        .downloads
+        .iter()
+        .map(|download| download.path.to_owned())
+        .collect::<Vec<_>>();
+
+    let path = unique_path(&location, &title, extension(&stream), &taken);
+    let id = glib::uuid_string_random().to_string();
+
+    info!("Downloading {title} to {}", path.display());
+
+    DOWNLOADS_STATE.write().downloads.push(Download {
+        id: id.to_owned(),
+        title,
+        image,
+        stream,
+        path,
+        downloaded: 0,
+        total: None,
+        status: DownloadStatus::Paused,
+    });
+
+    resume(&id);
+
+    Ok(())
+}
+
+pub fn pause(id: &str) {
+    abort(id);
+    set_status(id, DownloadStatus::Paused);
+}
+
+pub fn resume(id: &str) {
+    let server_url = CTX_STATE
+        .read_inner()
+        .settings
+        .streaming_server_url
+        .to_owned();
+
+    let Some(download) = find(id) else {
+        return;
+    };
+
+    let Some(url) = CoreStream::from(download.stream.to_owned()).streaming_url(Some(&server_url))
+    else {
+        error!("Stream of {} cannot be downloaded", download.title);
+        set_status(id, DownloadStatus::Failed);
+        return;
+    };
+
+    abort(id);
+    set_status(id, DownloadStatus::Downloading);
+
+    let task = relm4::spawn({
+        let id = id.to_owned();
+
+        async move {
+            let status = match run(&id, url, &download.path).await {
+                Ok(()) => DownloadStatus::Completed,
+                Err(e) => {
+                    error!("Failed to download {}: {e}", download.title);
+                    DownloadStatus::Failed
+                }
+            };
+
+            TASKS
+                .lock()
+                .expect("Failed to lock downloads")
+                .retain(|(task_id, _)| *task_id != id);
+
+            set_status(&id, status);
+        }
+    });
+
+    TASKS
+        .lock()
+        .expect("Failed to lock downloads")
+        .push((id.to_owned(), task));
+}
+
+pub fn cancel(id: &str) {
+    abort(id);
+
+    let removed = {
+        let mut state = DOWNLOADS_STATE.write();
+        let position = state
+            .downloads
+            .iter()
+            .position(|download| download.id == id);
+        position.map(|position| state.downloads.remove(position))
+    };
+
+    if let Some(download) = removed {
+        if let Err(e) = fs::remove_file(&download.path) {
+            error!("Failed to remove {}: {e}", download.path.display());
+        }
+    }
+
+    save();
+}
+
+/// Bytes still available on the disk downloads are saved to
+pub fn free_space() -> Option<u64> {
+    available_space(&location())
+}
+
+async fn run(id: &str, url: Url, path: &Path) -> anyhow::Result<()> {
+    // Resume from what actually made it to the disk
+    let offset = tokio::fs::metadata(path)
+        .await
+        .map_or(0, |metadata| metadata.len());
+
+    let mut request = net::streaming_client().get(url);
+    if offset > 0 {
+        request = request.header(header::RANGE, format!("bytes={offset}-"));
+    }
+
+    let response = request.send().await?;
+
+    // The file was already complete when it got interrupted
+    if offset > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        update(id, |download| {
+            download.downloaded = offset;
+            download.total = Some(offset);
+        });
+
+        return Ok(());
+    }
+
+    let mut response = response.error_for_status()?;
+
+    let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { offset } else { 0 };
+    let total = response.content_length().map(|length| length + downloaded);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(path)
+        .await?;
+
+    update(id, |download| {
+        download.downloaded = downloaded;
+        download.total = total;
+    });
+
+    let mut last_update = Instant::now();
+
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+
+        if last_update.elapsed() >= PROGRESS_INTERVAL {
+            last_update = Instant::now();
+            update(id, |download| download.downloaded = downloaded);
+        }
+    }
+
+    file.flush().await?;
+
+    update(id, |download| {
+        download.downloaded = downloaded;
+        download.total = Some(downloaded);
+    });
+
+    Ok(())
+}
+
+fn find(id: &str) -> Option<Download> {
+    DOWNLOADS_STATE
+        .read_inner()
+        .downloads
+        .iter()
+        .find(|download| download.id == id)
+        .cloned()
+}
+
+fn update<F: FnOnce(&mut Download)>(id: &str, f: F) {
+    let mut state = DOWNLOADS_STATE.write();
+
+    if let Some(download) = state
+        .downloads
+        .iter_mut()
+        .find(|download| download.id == id)
+    {
+        f(download);
+    }
+}
+
+fn set_status(id: &str, status: DownloadStatus) {
+    update(id, |download| download.status = status);
+    save();
+}
+
+fn abort(id: &str) {
+    TASKS
+        .lock()
+        .expect("Failed to lock downloads")
+        .retain(|(task_id, task)| {
+            if task_id == id {
+                task.abort();
+            }
+
+            task_id != id
+        });
+}
+
+fn save() {
+    let Some(store) = STORE.get() else {
+        return;
+    };
+
+    let state = DOWNLOADS_STATE.read_inner();
+
+    if let Err(e) = serde_json::to_vec(&state.downloads)
+        .map_err(std::io::Error::other)
+        .and_then(|contents| fs::write(store, contents))
+    {
+        error!("Failed to save downloads: {e}");
+    }
+}
+
+fn extension(stream: &Stream) -> &str {
+    let name = match &stream.source {
+        StreamSource::Url { url } => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back()),
+        // Engine urls have no file name, torrent streams are named after their file
+        StreamSource::Torrent { .. } => stream
+            .behavior_hints
+            .filename
+            .as_deref()
+            .into_iter()
+            .chain([stream.name.as_str()])
+            .find(|name| torrent::is_video_name(name)),
+        _ => None,
+    };
+
+    name.or(stream.behavior_hints.filename.as_deref())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|extension| !extension.is_empty() && extension.len() <= 4)
+        .unwrap_or(DEFAULT_EXTENSION)
+}
+
+fn unique_path(location: &Path, title: &str, extension: &str, taken: &[PathBuf]) -> PathBuf {
+    let name = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    let mut path = location.join(format!("{name}.{extension}"));
+    let mut count = 1;
+
+    while path.exists() || taken.contains(&path) {
+        count += 1;
+        path = location.join(format!("{name} ({count}).{extension}"));
+    }
+
+    path
+}
+
+fn available_space(path: &Path) -> Option<u64> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: the path is a valid C string and stat is only read when statvfs succeeds
+    unsafe {
+        (libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) == 0).then(|| {
+            let stat = stat.assume_init();
+            stat.f_bavail as u64 * stat.f_frsize as u64
+        })
+    }
+}
@@ -0,0 +1,381 @@
+use std::{convert::Infallible, io::SeekFrom, sync::Arc};
+
+use bytes::{Bytes, BytesMut};
+use futures::stream;
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Full, StreamBody};
+use hyper::{
+    body::{Frame, Incoming},
+    header::{self, HeaderValue},
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use itertools::Itertools;
+use librqbit::ManagedTorrentHandle;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt},
+    net::TcpListener,
+};
+use tracing::{debug, error};
+use url::form_urlencoded;
+
+use super::{stats, Engine, Sources, StreamingGuard};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Body = UnsyncBoxBody<Bytes, std::io::Error>;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateRequest {
+    #[serde(default)]
+    peer_search: Option<PeerSearch>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PeerSearch {
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+pub async fn serve(engine: Arc<Engine>, listener: TcpListener) -> anyhow::Result<()> {
+    loop {
+        let (stream, address) = listener.accept().await?;
+        let engine = engine.clone();
+
+        tokio::spawn(async move {
+            let service = service_fn(move |request| handle(request, engine.clone()));
+
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Engine connection with {address} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    engine: Arc<Engine>,
+) -> Result<Response<Body>, Infallible> {
+    let path = request.uri().path().to_owned();
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect_vec();
+
+    let response = match (request.method(), segments.as_slice()) {
+        (&Method::GET, ["settings"]) => json_response(engine.settings()),
+        (&Method::POST, ["settings"]) => match read_json::<Map<String, Value>>(request).await {
+            Some(values) => {
+                engine.update_settings(values);
+                json_response(json!({ "success": true }))
+            }
+            None => status(StatusCode::BAD_REQUEST),
+        },
+        (&Method::GET, ["device-info"]) => {
+            json_response(json!({ "availableHardwareAccelerations": [] }))
+        }
+        (&Method::GET, ["network-info"]) => {
+            json_response(json!({ "availableInterfaces": ["127.0.0.1"] }))
+        }
+        (&Method::GET, ["stats.json"]) => {
+            let torrents = engine
+                .torrents()
+                .iter()
+                .map(|handle| {
+                    (
+                        handle.info_hash().as_string(),
+                        stats::torrent(&engine, handle, None),
+                    )
+                })
+                .collect::<Map<_, _>>();
+
+            json_response(Value::Object(torrents))
+        }
+        (&Method::POST, [info_hash, "create"]) => {
+            let request = read_json::<CreateRequest>(request)
+                .await
+                .unwrap_or_default();
+            let sources = Sources::parse(request.peer_search.map(|search| search.sources));
+
+            match engine.add(info_hash, sources).await {
+                Ok(handle) => json_response(stats::torrent(&engine, &handle, None)),
+                Err(e) => {
+                    error!("Failed to add torrent {info_hash}: {e}");
+                    status(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }
+        (&Method::GET, [info_hash, file_idx, "stats.json"]) => {
+            let torrent = engine.torrent(info_hash).and_then(|handle| {
+                let file_idx = stats::file_index(&handle, file_idx)?;
+                Some((handle, file_idx))
+            });
+
+            match torrent {
+                Some((handle, file_idx)) => {
+                    json_response(stats::torrent(&engine, &handle, Some(file_idx)))
+                }
+                None => status(StatusCode::NOT_FOUND),
+            }
+        }
+        (&Method::GET, [info_hash, "remove"]) => match engine.remove(info_hash).await {
+            Ok(()) => json_response(json!({})),
+            Err(e) => {
+                debug!("Failed to remove torrent {info_hash}: {e}");
+                status(StatusCode::NOT_FOUND)
+            }
+        },
+        (&Method::GET | &Method::HEAD, [info_hash, file_idx]) => {
+            serve_file(&engine, request, info_hash, file_idx).await
+        }
+        _ => status(StatusCode::NOT_FOUND),
+    };
+
+    Ok(response)
+}
+
+async fn serve_file(
+    engine: &Engine,
+    request: Request<Incoming>,
+    info_hash: &str,
+    file_idx: &str,
+) -> Response<Body> {
+    let sources = request.uri().query().map(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .filter(|(key, _)| key == "tr")
+            .map(|(_, value)| value.to_string())
+            .collect()
+    });
+
+    let handle = match engine.add(info_hash, Sources::parse(sources)).await {
+        Ok(handle) => handle,
+        Err(e) => {
+            error!("Failed to add torrent {info_hash}: {e}");
+            return status(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let Some(file_idx) = stats::file_index(&handle, file_idx) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+
+    if let Err(e) = engine.select(&handle, file_idx).await {
+        error!("Failed to select file {file_idx} of {info_hash}: {e}");
+        return status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let streaming = engine.streaming(info_hash);
+
+    let mut file = match handle.clone().stream(file_idx) {
+        Ok(file) => file,
+        Err(e) => {
+            error!("Failed to stream file {file_idx} of {info_hash}: {e}");
+            return status(StatusCode::NOT_FOUND);
+        }
+    };
+
+    let length = file.len();
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_range(value, length));
+
+    let (start, end) = match range {
+        Some(range) => range,
+        None if request.headers().contains_key(header::RANGE) => {
+            let mut response = status(StatusCode::RANGE_NOT_SATISFIABLE);
+            insert_header(
+                &mut response,
+                header::CONTENT_RANGE,
+                format!("bytes */{length}"),
+            );
+            return response;
+        }
+        None => (0, length.saturating_sub(1)),
+    };
+
+    // An empty file has no last byte, the range above is only a placeholder
+    let content_length = match length {
+        0 => 0,
+        _ => end + 1 - start,
+    };
+
+    if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+        error!("Failed to seek in {info_hash}: {e}");
+        return status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let body = match request.method() {
+        &Method::HEAD => empty(),
+        _ => chunks(file.take(content_length), streaming),
+    };
+
+    let mut response = Response::new(body);
+    insert_header(&mut response, header::ACCEPT_RANGES, "bytes".to_owned());
+    insert_header(
+        &mut response,
+        header::CONTENT_LENGTH,
+        content_length.to_string(),
+    );
+    insert_header(
+        &mut response,
+        header::CONTENT_TYPE,
+        mime(&handle, file_idx).to_owned(),
+    );
+
+    if range.is_some() {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        insert_header(
+            &mut response,
+            header::CONTENT_RANGE,
+            format!("bytes {start}-{end}/{length}"),
+        );
+    }
+
+    response
+}
+
+/// The guard lives as long as the body, so the torrent is not evicted while it is streamed
+fn chunks(reader: impl AsyncRead + Unpin + Send + 'static, guard: StreamingGuard) -> Body {
+    let frames = stream::unfold((reader, guard), |(mut reader, guard)| async move {
+        let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+
+        match reader.read_buf(&mut buffer).await {
+            Ok(0) => None,
+            Ok(_) => Some((Ok(Frame::data(buffer.freeze())), (reader, guard))),
+            Err(e) => Some((Err(e), (reader, guard))),
+        }
+    });
+
+    StreamBody::new(frames).boxed_unsync()
+}
+
+/// Parses a single `bytes=start-end` range, suffix ranges included
+fn parse_range(value: &str, length: u64) -> Option<(u64, u64)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let last = length.checked_sub(1)?;
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        // A suffix longer than the file selects all of it
+        ("", suffix) => (length.saturating_sub(suffix.parse().ok()?), last),
+        (start, "") => (start.parse().ok()?, last),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(last)),
+    };
+
+    (start <= end).then_some((start, end))
+}
+
+fn mime(handle: &ManagedTorrentHandle, file_idx: usize) -> &'static str {
+    stats::file_name(handle, file_idx)
+        .and_then(|name| mime_type(&name))
+        .unwrap_or("application/octet-stream")
+}
+
+/// Mime type of the video files that can be streamed
+pub fn mime_type(name: &str) -> Option<&'static str> {
+    let (_, extension) = name.rsplit_once('.')?;
+
+    match extension.to_lowercase().as_str() {
+        "mkv" => Some("video/x-matroska"),
+        "mp4" | "m4v" => Some("video/mp4"),
+        "webm" => Some("video/webm"),
+        "avi" => Some("video/x-msvideo"),
+        "mov" => Some("video/quicktime"),
+        "ts" => Some("video/mp2t"),
+        _ => None,
+    }
+}
+
+async fn read_json<T: for<'de> Deserialize<'de>>(request: Request<Incoming>) -> Option<T> {
+    let body = request.into_body().collect().await.ok()?.to_bytes();
+    serde_json::from_slice(&body).ok()
+}
+
+fn json_response(value: Value) -> Response<Body> {
+    let mut response = Response::new(full(value.to_string()));
+    insert_header(
+        &mut response,
+        header::CONTENT_TYPE,
+        "application/json".to_owned(),
+    );
+
+    response
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(full(status.to_string()));
+    *response.status_mut() = status;
+
+    response
+}
+
+fn insert_header(response: &mut Response<Body>, name: header::HeaderName, value: String) {
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+fn full<T: Into<Bytes>>(body: T) -> Body {
+    Full::new(body.into())
+        .map_err(|never| match never {})
+        .boxed_unsync()
+}
+
+fn empty() -> Body {
+    full(Bytes::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_range;
+
+    #[test]
+    fn full_range() {
+        assert_eq!(parse_range("bytes=0-99", 100), Some((0, 99)));
+        assert_eq!(parse_range("bytes=10-19", 100), Some((10, 19)));
+    }
+
+    #[test]
+    fn suffix_range() {
+        assert_eq!(parse_range("bytes=-10", 100), Some((90, 99)));
+        assert_eq!(parse_range("bytes=-500", 100), Some((0, 99)));
+        assert_eq!(parse_range("bytes=-0", 100), None);
+    }
+
+    #[test]
+    fn open_ended_range() {
+        assert_eq!(parse_range("bytes=50-", 100), Some((50, 99)));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+    }
+
+    #[test]
+    fn clamped_range() {
+        assert_eq!(parse_range("bytes=90-1000", 100), Some((90, 99)));
+    }
+
+    #[test]
+    fn inverted_range() {
+        assert_eq!(parse_range("bytes=20-10", 100), None);
+    }
+
+    #[test]
+    fn empty_file() {
+        assert_eq!(parse_range("bytes=0-", 0), None);
+        assert_eq!(parse_range("bytes=-10", 0), None);
+    }
+
+    #[test]
+    fn malformed_range() {
+        assert_eq!(parse_range("items=0-10", 100), None);
+        assert_eq!(parse_range("bytes=a-b", 100), None);
+        assert_eq!(parse_range("bytes=10", 100), None);
+    }
+}
@@ -0,0 +1,389 @@
+mod http;
+mod stats;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    net::{Ipv4Addr, SocketAddr},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use itertools::Itertools;
+use librqbit::{
+    limits::LimitsConfig, AddTorrent, AddTorrentOptions, ManagedTorrentHandle,
+    PeerConnectionOptions, Session, SessionOptions, TorrentIdOrHash,
+};
+use serde_json::{json, Map, Value};
+use tokio::net::TcpListener;
+use tracing::{debug, error, info};
+
+use crate::common::net;
+
+const PORT: u16 = 11470;
+const SETTINGS_FILE: &str = "engine-settings.json";
+const CACHE_DIR: &str = "engine-cache";
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// In-process replacement for server.js, only torrent streaming is supported
+pub struct Engine {
+    session: Arc<Session>,
+    settings: RwLock<Map<String, Value>>,
+    settings_path: PathBuf,
+    cache_root: PathBuf,
+    usage: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+/// Where to look for the peers of a torrent, from `peerSearch.sources` or `tr` parameters
+#[derive(Debug, Default)]
+pub struct Sources {
+    trackers: Vec<String>,
+    peers: Vec<SocketAddr>,
+}
+
+impl Sources {
+    /// Keeps `tracker:<url>` sources, and `peer:<address>` ones to reach seeders directly
+    pub fn parse(sources: Option<Vec<String>>) -> Self {
+        let sources = sources.unwrap_or_default();
+
+        Self {
+            trackers: sources
+                .iter()
+                .filter_map(|source| source.strip_prefix("tracker:"))
+                .map(ToOwned::to_owned)
+                .collect(),
+            peers: sources
+                .iter()
+                .filter_map(|source| source.strip_prefix("peer:"))
+                .filter_map(|address| address.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Usage {
+    streams: usize,
+    last_used: Instant,
+}
+
+/// Marks a torrent as streamed until dropped
+pub struct StreamingGuard {
+    info_hash: String,
+    usage: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl Drop for StreamingGuard {
+    fn drop(&mut self) {
+        let mut usage = self.usage.lock().expect("Failed to lock engine usage");
+
+        if let Some(usage) = usage.get_mut(&self.info_hash) {
+            usage.streams = usage.streams.saturating_sub(1);
+            usage.last_used = Instant::now();
+        }
+    }
+}
+
+impl Engine {
+    pub async fn new(data_location: &Path) -> anyhow::Result<Self> {
+        let settings_path = data_location.join(SETTINGS_FILE);
+        let settings = load_settings(&settings_path, data_location);
+
+        let cache_root = settings
+            .get("cacheRoot")
+            .and_then(Value::as_str)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| data_location.join(CACHE_DIR));
+
+        let session = Session::new_with_opts(cache_root.to_owned(), session_options(&settings))
+            .await
+            .context("Failed to create torrent session")?;
+
+        Ok(Self {
+            session,
+            settings: RwLock::new(settings),
+            settings_path,
+            cache_root,
+            usage: Default::default(),
+        })
+    }
+
+    pub async fn bind() -> std::io::Result<TcpListener> {
+        net::bind(Ipv4Addr::LOCALHOST.into(), PORT).await
+    }
+
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> anyhow::Result<()> {
+        tokio::select! {
+            result = http::serve(self.clone(), listener) => result,
+            _ = self.evict_periodically() => Ok(()),
+        }
+    }
+
+    fn streaming(&self, info_hash: &str) -> StreamingGuard {
+        let info_hash = info_hash.to_lowercase();
+
+        self.usage
+            .lock()
+            .expect("Failed to lock engine usage")
+            .entry(info_hash.to_owned())
+            .and_modify(|usage| usage.streams += 1)
+            .or_insert(Usage {
+                streams: 1,
+                last_used: Instant::now(),
+            });
+
+        StreamingGuard {
+            info_hash,
+            usage: self.usage.clone(),
+        }
+    }
+
+    async fn evict_periodically(&self) {
+        let mut interval = tokio::time::interval(EVICTION_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            self.evict().await;
+        }
+    }
+
+    /// Deletes the least recently used torrents that are not streamed while the cache is over
+    /// `cacheSize`, a null size meaning no limit
+    async fn evict(&self) {
+        let Some(cache_size) = self
+            .settings
+            .read()
+            .expect("Failed to read engine settings")
+            .get("cacheSize")
+            .and_then(Value::as_f64)
+        else {
+            return;
+        };
+
+        let mut used = self
+            .torrents()
+            .iter()
+            .map(|handle| handle.stats().progress_bytes)
+            .sum::<u64>();
+
+        let candidates = {
+            let usage = self.usage.lock().expect("Failed to lock engine usage");
+
+            self.torrents()
+                .into_iter()
+                .map(|handle| {
+                    let info_hash = handle.info_hash().as_string();
+                    let usage = usage.get(&info_hash);
+                    (
+                        handle,
+                        info_hash,
+                        usage.map(|usage| (usage.streams, usage.last_used)),
+                    )
+                })
+                .filter(|(_, _, usage)| usage.is_none_or(|(streams, _)| streams == 0))
+                .sorted_by_key(|(_, _, usage)| usage.map(|(_, last_used)| last_used))
+                .collect_vec()
+        };
+
+        for (handle, info_hash, _) in candidates {
+            if used as f64 <= cache_size {
+                break;
+            }
+
+            info!("Evicting torrent {info_hash} from the engine cache");
+            used = used.saturating_sub(handle.stats().progress_bytes);
+
+            if let Err(e) = self
+                .session
+                .delete(TorrentIdOrHash::Id(handle.id()), true)
+                .await
+            {
+                error!("Failed to evict torrent {info_hash}: {e}");
+            }
+
+            self.usage
+                .lock()
+                .expect("Failed to lock engine usage")
+                .remove(&info_hash);
+        }
+    }
+
+    fn settings(&self) -> Value {
+        let settings = self
+            .settings
+            .read()
+            .expect("Failed to read engine settings");
+
+        json!({
+            "baseUrl": format!("http://{}:{PORT}", Ipv4Addr::LOCALHOST),
+            "values": &*settings,
+            "options": [],
+        })
+    }
+
+    fn update_settings(&self, values: Map<String, Value>) {
+        let mut settings = self
+            .settings
+            .write()
+            .expect("Failed to write engine settings");
+        settings.extend(values);
+
+        // The cache location is only read when the session is created, on the next start
+        if let Err(e) = serde_json::to_vec_pretty(&*settings)
+            .map_err(std::io::Error::other)
+            .and_then(|contents| fs::write(&self.settings_path, contents))
+        {
+            error!("Failed to save engine settings: {e}");
+        }
+    }
+
+    fn torrent(&self, info_hash: &str) -> Option<ManagedTorrentHandle> {
+        let id = TorrentIdOrHash::try_from(info_hash).ok()?;
+        self.session.get(id)
+    }
+
+    fn torrents(&self) -> Vec<ManagedTorrentHandle> {
+        self.session
+            .with_torrents(|torrents| torrents.map(|(_, handle)| handle.clone()).collect())
+    }
+
+    /// Adds a torrent without downloading anything, files are selected when streamed
+    async fn add(&self, info_hash: &str, sources: Sources) -> anyhow::Result<ManagedTorrentHandle> {
+        if let Some(handle) = self.torrent(info_hash) {
+            return Ok(handle);
+        }
+
+        debug!("Adding torrent {info_hash}");
+
+        let magnet = format!("magnet:?xt=urn:btih:{info_hash}");
+        let options = AddTorrentOptions {
+            paused: true,
+            overwrite: true,
+            trackers: Some(sources.trackers),
+            initial_peers: Some(sources.peers),
+            ..Default::default()
+        };
+
+        self.session
+            .add_torrent(AddTorrent::from_url(magnet), Some(options))
+            .await?
+            .into_handle()
+            .context("Torrent was not added")
+    }
+
+    async fn remove(&self, info_hash: &str) -> anyhow::Result<()> {
+        let id = TorrentIdOrHash::try_from(info_hash)?;
+        self.session.delete(id, false).await
+    }
+
+    async fn select(&self, handle: &ManagedTorrentHandle, file_idx: usize) -> anyhow::Result<()> {
+        let files = HashSet::from([file_idx]);
+        self.session.update_only_files(handle, &files).await?;
+
+        if handle.is_paused() {
+            self.session.unpause(handle).await?;
+        }
+
+        handle.wait_until_initialized().await
+    }
+}
+
+/// Limits are read when the session is created, they apply from the next start
+fn session_options(settings: &Map<String, Value>) -> SessionOptions {
+    let duration = |key: &str| {
+        settings
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(Duration::from_millis)
+    };
+
+    let download_bps = settings
+        .get("btDownloadSpeedHardLimit")
+        .and_then(Value::as_f64)
+        .and_then(|limit| NonZeroU32::new(limit as u32));
+
+    SessionOptions {
+        peer_opts: Some(PeerConnectionOptions {
+            connect_timeout: duration("btHandshakeTimeout"),
+            read_write_timeout: duration("btRequestTimeout"),
+            ..Default::default()
+        }),
+        ratelimits: LimitsConfig {
+            download_bps,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn load_settings(path: &Path, data_location: &Path) -> Map<String, Value> {
+    let mut settings = default_settings(data_location);
+
+    if let Some(saved) = fs::read(path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<Map<String, Value>>(&contents).ok())
+    {
+        settings.extend(saved);
+    }
+
+    settings
+}
+
+fn default_settings(data_location: &Path) -> Map<String, Value> {
+    let values = json!({
+        "serverVersion": env!("CARGO_PKG_VERSION"),
+        "appPath": data_location,
+        "cacheRoot": data_location.join(CACHE_DIR),
+        "cacheSize": 2147483648.0,
+        "btMaxConnections": 35,
+        "btHandshakeTimeout": 20000,
+        "btRequestTimeout": 4000,
+        "btDownloadSpeedSoftLimit": 2621440.0,
+        "btDownloadSpeedHardLimit": 3670016.0,
+        "btMinPeersForStable": 5,
+        "remoteHttps": "",
+        "localAddonEnabled": false,
+        "transcodeProfile": null,
+        "proxyStreamsEnabled": false,
+    });
+
+    match values {
+        Value::Object(values) => values,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::Sources;
+
+    #[test]
+    fn sources() {
+        let sources = Sources::parse(Some(vec![
+            "tracker:udp://tracker.example:80".to_owned(),
+            "dht:0000000000000000000000000000000000000000".to_owned(),
+            "peer:127.0.0.1:6881".to_owned(),
+            "peer:not an address".to_owned(),
+        ]));
+
+        assert_eq!(sources.trackers, ["udp://tracker.example:80"]);
+        assert_eq!(
+            sources.peers,
+            ["127.0.0.1:6881".parse::<SocketAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn no_sources() {
+        let sources = Sources::parse(None);
+
+        assert!(sources.trackers.is_empty());
+        assert!(sources.peers.is_empty());
+    }
+}
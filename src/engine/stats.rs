@@ -0,0 +1,179 @@
+use librqbit::ManagedTorrentHandle;
+use serde_json::{json, Value};
+
+use super::{http, Engine};
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Torrent statistics in the format returned by server.js
+pub fn torrent(engine: &Engine, handle: &ManagedTorrentHandle, file_idx: Option<usize>) -> Value {
+    let info_hash = handle.info_hash().as_string();
+    let stats = handle.stats();
+    let live = stats.live.as_ref();
+
+    let peers = live.map_or(0, |live| live.snapshot.peer_stats.live);
+    let queued = live.map_or(0, |live| live.snapshot.peer_stats.queued);
+    let seen = live.map_or(0, |live| live.snapshot.peer_stats.seen);
+    let download_speed = live.map_or(0.0, |live| live.download_speed.mbps * MB);
+    let upload_speed = live.map_or(0.0, |live| live.upload_speed.mbps * MB);
+
+    let files = files(handle);
+
+    let settings = engine
+        .settings
+        .read()
+        .expect("Failed to read engine settings");
+    let setting = |key: &str| settings.get(key).cloned().unwrap_or_default();
+
+    let (stream_name, stream_len, stream_progress) = file_idx
+        .and_then(|file_idx| {
+            let (name, length) = files.get(file_idx)?;
+            let downloaded = stats.file_progress.get(file_idx).copied().unwrap_or(0);
+            let progress = match length {
+                0 => 0.0,
+                length => downloaded as f64 / *length as f64,
+            };
+
+            Some((name.to_owned(), *length, progress))
+        })
+        .unwrap_or_default();
+
+    json!({
+        "infoHash": info_hash,
+        "name": handle.name().unwrap_or_default(),
+        "peers": peers,
+        "unchoked": peers,
+        "queued": queued,
+        "unique": seen,
+        "connectionTries": seen,
+        "swarmPaused": handle.is_paused(),
+        "swarmConnections": peers,
+        "swarmSize": seen,
+        "selections": [],
+        "wires": [],
+        "files": files
+            .iter()
+            .map(|(name, length)| json!({ "name": name, "path": name, "length": length }))
+            .collect::<Vec<_>>(),
+        "downloaded": stats.progress_bytes,
+        "uploaded": stats.uploaded_bytes,
+        "downloadSpeed": download_speed,
+        "uploadSpeed": upload_speed,
+        "sources": [{
+            "numFound": seen,
+            "numFoundUniq": seen,
+            "numRequests": 1,
+            "url": format!("dht:{info_hash}"),
+            "lastStarted": "",
+        }],
+        "peerSearchRunning": live.is_some(),
+        "opts": {
+            "connections": setting("btMaxConnections"),
+            "dht": true,
+            "growler": { "flood": 0, "pulse": 39321600 },
+            "handshakeTimeout": setting("btHandshakeTimeout"),
+            "path": engine.cache_root,
+            "peerSearch": { "min": 40, "max": 200, "sources": [format!("dht:{info_hash}")] },
+            "swarmCap": {
+                "minPeers": setting("btMinPeersForStable"),
+                "maxSpeed": setting("btDownloadSpeedHardLimit"),
+            },
+            "timeout": setting("btRequestTimeout"),
+            "tracker": true,
+            "virtual": true,
+        },
+        "streamLen": stream_len,
+        "streamName": stream_name,
+        "streamProgress": stream_progress,
+    })
+}
+
+/// Resolves a file index from a url, `-1` standing for the largest video file as in server.js
+pub fn file_index(handle: &ManagedTorrentHandle, file_idx: &str) -> Option<usize> {
+    resolve_file_index(&files(handle), file_idx)
+}
+
+fn resolve_file_index(files: &[(String, u64)], file_idx: &str) -> Option<usize> {
+    match file_idx.parse::<i64>().ok()? {
+        -1 => largest_video(files),
+        file_idx => usize::try_from(file_idx).ok(),
+    }
+}
+
+fn largest_video(files: &[(String, u64)]) -> Option<usize> {
+    let largest = |video: bool| {
+        files
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, _))| !video || http::mime_type(name).is_some())
+            .max_by_key(|(_, (_, length))| *length)
+            .map(|(file_idx, _)| file_idx)
+    };
+
+    largest(true).or_else(|| largest(false))
+}
+
+pub fn file_name(handle: &ManagedTorrentHandle, file_idx: usize) -> Option<String> {
+    files(handle)
+        .into_iter()
+        .nth(file_idx)
+        .map(|(name, _)| name)
+}
+
+fn files(handle: &ManagedTorrentHandle) -> Vec<(String, u64)> {
+    handle
+        .with_metadata(|metadata| {
+            metadata
+                .file_infos
+                .iter()
+                .map(|file| {
+                    (
+                        file.relative_filename.to_string_lossy().to_string(),
+                        file.len,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_file_index;
+
+    fn files(files: &[(&str, u64)]) -> Vec<(String, u64)> {
+        files
+            .iter()
+            .map(|(name, length)| (name.to_string(), *length))
+            .collect()
+    }
+
+    #[test]
+    fn largest_video() {
+        let files = files(&[
+            ("Sample.mkv", 50),
+            ("Extras.zip", 5000),
+            ("Movie.mp4", 1000),
+            ("Movie.nfo", 1),
+        ]);
+
+        assert_eq!(resolve_file_index(&files, "-1"), Some(2));
+    }
+
+    #[test]
+    fn largest_file_without_videos() {
+        let files = files(&[("Readme.txt", 10), ("Archive.zip", 100)]);
+
+        assert_eq!(resolve_file_index(&files, "-1"), Some(1));
+        assert_eq!(resolve_file_index(&[], "-1"), None);
+    }
+
+    #[test]
+    fn explicit_index() {
+        let files = files(&[("Episode 1.mkv", 10), ("Episode 2.mkv", 20)]);
+
+        assert_eq!(resolve_file_index(&files, "0"), Some(0));
+        assert_eq!(resolve_file_index(&files, "-2"), None);
+        assert_eq!(resolve_file_index(&files, "first"), None);
+    }
+}
@@ -0,0 +1,221 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use relm4::{gtk::glib, JoinHandle, SharedState};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+    process::{Child, Command},
+    time::sleep,
+};
+use tracing::{debug, error};
+use url::Url;
+
+const IPC_CONNECT_ATTEMPTS: u32 = 20;
+const IPC_CONNECT_INTERVAL: Duration = Duration::from_millis(250);
+const TIME_UPDATE_THRESHOLD: f64 = 1000.0;
+
+#[derive(Default, Debug)]
+pub struct ExternalPlayerState {
+    pub running: bool,
+    pub time: f64,
+    pub duration: f64,
+}
+
+pub static EXTERNAL_PLAYER_STATE: SharedState<ExternalPlayerState> = SharedState::new();
+
+#[derive(Deserialize)]
+struct IpcEvent {
+    event: Option<String>,
+    name: Option<String>,
+    data: Option<f64>,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Mpv,
+    Vlc,
+    Other,
+}
+
+pub struct ExternalPlayer {
+    task: JoinHandle<()>,
+}
+
+impl ExternalPlayer {
+    pub fn launch(
+        command: &str,
+        url: &Url,
+        title: &str,
+        subtitles: &[Url],
+        start_time: f64,
+    ) -> io::Result<Self> {
+        let argv = glib::shell_parse_argv(command).map_err(io::Error::other)?;
+        let (program, arguments) = argv
+            .split_first()
+            .ok_or_else(|| io::Error::other("Empty external player command"))?;
+
+        let kind = match Path::new(program)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+        {
+            Some("mpv") => Kind::Mpv,
+            Some("vlc") => Kind::Vlc,
+            _ => Kind::Other,
+        };
+
+        let socket =
+            glib::user_runtime_dir().join(format!("losange-mpv-{}.sock", std::process::id()));
+        let start_seconds = (start_time / 1000.0).floor();
+
+        let mut process = Command::new(program);
+        process
+            .args(arguments)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .kill_on_drop(false);
+
+        match kind {
+            Kind::Mpv => {
+                process.arg(format!("--force-media-title={title}"));
+                process.arg(format!("--input-ipc-server={}", socket.display()));
+
+                if start_seconds > 0.0 {
+                    process.arg(format!("--start={start_seconds}"));
+                }
+
+                for subtitle in subtitles {
+                    process.arg(format!("--sub-file={subtitle}"));
+                }
+            }
+            Kind::Vlc => {
+                process.arg(format!("--meta-title={title}"));
+
+                if start_seconds > 0.0 {
+                    process.arg(format!("--start-time={start_seconds}"));
+                }
+
+                for subtitle in subtitles {
+                    process.arg(format!("--input-slave={subtitle}"));
+                }
+            }
+            Kind::Other => {}
+        }
+
+        process.arg(url.as_str());
+
+        let child = process.spawn()?;
+
+        {
+            let mut state = EXTERNAL_PLAYER_STATE.write();
+            state.running = true;
+            state.time = start_time;
+            state.duration = 0.0;
+        }
+
+        let task = relm4::spawn(async move {
+            let ipc = match kind {
+                Kind::Mpv => Some(socket),
+                _ => None,
+            };
+
+            Self::watch(child, ipc).await;
+            EXTERNAL_PLAYER_STATE.write().running = false;
+        });
+
+        Ok(Self { task })
+    }
+
+    async fn watch(mut child: Child, ipc: Option<PathBuf>) {
+        if let Some(socket) = &ipc {
+            tokio::select! {
+                result = Self::track(socket) => {
+                    if let Err(e) = result {
+                        debug!("External player IPC ended: {e}");
+                    }
+                }
+                _ = child.wait() => {}
+            }
+
+            let _ = std::fs::remove_file(socket);
+        }
+
+        if let Err(e) = child.wait().await {
+            error!("Failed to wait for external player: {e}");
+        }
+    }
+
+    async fn track(socket: &Path) -> io::Result<()> {
+        // The socket only exists once the player is done starting up
+        let mut attempts = 0;
+        let stream = loop {
+            match UnixStream::connect(socket).await {
+                Ok(stream) => break stream,
+                Err(e) if attempts >= IPC_CONNECT_ATTEMPTS => return Err(e),
+                Err(_) => {
+                    attempts += 1;
+                    sleep(IPC_CONNECT_INTERVAL).await;
+                }
+            }
+        };
+
+        let (reader, mut writer) = stream.into_split();
+
+        writer
+            .write_all(
+                concat!(
+                    r#"{"command":["observe_property",1,"time-pos"]}"#,
+                    "\n",
+                    r#"{"command":["observe_property",2,"duration"]}"#,
+                    "\n",
+                )
+                .as_bytes(),
+            )
+            .await?;
+
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            let Ok(event) = serde_json::from_str::<IpcEvent>(&line) else {
+                continue;
+            };
+
+            if event.event.as_deref() != Some("property-change") {
+                continue;
+            }
+
+            let Some(value) = event.data.map(|seconds| seconds * 1000.0) else {
+                continue;
+            };
+
+            let state = EXTERNAL_PLAYER_STATE.read_inner();
+
+            // Time changes on every frame, only report meaningful updates
+            match event.name.as_deref() {
+                Some("time-pos") if (value - state.time).abs() >= TIME_UPDATE_THRESHOLD => {
+                    drop(state);
+                    EXTERNAL_PLAYER_STATE.write().time = value;
+                }
+                Some("duration") if value != state.duration => {
+                    drop(state);
+                    EXTERNAL_PLAYER_STATE.write().duration = value;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ExternalPlayer {
+    fn drop(&mut self) {
+        self.task.abort();
+        EXTERNAL_PLAYER_STATE.write().running = false;
+    }
+}
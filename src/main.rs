@@ -1,11 +1,22 @@
+mod addon_updates;
 mod app;
+mod cast;
 mod common;
 mod components;
 mod constants;
+mod dbus;
+mod deep_link;
 mod dialogs;
+mod downloads;
+mod engine;
+mod external_player;
 mod mpris;
+mod node;
 mod pages;
+mod remote;
+mod search_provider;
 mod server;
+mod server_urls;
 
 use std::ptr;
 
@@ -13,12 +24,16 @@ use app::{App, AppMsg};
 use clap::Parser;
 use constants::APP_ID;
 
-use gtk::prelude::ApplicationExt;
+use gtk::{
+    gio, glib,
+    prelude::{ApplicationCommandLineExt, ApplicationExt, GtkApplicationExt, GtkWindowExt},
+};
 use relm4::{
     actions::{AccelsPlus, RelmAction, RelmActionGroup},
     gtk, MessageBroker, RelmApp,
 };
 use rust_i18n::i18n;
+use url::Url;
 
 include!(concat!(env!("OUT_DIR"), "/icons.rs"));
 
@@ -34,6 +49,9 @@ pub static APP_BROKER: MessageBroker<AppMsg> = MessageBroker::new();
 pub struct Args {
     #[arg(short, long)]
     pub open: Option<String>,
+    /// Handled by GApplication when started through D-Bus activation
+    #[arg(long = "gapplication-service", hide = true)]
+    pub service: bool,
 }
 
 fn main() {
@@ -69,10 +87,53 @@ fn main() {
 
     app.set_accelerators_for_action::<QuitAction>(&["<Control>q"]);
 
-    let args = Args::parse();
+    // Parse locally first so that --help and invalid arguments are reported by this process
+    Args::parse();
 
     let app = RelmApp::new(APP_ID);
-    app.with_args(vec![])
+
+    let application = relm4::main_application();
+    application.set_flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE);
+
+    // Registered at startup so that a D-Bus activated instance answers searches without a window
+    application.connect_startup(|application| {
+        if let Some(connection) = application.dbus_connection() {
+            search_provider::register(&connection);
+        }
+    });
+    application.connect_command_line(|application, command_line| {
+        let args = match Args::try_parse_from(command_line.arguments()) {
+            Ok(args) => args,
+            Err(e) => {
+                command_line.printerr_literal(&e.to_string());
+                return glib::ExitCode::FAILURE;
+            }
+        };
+
+        if let Some(location) = args.open.filter(|location| !location.is_empty()) {
+            // Relative paths are resolved against the directory of the invoking process, which
+            // can differ from the primary instance
+            let location = match Url::parse(&location) {
+                Ok(_) => location,
+                Err(_) => command_line
+                    .create_file_for_arg(&location)
+                    .path()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or(location),
+            };
+
+            APP_BROKER.send(AppMsg::OpenLocation(location));
+        }
+
+        match application.active_window() {
+            Some(window) => window.present(),
+            None => application.activate(),
+        }
+
+        glib::ExitCode::SUCCESS
+    });
+
+    app.with_args(std::env::args().collect())
         .with_broker(&APP_BROKER)
-        .run_async::<App>(args);
+        .run_async::<App>(());
 }
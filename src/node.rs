@@ -0,0 +1,150 @@
+use std::{
+    env,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use itertools::Itertools;
+use rust_i18n::t;
+use shellexpand::tilde;
+use tokio::{process::Command, time::timeout};
+use tracing::{debug, warn};
+
+use crate::constants::NODE_MIN_VERSION;
+
+const NODE_BINARIES: &[&str] = &["node", "nodejs"];
+const COMMON_DIRS: &[&str] = &[
+    "/app/bin",
+    "/usr/local/bin",
+    "/usr/bin",
+    "/opt/homebrew/bin",
+    "~/.local/bin",
+    "~/.volta/bin",
+];
+const NVM_DIR: &str = "~/.nvm/versions/node";
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRuntime {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeError {
+    NotFound,
+    Unsupported { path: PathBuf, version: String },
+}
+
+impl Display for NodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeError::NotFound => {
+                write!(f, "{}", t!("node_not_found", required = NODE_MIN_VERSION))
+            }
+            NodeError::Unsupported { path, version } => write!(
+                f,
+                "{}",
+                t!(
+                    "node_unsupported",
+                    path = path.display(),
+                    version = version,
+                    required = NODE_MIN_VERSION
+                )
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NodeError {}
+
+/// Finds a usable node binary, trying the configured path first, then `PATH` and common install locations
+pub async fn detect(configured: &str) -> Result<NodeRuntime, NodeError> {
+    let mut unsupported = None;
+
+    for path in candidates(configured) {
+        let Some(version) = version(&path).await else {
+            continue;
+        };
+
+        if major(&version).is_some_and(|major| major >= NODE_MIN_VERSION) {
+            debug!("Using node {version} from {}", path.display());
+            return Ok(NodeRuntime { path, version });
+        }
+
+        warn!("Ignoring node {version} from {}", path.display());
+        unsupported.get_or_insert(NodeError::Unsupported { path, version });
+    }
+
+    Err(unsupported.unwrap_or(NodeError::NotFound))
+}
+
+fn candidates(configured: &str) -> Vec<PathBuf> {
+    let configured = Some(configured.trim())
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(tilde(path).to_string()));
+
+    let path_dirs = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).collect_vec())
+        .unwrap_or_default();
+
+    let common_dirs = COMMON_DIRS
+        .iter()
+        .map(|dir| PathBuf::from(tilde(dir).to_string()));
+
+    configured
+        .into_iter()
+        .chain(
+            path_dirs
+                .into_iter()
+                .chain(common_dirs)
+                .chain(nvm_dirs())
+                .flat_map(|dir| NODE_BINARIES.iter().map(move |binary| dir.join(binary))),
+        )
+        .filter(|path| path.is_file())
+        .unique()
+        .collect()
+}
+
+fn nvm_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = Path::new(&tilde(NVM_DIR).to_string()).read_dir() else {
+        return vec![];
+    };
+
+    // Newest versions first
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .sorted_by_key(|path| {
+            path.file_name()
+                .and_then(|name| major(&name.to_string_lossy()))
+        })
+        .rev()
+        .map(|path| path.join("bin"))
+        .collect()
+}
+
+async fn version(path: &Path) -> Option<String> {
+    let output = timeout(
+        VERSION_TIMEOUT,
+        Command::new(path).arg("--version").output(),
+    )
+    .await
+    .ok()?
+    .map_err(|e| debug!("Failed to run {}: {e}", path.display()))
+    .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn major(version: &str) -> Option<u32> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .next()
+        .and_then(|major| major.parse().ok())
+}
@@ -5,7 +5,12 @@ use relm4::{
     ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent,
 };
 use rust_i18n::t;
-use stremio_core_losange::models::{self, addon_details::ADDON_DETAILS_STATE};
+use serde_json::{Map, Value};
+use stremio_core_losange::{
+    addon_config::{self, ConfigField, ConfigFieldType},
+    models::{self, addon_details::ADDON_DETAILS_STATE},
+};
+use tracing::{debug, error};
 use url::Url;
 
 use crate::{
@@ -24,10 +29,25 @@ pub enum AddonPageInput {
     Install,
     Uninstall,
     Configure,
+    ConfigLoaded((Url, Vec<ConfigField>)),
+    SaveConfig,
+    ConfigSaved(Option<Url>),
+}
+
+enum ConfigRow {
+    Text(adw::EntryRow),
+    Password(adw::PasswordEntryRow),
+    Number(adw::SpinRow),
+    Select(adw::ComboRow),
+    Checkbox(adw::SwitchRow),
 }
 
 pub struct AddonPage {
     icon: AsyncController<Image>,
+    config_url: Option<Url>,
+    config_rows: Vec<(ConfigField, ConfigRow)>,
+    config_list: gtk::ListBox,
+    saving: bool,
 }
 
 #[relm4::component(pub)]
@@ -143,7 +163,7 @@ impl SimpleComponent for AddonPage {
                                         set_width_request: 105,
                                         set_label: &t!("configure"),
                                         #[watch]
-                                        set_visible: addon.configuration_required && !addon.installed,
+                                        set_visible: addon.configuration_required && !addon.installed && model.config_rows.is_empty(),
                                         connect_clicked => AddonPageInput::Configure,
                                     },
 
@@ -159,7 +179,7 @@ impl SimpleComponent for AddonPage {
                                     gtk::Button {
                                         set_icon_name: "emblem-system-symbolic",
                                         #[watch]
-                                        set_visible: !addon.configuration_required && addon.configurable,
+                                        set_visible: !addon.configuration_required && addon.configurable && model.config_rows.is_empty(),
                                         connect_clicked => AddonPageInput::Configure,
                                     }
                                 }
@@ -170,7 +190,35 @@ impl SimpleComponent for AddonPage {
                                 set_wrap: true,
                                 #[watch]
                                 set_label: &addon.description,
-                            }
+                            },
+
+                            adw::PreferencesGroup {
+                                set_title: &t!("configuration"),
+                                set_margin_bottom: 26,
+                                #[watch]
+                                set_visible: !model.config_rows.is_empty(),
+
+                                #[local_ref]
+                                config_list -> gtk::ListBox {
+                                    add_css_class: css::classes::BOXED_LIST,
+                                    set_selection_mode: gtk::SelectionMode::None,
+                                },
+
+                                gtk::Button {
+                                    add_css_class: css::classes::SUGGESTED_ACTION,
+                                    set_halign: gtk::Align::End,
+                                    set_margin_top: 12,
+                                    set_width_request: 105,
+                                    #[watch]
+                                    set_label: &match addon.installed {
+                                        true => t!("save"),
+                                        false => t!("install"),
+                                    },
+                                    #[watch]
+                                    set_sensitive: !model.saving,
+                                    connect_clicked => AddonPageInput::SaveConfig,
+                                },
+                            },
                         },
                         None => {
                             #[template]
@@ -201,9 +249,16 @@ impl SimpleComponent for AddonPage {
             )
             .detach();
 
-        let model = AddonPage { icon };
+        let model = AddonPage {
+            icon,
+            config_url: None,
+            config_rows: vec![],
+            config_list: gtk::ListBox::default(),
+            saving: false,
+        };
 
         let icon = model.icon.widget();
+        let config_list = &model.config_list;
         let widgets = view_output!();
 
         ComponentParts { model, widgets }
@@ -213,7 +268,7 @@ impl SimpleComponent for AddonPage {
         let state = ADDON_DETAILS_STATE.read_inner();
     }
 
-    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             AddonPageInput::Load(transport_url) => {
                 self.icon.emit(ImageInput::Unload);
@@ -222,7 +277,11 @@ impl SimpleComponent for AddonPage {
             AddonPageInput::Update => {
                 let state = ADDON_DETAILS_STATE.read_inner();
                 if let Some(addon) = &state.addon {
-                    self.icon.emit(ImageInput::Update(addon.icon.to_owned()))
+                    self.icon.emit(ImageInput::Update(addon.icon.to_owned()));
+
+                    if self.config_url.as_ref() != Some(&addon.manifest_url) {
+                        self.load_config(addon.manifest_url.to_owned(), &sender);
+                    }
                 }
             }
             AddonPageInput::Install => {
@@ -241,6 +300,170 @@ impl SimpleComponent for AddonPage {
                     APP_BROKER.send(AppMsg::OpenExternal(configure_url));
                 }
             }
+            AddonPageInput::ConfigLoaded((url, fields)) => {
+                if self.config_url.as_ref() == Some(&url) {
+                    self.build_config_rows(&url, fields);
+                }
+            }
+            AddonPageInput::SaveConfig => {
+                let Some(url) = &self.config_url else {
+                    return;
+                };
+
+                let values = match self.config_values() {
+                    Ok(values) => values,
+                    Err(field) => {
+                        let message = t!("config_required", field = field).to_string();
+                        APP_BROKER.send(AppMsg::Toast((message, 3)));
+                        return;
+                    }
+                };
+
+                let Some(configured_url) = addon_config::configured_url(url, &values) else {
+                    APP_BROKER.send(AppMsg::Toast((t!("error_configure").to_string(), 3)));
+                    return;
+                };
+
+                self.saving = true;
+
+                relm4::spawn(async move {
+                    let result = match addon_config::fetch_descriptor(&configured_url).await {
+                        Ok(descriptor) => {
+                            models::ctx::replace_addon(descriptor);
+                            Some(configured_url)
+                        }
+                        Err(e) => {
+                            error!("Failed to configure addon: {e}");
+                            None
+                        }
+                    };
+
+                    sender.input(AddonPageInput::ConfigSaved(result));
+                });
+            }
+            AddonPageInput::ConfigSaved(url) => {
+                self.saving = false;
+
+                let message = match url {
+                    Some(url) => {
+                        sender.input(AddonPageInput::Load(url));
+                        t!("addon_configured")
+                    }
+                    None => t!("error_configure"),
+                };
+
+                APP_BROKER.send(AppMsg::Toast((message.to_string(), 3)));
+            }
+        }
+    }
+}
+
+impl AddonPage {
+    fn load_config(&mut self, url: Url, sender: &ComponentSender<Self>) {
+        self.config_url = Some(url.to_owned());
+        self.config_rows.clear();
+        self.config_list.remove_all();
+
+        let sender = sender.input_sender().clone();
+        relm4::spawn(async move {
+            let fields = addon_config::fetch_fields(&url).await.unwrap_or_else(|e| {
+                debug!("No configuration fields for {url}: {e}");
+                vec![]
+            });
+
+            sender.emit(AddonPageInput::ConfigLoaded((url, fields)));
+        });
+    }
+
+    fn build_config_rows(&mut self, url: &Url, fields: Vec<ConfigField>) {
+        let values = addon_config::current_values(url);
+
+        for field in fields {
+            let value = values.get(&field.key).or(field.default.as_ref());
+            let text = value.map(Self::value_text).unwrap_or_default();
+
+            let row = match field.r#type {
+                ConfigFieldType::Text => {
+                    let row = adw::EntryRow::builder()
+                        .title(field.title())
+                        .text(&text)
+                        .build();
+                    self.config_list.append(&row);
+                    ConfigRow::Text(row)
+                }
+                ConfigFieldType::Password => {
+                    let row = adw::PasswordEntryRow::builder()
+                        .title(field.title())
+                        .text(&text)
+                        .build();
+                    self.config_list.append(&row);
+                    ConfigRow::Password(row)
+                }
+                ConfigFieldType::Number => {
+                    let row = adw::SpinRow::with_range(i32::MIN as f64, i32::MAX as f64, 1.0);
+                    row.set_title(field.title());
+                    row.set_value(text.parse().unwrap_or_default());
+                    self.config_list.append(&row);
+                    ConfigRow::Number(row)
+                }
+                ConfigFieldType::Select => {
+                    let options = field.options.iter().map(String::as_str).collect::<Vec<_>>();
+                    let row = adw::ComboRow::builder()
+                        .title(field.title())
+                        .model(&gtk::StringList::new(&options))
+                        .build();
+                    let selected = field.options.iter().position(|option| *option == text);
+                    row.set_selected(selected.unwrap_or_default() as u32);
+                    self.config_list.append(&row);
+                    ConfigRow::Select(row)
+                }
+                ConfigFieldType::Checkbox => {
+                    let row = adw::SwitchRow::builder()
+                        .title(field.title())
+                        .active(matches!(value, Some(Value::Bool(true))) || text == "checked")
+                        .build();
+                    self.config_list.append(&row);
+                    ConfigRow::Checkbox(row)
+                }
+            };
+
+            self.config_rows.push((field, row));
+        }
+    }
+
+    /// The values entered in the form, or the title of a required field left empty
+    fn config_values(&self) -> Result<Map<String, Value>, String> {
+        let mut values = Map::new();
+
+        for (field, row) in &self.config_rows {
+            let value = match row {
+                ConfigRow::Text(row) => Some(row.text().to_string()),
+                ConfigRow::Password(row) => Some(row.text().to_string()),
+                ConfigRow::Number(row) => Some(row.value().to_string()),
+                ConfigRow::Select(row) => field.options.get(row.selected() as usize).cloned(),
+                // Addons built with the addon SDK expect checked boxes as "checked"
+                ConfigRow::Checkbox(row) => row.is_active().then(|| "checked".to_owned()),
+            }
+            .filter(|value| !value.is_empty());
+
+            match value {
+                Some(value) => {
+                    values.insert(field.key.to_owned(), Value::String(value));
+                }
+                None if field.required && field.r#type != ConfigFieldType::Checkbox => {
+                    return Err(field.title().to_owned());
+                }
+                None => {}
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn value_text(value: &Value) -> String {
+        match value {
+            Value::String(value) => value.to_owned(),
+            value => value.to_string(),
         }
     }
 }
@@ -1,8 +1,10 @@
 use crate::{
+    addon_updates::{AddonChange, ManifestPart},
     common::format::Format,
     components::image::{init::ImageInit, Image},
 };
 use adw::prelude::*;
+use gtk::{gdk, glib};
 use relm4::{
     adw,
     factory::{DynamicIndex, FactoryComponent},
@@ -10,22 +12,38 @@ use relm4::{
     prelude::{AsyncComponent, AsyncComponentController, AsyncController},
     FactorySender, RelmWidgetExt,
 };
+use rust_i18n::t;
 use stremio_core_losange::types::addon::Addon;
+use url::Url;
+
+#[derive(Debug)]
+pub enum AddonRowInput {
+    SetChanges(Vec<AddonChange>),
+    Upgrade,
+}
 
 #[derive(Debug)]
 pub enum AddonRowOutput {
     Clicked(usize),
+    Moved(Url, Url),
+    Toggled(Url, bool),
+    Upgrade(Url),
 }
 
 pub struct AddonRow {
     pub icon: AsyncController<Image>,
     pub title: String,
     pub description: String,
+    pub manifest_url: Url,
+    pub installed: bool,
+    pub protected: bool,
+    pub disabled: bool,
+    pub changes: Vec<AddonChange>,
 }
 
 #[relm4::factory(pub)]
 impl FactoryComponent for AddonRow {
-    type Input = ();
+    type Input = AddonRowInput;
     type Output = AddonRowOutput;
     type Init = Addon;
     type CommandOutput = ();
@@ -42,6 +60,13 @@ impl FactoryComponent for AddonRow {
                 sender.output_sender().emit(AddonRowOutput::Clicked(index.current_index()));
             },
 
+            #[name(drag_handle)]
+            add_prefix = &gtk::Image {
+                set_icon_name: Some("list-drag-handle-symbolic"),
+                set_tooltip_text: Some(&t!("drag_to_reorder")),
+                set_visible: self.installed && !self.protected,
+            },
+
             add_prefix = &gtk::Box {
                 add_css_class: "medium-icon",
                 set_halign: gtk::Align::Center,
@@ -57,6 +82,50 @@ impl FactoryComponent for AddonRow {
                 icon -> adw::Clamp,
             },
 
+            add_suffix = &gtk::MenuButton {
+                add_css_class: "suggested-action",
+                add_css_class: "pill",
+                set_valign: gtk::Align::Center,
+                set_label: &t!("update_available"),
+
+                #[watch]
+                set_visible: !self.changes.is_empty(),
+
+                #[wrap(Some)]
+                set_popover = &gtk::Popover {
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 12,
+                        set_margin_all: 6,
+
+                        gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_wrap: true,
+                            set_max_width_chars: 40,
+
+                            #[watch]
+                            set_label: &self.changes_summary(),
+                        },
+
+                        gtk::Button {
+                            add_css_class: "suggested-action",
+                            set_label: &t!("upgrade"),
+                            connect_clicked => AddonRowInput::Upgrade,
+                        },
+                    },
+                },
+            },
+
+            add_suffix = &gtk::Switch {
+                set_valign: gtk::Align::Center,
+                set_tooltip_text: Some(&t!("addon_enabled")),
+                set_visible: self.installed,
+                set_active: !self.disabled,
+                connect_active_notify[sender, manifest_url = self.manifest_url.clone()] => move |switch| {
+                    sender.output_sender().emit(AddonRowOutput::Toggled(manifest_url.clone(), switch.is_active()));
+                },
+            },
+
             add_suffix = &gtk::Image {
                 set_margin_start: 6,
                 set_icon_name: Some("right"),
@@ -79,6 +148,26 @@ impl FactoryComponent for AddonRow {
             icon,
             title: init.name,
             description: init.description,
+            manifest_url: init.manifest_url,
+            installed: init.installed,
+            protected: init.protected,
+            disabled: init.disabled,
+            changes: vec![],
+        }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
+        match message {
+            AddonRowInput::SetChanges(changes) => {
+                self.changes = changes;
+            }
+            AddonRowInput::Upgrade => {
+                self.changes.clear();
+
+                sender
+                    .output(AddonRowOutput::Upgrade(self.manifest_url.to_owned()))
+                    .ok();
+            }
         }
     }
 
@@ -92,6 +181,94 @@ impl FactoryComponent for AddonRow {
         let icon = self.icon.widget();
         let widgets = view_output!();
 
+        // Protected addons cannot be reinstalled, so they are neither moved nor moved onto
+        if self.installed && !self.protected {
+            self.setup_drag_and_drop(&root, &widgets.drag_handle, &sender);
+        }
+
         widgets
     }
 }
+
+impl AddonRow {
+    fn changes_summary(&self) -> String {
+        self.changes
+            .iter()
+            .map(|change| match change {
+                AddonChange::Version(from, to) => {
+                    t!("addon_change_version", from = from, to = to).to_string()
+                }
+                AddonChange::Added(part, value) => {
+                    t!("addon_change_added", part = part_name(part), value = value).to_string()
+                }
+                AddonChange::Removed(part, value) => t!(
+                    "addon_change_removed",
+                    part = part_name(part),
+                    value = value
+                )
+                .to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Rows are dragged by their handle and dropped on the row whose place they take
+    fn setup_drag_and_drop(
+        &self,
+        row: &adw::ActionRow,
+        drag_handle: &gtk::Image,
+        sender: &FactorySender<Self>,
+    ) {
+        let drag_source = gtk::DragSource::new();
+        drag_source.set_actions(gdk::DragAction::MOVE);
+
+        let manifest_url = self.manifest_url.to_string();
+        drag_source.connect_prepare(move |_, _, _| {
+            Some(gdk::ContentProvider::for_value(&manifest_url.to_value()))
+        });
+
+        drag_source.connect_drag_begin(glib::clone!(
+            #[weak]
+            row,
+            move |source, _| {
+                let paintable = gtk::WidgetPaintable::new(Some(&row));
+                source.set_icon(Some(&paintable), 0, 0);
+            }
+        ));
+
+        drag_handle.add_controller(drag_source);
+
+        let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+
+        let target_url = self.manifest_url.clone();
+        let sender = sender.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Some(source_url) = value
+                .get::<String>()
+                .ok()
+                .and_then(|url| Url::parse(&url).ok())
+            else {
+                return false;
+            };
+
+            if source_url != target_url {
+                sender
+                    .output_sender()
+                    .emit(AddonRowOutput::Moved(source_url, target_url.clone()));
+            }
+
+            true
+        });
+
+        row.add_controller(drop_target);
+    }
+}
+
+fn part_name(part: &ManifestPart) -> String {
+    match part {
+        ManifestPart::Resource => t!("addon_part_resource"),
+        ManifestPart::Type => t!("addon_part_type"),
+        ManifestPart::Catalog => t!("addon_part_catalog"),
+    }
+    .to_string()
+}
@@ -1,7 +1,8 @@
 mod addon_row;
 
-use addon_row::{AddonRow, AddonRowOutput};
+use addon_row::{AddonRow, AddonRowInput, AddonRowOutput};
 use adw::prelude::*;
+use gtk::gio;
 use relm4::prelude::*;
 use relm4::{adw, factory::FactoryVecDeque, gtk, AsyncComponentSender, RelmWidgetExt};
 use rust_i18n::t;
@@ -10,10 +11,12 @@ use stremio_core_losange::{
     stremio_core::constants::OFFICIAL_ADDONS,
     types::addon::Addon,
 };
+use url::Url;
 
+use crate::addon_updates::{self, ADDON_UPDATES_STATE};
 use crate::app::AppMsg;
 use crate::components::spinner::Spinner;
-use crate::constants::COMMUNITY_MANIFESTS;
+use crate::constants::{APP_ID, COMMUNITY_MANIFESTS};
 use crate::APP_BROKER;
 
 #[derive(Debug)]
@@ -23,9 +26,13 @@ pub enum AddonsInput {
     LoadCommunity,
     UpdateInstalled,
     UpdateCommunity,
+    UpdateChanges,
     InstalledAddonClicked(usize),
     OfficialAddonClicked(usize),
     CommunityAddonClicked(usize),
+    AddonMoved(Url, Url),
+    AddonToggled(Url, bool),
+    UpgradeAddon(Url),
     ManifestChanged(usize),
 }
 
@@ -34,6 +41,7 @@ pub struct Addons {
     official_list: FactoryVecDeque<AddonRow>,
     community_list: FactoryVecDeque<AddonRow>,
     manifest_dropdown: gtk::DropDown,
+    settings: gio::Settings,
 }
 
 #[relm4::component(pub async)]
@@ -165,23 +173,33 @@ impl AsyncComponent for Addons {
 
         INSTALLED_ADDONS_STATE.subscribe(sender.input_sender(), |_| AddonsInput::UpdateInstalled);
         REMOTE_ADDONS_STATE.subscribe(sender.input_sender(), |_| AddonsInput::UpdateCommunity);
+        ADDON_UPDATES_STATE.subscribe(sender.input_sender(), |_| AddonsInput::UpdateChanges);
 
         let installed_list = FactoryVecDeque::builder()
             .launch(gtk::ListBox::new())
             .forward(sender.input_sender(), |msg| match msg {
                 AddonRowOutput::Clicked(index) => AddonsInput::InstalledAddonClicked(index),
+                AddonRowOutput::Moved(url, target) => AddonsInput::AddonMoved(url, target),
+                AddonRowOutput::Toggled(url, enabled) => AddonsInput::AddonToggled(url, enabled),
+                AddonRowOutput::Upgrade(url) => AddonsInput::UpgradeAddon(url),
             });
 
         let official_list = FactoryVecDeque::builder()
             .launch(gtk::ListBox::new())
             .forward(sender.input_sender(), |msg| match msg {
                 AddonRowOutput::Clicked(index) => AddonsInput::OfficialAddonClicked(index),
+                AddonRowOutput::Moved(url, target) => AddonsInput::AddonMoved(url, target),
+                AddonRowOutput::Toggled(url, enabled) => AddonsInput::AddonToggled(url, enabled),
+                AddonRowOutput::Upgrade(url) => AddonsInput::UpgradeAddon(url),
             });
 
         let community_list = FactoryVecDeque::builder()
             .launch(gtk::ListBox::new())
             .forward(sender.input_sender(), |msg| match msg {
                 AddonRowOutput::Clicked(index) => AddonsInput::CommunityAddonClicked(index),
+                AddonRowOutput::Moved(url, target) => AddonsInput::AddonMoved(url, target),
+                AddonRowOutput::Toggled(url, enabled) => AddonsInput::AddonToggled(url, enabled),
+                AddonRowOutput::Upgrade(url) => AddonsInput::UpgradeAddon(url),
             });
 
         let manifest_dropdown = gtk::DropDown::from_strings(&[&t!("default"), &t!("alternative")]);
@@ -191,6 +209,7 @@ impl AsyncComponent for Addons {
             official_list,
             community_list,
             manifest_dropdown,
+            settings: gio::Settings::new(APP_ID),
         };
 
         let installed_list = model.installed_list.widget();
@@ -225,10 +244,12 @@ impl AsyncComponent for Addons {
                 models::remote_addons::load(COMMUNITY_MANIFESTS[0]);
             }
             AddonsInput::UpdateInstalled => {
-                let state = INSTALLED_ADDONS_STATE.read_inner();
+                let addons = INSTALLED_ADDONS_STATE.read_inner().addons.to_owned();
 
                 self.installed_list.guard().clear();
-                self.installed_list.extend(state.addons.to_owned());
+                self.installed_list.extend(addons);
+
+                self.update_changes();
             }
             AddonsInput::UpdateCommunity => {
                 let state = REMOTE_ADDONS_STATE.read_inner();
@@ -236,6 +257,9 @@ impl AsyncComponent for Addons {
                 self.community_list.guard().clear();
                 self.community_list.extend(state.addons.to_owned());
             }
+            AddonsInput::UpdateChanges => {
+                self.update_changes();
+            }
             AddonsInput::InstalledAddonClicked(index) => {
                 let state = INSTALLED_ADDONS_STATE.read_inner();
                 if let Some(addon) = state.addons.get(index) {
@@ -253,6 +277,36 @@ impl AsyncComponent for Addons {
                     APP_BROKER.send(AppMsg::OpenAddon(addon.manifest_url.to_owned()));
                 }
             }
+            AddonsInput::AddonMoved(url, target) => {
+                models::ctx::move_addon(&url, &target);
+            }
+            AddonsInput::AddonToggled(url, enabled) => {
+                let mut disabled = self
+                    .settings
+                    .strv("disabled-addons")
+                    .iter()
+                    .filter_map(|url| Url::parse(url.as_str()).ok())
+                    .collect::<Vec<_>>();
+
+                if disabled.contains(&url) != enabled {
+                    return;
+                }
+
+                match enabled {
+                    true => disabled.retain(|disabled_url| *disabled_url != url),
+                    false => disabled.push(url),
+                }
+
+                let urls = disabled.iter().map(Url::as_str).collect::<Vec<_>>();
+                let _ = self.settings.set_strv("disabled-addons", urls);
+
+                models::ctx::set_disabled_addons(disabled);
+            }
+            AddonsInput::UpgradeAddon(url) => {
+                if addon_updates::upgrade(&url) {
+                    APP_BROKER.send(AppMsg::Toast((t!("addon_upgraded").to_string(), 3)));
+                }
+            }
             AddonsInput::ManifestChanged(id) => {
                 if let Some(manifest) = COMMUNITY_MANIFESTS.get(id) {
                     models::remote_addons::load(manifest);
@@ -261,3 +315,20 @@ impl AsyncComponent for Addons {
         }
     }
 }
+
+impl Addons {
+    fn update_changes(&self) {
+        let installed = INSTALLED_ADDONS_STATE.read_inner();
+        let state = ADDON_UPDATES_STATE.read_inner();
+
+        for (index, addon) in installed.addons.iter().enumerate() {
+            let changes = state
+                .updates
+                .get(&addon.manifest_url)
+                .map_or(vec![], |update| update.changes.to_owned());
+
+            self.installed_list
+                .send(index, AddonRowInput::SetChanges(changes));
+        }
+    }
+}
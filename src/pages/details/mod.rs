@@ -29,6 +29,7 @@ use crate::{
 #[derive(Debug)]
 pub enum DetailsPageInput {
     Load((String, String)),
+    LoadVideo((String, String, String)),
     Unload,
     Update,
     AddToLibrary,
@@ -363,6 +364,12 @@ impl AsyncComponent for DetailsPage {
                 self.sidebar.emit(SidebarInput::Reset);
                 models::meta_details::load(&r#type, &id, None);
             }
+            DetailsPageInput::LoadVideo((id, r#type, video_id)) => {
+                self.sidebar.emit(SidebarInput::Reset);
+                self.sidebar
+                    .emit(SidebarInput::SelectVideo(video_id.to_owned()));
+                models::meta_details::load(&r#type, &id, Some(&video_id));
+            }
             DetailsPageInput::Unload => {
                 self.logo.emit(ImageInput::Unload);
             }
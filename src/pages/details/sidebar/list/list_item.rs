@@ -4,8 +4,8 @@ use ordered_float::NotNan;
 use relm4::{
     css,
     gtk::{
-        self,
-        prelude::{BoxExt, OrientableExt, WidgetExt},
+        self, gdk, gio,
+        prelude::{BoxExt, OrientableExt, PopoverExt, WidgetExt},
     },
     typed_view::list::RelmListItem,
     view, RelmWidgetExt,
@@ -20,6 +20,7 @@ pub struct ListItem {
     pub description: String,
     pub icon: &'static str,
     pub progress: Option<NotNan<f64>>,
+    pub menu: Vec<(String, String)>,
 }
 
 pub struct Widgets {
@@ -28,6 +29,7 @@ pub struct Widgets {
     description: gtk::Label,
     icon: gtk::Image,
     progress: gtk::ProgressBar,
+    popover: gtk::PopoverMenu,
 }
 
 impl RelmListItem for ListItem {
@@ -89,12 +91,34 @@ impl RelmListItem for ListItem {
             },
         }
 
+        let popover = gtk::PopoverMenu::from_model(None::<&gio::MenuModel>);
+        popover.set_parent(&root);
+        popover.set_has_arrow(false);
+
+        let gesture = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_SECONDARY)
+            .build();
+
+        gesture.connect_pressed({
+            let popover = popover.clone();
+            move |_, _, x, y| {
+                if popover.menu_model().is_some() {
+                    let position = gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+                    popover.set_pointing_to(Some(&position));
+                    popover.popup();
+                }
+            }
+        });
+
+        root.add_controller(gesture);
+
         let widgets = Widgets {
             number,
             title,
             description,
             icon,
             progress,
+            popover,
         };
 
         (root, widgets)
@@ -107,6 +131,7 @@ impl RelmListItem for ListItem {
             description,
             icon,
             progress,
+            popover,
         } = widgets;
 
         number.set_label(&self.number.to_string());
@@ -121,5 +146,17 @@ impl RelmListItem for ListItem {
         let progress_value = self.progress.map_or(0.0, |progress| progress.div(100.0));
         progress.set_fraction(progress_value);
         progress.set_visible(progress_value > 0.0);
+
+        let menu = (!self.menu.is_empty()).then(|| {
+            let menu = gio::Menu::new();
+
+            for (label, action) in &self.menu {
+                menu.append(Some(label), Some(action));
+            }
+
+            menu
+        });
+
+        popover.set_menu_model(menu.as_ref());
     }
 }
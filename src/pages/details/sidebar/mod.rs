@@ -5,8 +5,10 @@ use itertools::Itertools;
 use list::{list_item::ListItem, List, ListOutput};
 use ordered_float::NotNan;
 use relm4::{
-    adw, css, gtk, Component, ComponentController, ComponentParts, ComponentSender, Controller,
-    RelmWidgetExt, SimpleComponent,
+    adw, css,
+    gtk::{self, gio, glib},
+    Component, ComponentController, ComponentParts, ComponentSender, Controller, RelmWidgetExt,
+    SimpleComponent,
 };
 use rust_i18n::t;
 use stremio_core_losange::{
@@ -17,6 +19,7 @@ use stremio_core_losange::{
     stremio_core::types::resource::StreamSource,
     types::{stream::Stream, video::Video},
 };
+use tracing::error;
 
 use crate::{
     app::AppMsg,
@@ -25,6 +28,7 @@ use crate::{
         header_menu::HeaderMenu,
         spinner::Spinner,
     },
+    downloads,
     pages::details::sidebar::list::ListInput,
     APP_BROKER,
 };
@@ -38,6 +42,10 @@ pub enum SidebarInput {
     VideoClicked(usize),
     AddonChanged(usize),
     StreamClicked(usize),
+    StreamCast(usize),
+    StreamExternal(usize),
+    StreamDownload(usize),
+    SelectVideo(String),
     Reset,
 }
 
@@ -51,6 +59,7 @@ pub struct Sidebar {
     addons: Controller<DropDown>,
     streams: Controller<List>,
     selected_video: Option<Video>,
+    pending_video: Option<String>,
 }
 
 #[relm4::component(pub)]
@@ -185,6 +194,43 @@ impl SimpleComponent for Sidebar {
                 ListOutput::Clicked(index) => SidebarInput::StreamClicked(index),
             });
 
+        let stream_actions = gio::SimpleActionGroup::new();
+
+        let cast_action = gio::SimpleAction::new("cast", Some(glib::VariantTy::INT32));
+        cast_action.connect_activate({
+            let sender = sender.input_sender().clone();
+            move |_, parameter| {
+                if let Some(index) = parameter.and_then(|parameter| parameter.get::<i32>()) {
+                    sender.emit(SidebarInput::StreamCast(index as usize));
+                }
+            }
+        });
+
+        let external_action = gio::SimpleAction::new("external", Some(glib::VariantTy::INT32));
+        external_action.connect_activate({
+            let sender = sender.input_sender().clone();
+            move |_, parameter| {
+                if let Some(index) = parameter.and_then(|parameter| parameter.get::<i32>()) {
+                    sender.emit(SidebarInput::StreamExternal(index as usize));
+                }
+            }
+        });
+
+        let download_action = gio::SimpleAction::new("download", Some(glib::VariantTy::INT32));
+        download_action.connect_activate({
+            let sender = sender.input_sender().clone();
+            move |_, parameter| {
+                if let Some(index) = parameter.and_then(|parameter| parameter.get::<i32>()) {
+                    sender.emit(SidebarInput::StreamDownload(index as usize));
+                }
+            }
+        });
+
+        stream_actions.add_action(&cast_action);
+        stream_actions.add_action(&external_action);
+        stream_actions.add_action(&download_action);
+        root.insert_action_group("stream", Some(&stream_actions));
+
         let model = Sidebar {
             header_menu,
             default_season: true,
@@ -195,6 +241,7 @@ impl SimpleComponent for Sidebar {
             addons,
             streams,
             selected_video: None,
+            pending_video: None,
         };
 
         let widgets = view_output!();
@@ -236,6 +283,17 @@ impl SimpleComponent for Sidebar {
                     }
                 }
 
+                if let Some(video_id) = &self.pending_video {
+                    let position = Self::find_video(&state.videos, video_id);
+
+                    if let Some((index, video)) = position {
+                        self.seasons.emit(DropDownInput::Select(index));
+                        self.selected_season = index;
+                        self.selected_video = Some(video.to_owned());
+                        self.pending_video = None;
+                    }
+                }
+
                 self.update_videos(&state.videos, &state.last_watched);
 
                 let addons = state
@@ -295,14 +353,89 @@ impl SimpleComponent for Sidebar {
                     }
                 }
             }
+            SidebarInput::StreamCast(index) => {
+                let state = META_DETAILS_STATE.read_inner();
+
+                let stream = state
+                    .streams
+                    .get(self.selected_addon)
+                    .and_then(|(.., streams)| streams.get(index));
+
+                if let Some(stream) = stream {
+                    APP_BROKER.send(AppMsg::OpenCast(Some(Box::new(stream.to_owned()))));
+                }
+            }
+            SidebarInput::StreamExternal(index) => {
+                let state = META_DETAILS_STATE.read_inner();
+
+                let stream = state
+                    .streams
+                    .get(self.selected_addon)
+                    .and_then(|(.., streams)| streams.get(index));
+
+                if let Some(stream) = stream {
+                    APP_BROKER.send(AppMsg::OpenExternalPlayer(Box::new(stream.to_owned())));
+                }
+            }
+            SidebarInput::StreamDownload(index) => {
+                let state = META_DETAILS_STATE.read_inner();
+
+                let stream = state
+                    .streams
+                    .get(self.selected_addon)
+                    .and_then(|(.., streams)| streams.get(index))
+                    .cloned();
+
+                let Some((stream, item)) = stream.zip(state.item.to_owned()) else {
+                    return;
+                };
+
+                let title = match &self.selected_video {
+                    Some(video) if !video.name.is_empty() => {
+                        format!("{} - {}", item.name, video.name)
+                    }
+                    _ => item.name,
+                };
+
+                let in_library = state.in_library;
+                drop(state);
+
+                // Downloads show up in the library through their meta item
+                if !in_library {
+                    models::meta_details::add_to_library();
+                }
+
+                let message = match downloads::start(stream, title, item.image) {
+                    Ok(()) => t!("download_started"),
+                    Err(e) => {
+                        error!("Failed to start download: {e}");
+                        t!("error_download")
+                    }
+                };
+
+                APP_BROKER.send(AppMsg::Toast((message.to_string(), 3)));
+            }
+            SidebarInput::SelectVideo(video_id) => {
+                self.pending_video = Some(video_id);
+            }
             SidebarInput::Reset => {
                 self.selected_video = None;
+                self.pending_video = None;
             }
         }
     }
 }
 
 impl Sidebar {
+    fn find_video<'a>(videos: &'a [(u32, Vec<Video>)], id: &str) -> Option<(usize, &'a Video)> {
+        videos.iter().enumerate().find_map(|(index, (_, videos))| {
+            videos
+                .iter()
+                .find(|video| video.id == id)
+                .map(|video| (index, video))
+        })
+    }
+
     fn update_videos(&self, videos: &[(u32, Vec<Video>)], last_watched: &Option<LastWatched>) {
         if let Some((.., videos)) = videos.get(self.selected_season) {
             let items = videos
@@ -327,14 +460,37 @@ impl Sidebar {
         if let Some((.., streams)) = streams.get(self.selected_addon) {
             let items = streams
                 .iter()
-                .map(|stream| ListItem {
-                    title: stream.name.clone(),
-                    description: stream.description.clone(),
-                    icon: match matches!(stream.source, StreamSource::External { .. }) {
-                        true => "external-link",
-                        false => "media-playback-start-symbolic",
-                    },
-                    ..Default::default()
+                .enumerate()
+                .map(|(index, stream)| {
+                    let external = matches!(stream.source, StreamSource::External { .. });
+                    let playable = matches!(
+                        stream.source,
+                        StreamSource::Url { .. } | StreamSource::Torrent { .. }
+                    );
+
+                    ListItem {
+                        title: stream.name.clone(),
+                        description: stream.description.clone(),
+                        icon: match external {
+                            true => "external-link",
+                            false => "media-playback-start-symbolic",
+                        },
+                        menu: match playable {
+                            false => vec![],
+                            true => vec![
+                                (t!("cast_to").to_string(), format!("stream.cast({index})")),
+                                (
+                                    t!("open_external_player").to_string(),
+                                    format!("stream.external({index})"),
+                                ),
+                                (
+                                    t!("download").to_string(),
+                                    format!("stream.download({index})"),
+                                ),
+                            ],
+                        },
+                        ..Default::default()
+                    }
                 })
                 .collect_vec();
 
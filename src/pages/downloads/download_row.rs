@@ -0,0 +1,150 @@
+use adw::prelude::*;
+use gtk::glib;
+use relm4::{adw, css, factory::FactoryComponent, gtk, FactorySender};
+use rust_i18n::t;
+
+use crate::{
+    common::format::Format,
+    downloads::{Download, DownloadStatus},
+};
+
+#[derive(Debug)]
+pub enum DownloadRowInput {
+    Update(Download),
+    Play,
+    Toggle,
+    Cancel,
+}
+
+#[derive(Debug)]
+pub enum DownloadRowOutput {
+    Play(String),
+    Pause(String),
+    Resume(String),
+    Cancel(String),
+}
+
+pub struct DownloadRow {
+    download: Download,
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for DownloadRow {
+    type Input = DownloadRowInput;
+    type Output = DownloadRowOutput;
+    type Init = Download;
+    type CommandOutput = ();
+    type ParentWidget = gtk::ListBox;
+
+    view! {
+        adw::ActionRow {
+            set_selectable: false,
+            set_activatable: false,
+            set_subtitle_lines: 1,
+
+            #[watch]
+            set_title: &self.download.title.escape(),
+            #[watch]
+            set_subtitle: &self.subtitle(),
+
+            add_suffix = &gtk::ProgressBar {
+                set_valign: gtk::Align::Center,
+                set_width_request: 100,
+
+                #[watch]
+                set_fraction: self.download.progress().unwrap_or_default(),
+                #[watch]
+                set_visible: self.download.status != DownloadStatus::Completed,
+            },
+
+            add_suffix = &gtk::Button {
+                add_css_class: css::classes::FLAT,
+                set_valign: gtk::Align::Center,
+                set_icon_name: "media-playback-start-symbolic",
+                set_tooltip_text: Some(&t!("play")),
+                connect_clicked => DownloadRowInput::Play,
+
+                #[watch]
+                set_visible: self.download.status == DownloadStatus::Completed,
+            },
+
+            add_suffix = &gtk::Button {
+                add_css_class: css::classes::FLAT,
+                set_valign: gtk::Align::Center,
+                connect_clicked => DownloadRowInput::Toggle,
+
+                #[watch]
+                set_visible: self.download.status != DownloadStatus::Completed,
+                #[watch]
+                set_icon_name: match self.download.status {
+                    DownloadStatus::Downloading => "media-playback-pause-symbolic",
+                    _ => "view-refresh-symbolic",
+                },
+                #[watch]
+                set_tooltip_text: Some(&match self.download.status {
+                    DownloadStatus::Downloading => t!("pause"),
+                    _ => t!("resume"),
+                }),
+            },
+
+            add_suffix = &gtk::Button {
+                add_css_class: css::classes::FLAT,
+                set_valign: gtk::Align::Center,
+                set_icon_name: "user-trash-symbolic",
+                set_tooltip_text: Some(&t!("delete_download")),
+                connect_clicked => DownloadRowInput::Cancel,
+            },
+        }
+    }
+
+    fn init_model(init: Self::Init, _index: &Self::Index, _sender: FactorySender<Self>) -> Self {
+        Self { download: init }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
+        let id = self.download.id.to_owned();
+
+        match message {
+            DownloadRowInput::Update(download) => {
+                self.download = download;
+            }
+            DownloadRowInput::Play => {
+                sender.output(DownloadRowOutput::Play(id)).ok();
+            }
+            DownloadRowInput::Toggle => {
+                let output = match self.download.status {
+                    DownloadStatus::Downloading => DownloadRowOutput::Pause(id),
+                    _ => DownloadRowOutput::Resume(id),
+                };
+
+                sender.output(output).ok();
+            }
+            DownloadRowInput::Cancel => {
+                sender.output(DownloadRowOutput::Cancel(id)).ok();
+            }
+        }
+    }
+}
+
+impl DownloadRow {
+    fn subtitle(&self) -> String {
+        let downloaded = glib::format_size(self.download.downloaded);
+
+        let size = match self.download.total {
+            Some(total) => t!(
+                "download_size",
+                downloaded = downloaded,
+                total = glib::format_size(total)
+            )
+            .to_string(),
+            None => downloaded.to_string(),
+        };
+
+        match self.download.status {
+            DownloadStatus::Downloading => size,
+            DownloadStatus::Paused => t!("download_paused", size = size).to_string(),
+            DownloadStatus::Completed => glib::format_size(self.download.downloaded).to_string(),
+            DownloadStatus::Failed => t!("download_failed").to_string(),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+mod download_row;
+
+use std::path::PathBuf;
+
+use adw::prelude::*;
+use download_row::{DownloadRow, DownloadRowInput, DownloadRowOutput};
+use gtk::{gio, glib};
+use relm4::{
+    adw, css, factory::FactoryVecDeque, gtk, ComponentParts, ComponentSender, SimpleComponent,
+};
+use rust_i18n::t;
+
+use crate::{
+    app::AppMsg,
+    constants::APP_ID,
+    downloads::{self, DOWNLOADS_STATE},
+    APP_BROKER,
+};
+
+#[derive(Debug)]
+pub enum DownloadsInput {
+    Update,
+    Play(String),
+    Pause(String),
+    Resume(String),
+    Cancel(String),
+    ChooseLocation,
+    LocationChanged(String),
+}
+
+pub struct Downloads {
+    list: FactoryVecDeque<DownloadRow>,
+    ids: Vec<String>,
+    location: PathBuf,
+    free_space: Option<u64>,
+    settings: gio::Settings,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for Downloads {
+    type Init = ();
+    type Input = DownloadsInput;
+    type Output = ();
+
+    view! {
+        adw::NavigationPage {
+            set_title: &t!("downloads"),
+            set_tag: Some("downloads"),
+
+            adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &adw::PreferencesGroup {
+                        adw::ActionRow {
+                            set_title: &t!("downloads_location"),
+                            add_css_class: "property",
+
+                            #[watch]
+                            set_subtitle: &model.location.display().to_string(),
+
+                            add_suffix = &gtk::Button {
+                                add_css_class: css::classes::FLAT,
+                                set_valign: gtk::Align::Center,
+                                set_icon_name: "folder-open-symbolic",
+                                set_tooltip_text: Some(&t!("choose_folder")),
+                                connect_clicked => DownloadsInput::ChooseLocation,
+                            },
+                        },
+
+                        adw::ActionRow {
+                            set_title: &t!("disk_usage"),
+                            add_css_class: "property",
+
+                            #[watch]
+                            set_subtitle: &model.disk_usage(),
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &t!("downloads"),
+
+                        #[local_ref]
+                        list -> gtk::ListBox {
+                            add_css_class: css::classes::BOXED_LIST,
+                            set_valign: gtk::Align::Start,
+
+                            #[watch]
+                            set_visible: !model.ids.is_empty(),
+                        },
+
+                        gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+                            set_margin_top: 24,
+                            set_label: &t!("no_downloads"),
+
+                            #[watch]
+                            set_visible: model.ids.is_empty(),
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        DOWNLOADS_STATE.subscribe(sender.input_sender(), |_| DownloadsInput::Update);
+
+        let list = FactoryVecDeque::builder()
+            .launch(gtk::ListBox::new())
+            .forward(sender.input_sender(), |msg| match msg {
+                DownloadRowOutput::Play(id) => DownloadsInput::Play(id),
+                DownloadRowOutput::Pause(id) => DownloadsInput::Pause(id),
+                DownloadRowOutput::Resume(id) => DownloadsInput::Resume(id),
+                DownloadRowOutput::Cancel(id) => DownloadsInput::Cancel(id),
+            });
+
+        let mut model = Downloads {
+            list,
+            ids: vec![],
+            location: downloads::location(),
+            free_space: None,
+            settings: gio::Settings::new(APP_ID),
+        };
+
+        model.update_list();
+
+        let list = model.list.widget();
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DownloadsInput::Update => {
+                self.update_list();
+            }
+            DownloadsInput::Play(id) => {
+                let state = DOWNLOADS_STATE.read_inner();
+
+                let stream = state
+                    .downloads
+                    .iter()
+                    .find(|download| download.id == id)
+                    .and_then(|download| download.local_stream());
+
+                if let Some(stream) = stream {
+                    APP_BROKER.send(AppMsg::OpenStream(Box::new(stream)));
+                }
+            }
+            DownloadsInput::Pause(id) => {
+                downloads::pause(&id);
+            }
+            DownloadsInput::Resume(id) => {
+                downloads::resume(&id);
+            }
+            DownloadsInput::Cancel(id) => {
+                downloads::cancel(&id);
+            }
+            DownloadsInput::ChooseLocation => {
+                let window = relm4::main_application().active_window();
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(t!("downloads_location"))
+                    .modal(true)
+                    .initial_folder(&gio::File::for_path(&self.location))
+                    .build();
+
+                file_dialog.select_folder(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                        let path = path.to_string_lossy().to_string();
+                        sender.input(DownloadsInput::LocationChanged(path));
+                    }
+                });
+            }
+            DownloadsInput::LocationChanged(path) => {
+                let _ = self.settings.set_string("downloads-location", &path);
+
+                self.location = downloads::location();
+                self.free_space = downloads::free_space();
+            }
+        }
+    }
+}
+
+impl Downloads {
+    fn update_list(&mut self) {
+        let state = DOWNLOADS_STATE.read_inner();
+
+        let ids = state
+            .downloads
+            .iter()
+            .map(|download| download.id.to_owned())
+            .collect::<Vec<_>>();
+
+        if ids == self.ids {
+            for (index, download) in state.downloads.iter().enumerate() {
+                self.list
+                    .send(index, DownloadRowInput::Update(download.to_owned()));
+            }
+        } else {
+            self.list.guard().clear();
+            self.list.extend(state.downloads.iter().cloned());
+            self.ids = ids;
+        }
+
+        self.free_space = downloads::free_space();
+    }
+
+    fn disk_usage(&self) -> String {
+        let state = DOWNLOADS_STATE.read_inner();
+        let used = state
+            .downloads
+            .iter()
+            .map(|download| download.downloaded)
+            .sum::<u64>();
+
+        let used = glib::format_size(used);
+
+        match self.free_space {
+            Some(free) => t!(
+                "disk_usage_free",
+                used = used,
+                free = glib::format_size(free)
+            ),
+            None => t!("disk_usage_used", used = used),
+        }
+        .to_string()
+    }
+}
@@ -2,6 +2,7 @@ pub mod addon;
 pub mod addons;
 pub mod details;
 pub mod discover;
+pub mod downloads;
 pub mod home;
 pub mod library;
 pub mod player;
@@ -1,11 +1,15 @@
 mod mpv;
+mod prebuffer;
+mod statistics_menu;
 mod tracks_menu;
-mod video;
+pub mod video;
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use adw::prelude::*;
 use gtk::glib;
+use itertools::Itertools;
+use prebuffer::Prebuffer;
 use relm4::{
     actions::{ActionGroupName, ActionName, RelmAction, RelmActionGroup},
     adw, css,
@@ -14,20 +18,30 @@ use relm4::{
     RelmWidgetExt, SimpleComponent,
 };
 use rust_i18n::t;
+use statistics_menu::StatisticsMenu;
 use stremio_core_losange::{
+    cast::Renderer,
     models::{self, ctx::CTX_STATE, player::PLAYER_STATE, server::SERVER_STATE},
-    stremio_core::types::streams::{AudioTrack, SubtitleTrack},
-    types::stream::Stream,
+    stremio_core::types::{
+        resource::Stream as CoreStream,
+        streams::{AudioTrack, StreamItemState, SubtitleTrack},
+    },
+    types::{stream::Stream, tracks::TrackPreference},
+    watch_party::PartyMessage,
 };
 use tokio::time::sleep;
+use tracing::error;
 use tracks_menu::{TracksMenu, TracksMenuInput, TracksMenuOutput};
-use video::{Video, VideoInput, VideoOutput, VIDEO_STATE};
+use url::Url;
+use video::{MediaTrack, Video, VideoInput, VideoOutput, VideoState, VIDEO_STATE};
 
 use crate::{
     app::AppMsg,
+    cast::{CastSession, CastState, CAST_STATE},
     common::window::WindowExt,
     components::spinner::Spinner,
     constants::{APP_ID, VOLUME_DEFAULT, VOLUME_MAX, VOLUME_STEP},
+    external_player::{ExternalPlayer, EXTERNAL_PLAYER_STATE},
     APP_BROKER,
 };
 
@@ -35,24 +49,38 @@ relm4::new_action_group!(pub(super) PlayerActionGroup, "player");
 relm4::new_stateless_action!(pub(super) PlayPauseAction, PlayerActionGroup, "play_pause");
 relm4::new_stateless_action!(pub(super) SeekPrevAction, PlayerActionGroup, "seek_prev");
 relm4::new_stateless_action!(pub(super) SeekNextAction, PlayerActionGroup, "seek_next");
+relm4::new_stateless_action!(pub(super) StepPrevAction, PlayerActionGroup, "step_prev");
+relm4::new_stateless_action!(pub(super) StepNextAction, PlayerActionGroup, "step_next");
+relm4::new_stateless_action!(pub(super) FramePrevAction, PlayerActionGroup, "frame_prev");
+relm4::new_stateless_action!(pub(super) FrameNextAction, PlayerActionGroup, "frame_next");
 relm4::new_stateless_action!(pub(super) VolumeUp, PlayerActionGroup, "volume_up");
 relm4::new_stateless_action!(pub(super) VolumeDown, PlayerActionGroup, "volume_down");
 relm4::new_stateless_action!(pub(super) ToggleFullscreen, PlayerActionGroup, "toggle_fullscreen");
 relm4::new_stateless_action!(pub(super) Exit, PlayerActionGroup, "exit");
 
+// Kept one entry per line, rustfmt would split the longest tuples over several lines
+#[rustfmt::skip]
 const SHORTCUTS: &[(&str, &str, &str)] = &[
     ("space", PlayerActionGroup::NAME, PlayPauseAction::NAME),
     ("Left", PlayerActionGroup::NAME, SeekPrevAction::NAME),
     ("Right", PlayerActionGroup::NAME, SeekNextAction::NAME),
+    ("<Shift>Left", PlayerActionGroup::NAME, StepPrevAction::NAME),
+    ("<Shift>Right", PlayerActionGroup::NAME, StepNextAction::NAME),
+    ("comma", PlayerActionGroup::NAME, FramePrevAction::NAME),
+    ("period", PlayerActionGroup::NAME, FrameNextAction::NAME),
     ("Up", PlayerActionGroup::NAME, VolumeUp::NAME),
     ("Down", PlayerActionGroup::NAME, VolumeDown::NAME),
     ("F", PlayerActionGroup::NAME, ToggleFullscreen::NAME),
     ("Escape", PlayerActionGroup::NAME, Exit::NAME),
 ];
 
+const SYNC_THRESHOLD: f64 = 1000.0;
+const PREBUFFER_THRESHOLD: f64 = 0.8;
+
 #[derive(Debug)]
 pub enum PlayerInput {
     Load(Box<Stream>),
+    LoadPlaylist((Vec<Stream>, usize)),
     Unload,
     UpdateVideo,
     MouseMove((f64, f64)),
@@ -64,6 +92,12 @@ pub enum PlayerInput {
     Seek,
     SeekPrev,
     SeekNext,
+    SeekShortPrev,
+    SeekShortNext,
+    SeekBy(f64),
+    SeekTo(f64),
+    FrameStepPrev,
+    FrameStepNext,
     Volume(f64),
     TextTrackChanged(i64),
     AudioTrackChanged(i64),
@@ -75,6 +109,14 @@ pub enum PlayerInput {
     SizeChanged((i64, i64)),
     Ended,
     Error,
+    SyncStream(Box<Stream>),
+    SyncPlayback { paused: bool, time: f64, rate: f64 },
+    ShareState,
+    Cast(Box<Renderer>),
+    StopCast,
+    CastChanged,
+    LoadExternal(Box<Stream>),
+    ExternalChanged,
 }
 
 pub struct Player {
@@ -89,8 +131,17 @@ pub struct Player {
     volume: gtk::ScaleButton,
     text_tracks_menu: Controller<TracksMenu>,
     audio_tracks_menu: Controller<TracksMenu>,
+    statistics_menu: Controller<StatisticsMenu>,
     statistics_task: Option<JoinHandle<()>>,
     default_window_size: Option<(i32, i32)>,
+    tracks_restored: bool,
+    stream: Option<Stream>,
+    playlist: Vec<Stream>,
+    pending_sync: Option<(bool, f64, f64, Instant)>,
+    cast: Option<CastSession>,
+    external: Option<ExternalPlayer>,
+    external_pending: bool,
+    prebuffer: Option<Prebuffer>,
 }
 
 #[relm4::component(pub)]
@@ -132,7 +183,7 @@ impl SimpleComponent for Player {
                         set_valign: gtk::Align::Start,
                         set_transition_type: gtk::RevealerTransitionType::Crossfade,
                         #[watch]
-                        set_reveal_child: state.paused || !model.immersed,
+                        set_reveal_child: paused || !model.immersed,
 
                         adw::HeaderBar {
                             add_css_class: relm4::css::classes::FLAT,
@@ -153,7 +204,23 @@ impl SimpleComponent for Player {
                                     false => "view-fullscreen-symbolic",
                                 },
                                 connect_clicked => PlayerInput::Fullscreen,
-                            }
+                            },
+
+                            pack_end = &gtk::Button {
+                                set_icon_name: "video-display-symbolic",
+                                set_tooltip_text: Some(&t!("cast_to")),
+                                connect_clicked => move |_| {
+                                    APP_BROKER.send(AppMsg::OpenCast(None));
+                                },
+                            },
+
+                            pack_end = &gtk::Button {
+                                set_icon_name: "people",
+                                set_tooltip_text: Some(&t!("watch_party")),
+                                connect_clicked => move |_| {
+                                    APP_BROKER.send(AppMsg::OpenWatchParty);
+                                },
+                            },
                         }
                     },
 
@@ -161,7 +228,7 @@ impl SimpleComponent for Player {
                         set_valign: gtk::Align::Center,
                         set_transition_type: gtk::RevealerTransitionType::Crossfade,
                         #[watch]
-                        set_reveal_child: state.buffering,
+                        set_reveal_child: state.buffering && cast.renderer.is_none(),
 
                         gtk::Box {
                             set_orientation: gtk::Orientation::Vertical,
@@ -189,12 +256,47 @@ impl SimpleComponent for Player {
                         }
                     },
 
+                    add_overlay = &gtk::Revealer {
+                        set_valign: gtk::Align::Center,
+                        set_transition_type: gtk::RevealerTransitionType::Crossfade,
+                        #[watch]
+                        set_reveal_child: cast.renderer.is_some(),
+
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_halign: gtk::Align::Center,
+                            set_spacing: 12,
+
+                            gtk::Image {
+                                set_icon_name: Some("video-display-symbolic"),
+                                set_pixel_size: 64,
+                            },
+
+                            gtk::Label {
+                                add_css_class: css::classes::TITLE_2,
+
+                                #[watch]
+                                set_label: &t!(
+                                    "casting_to",
+                                    name = cast.renderer.as_deref().unwrap_or_default()
+                                ),
+                            },
+
+                            gtk::Button {
+                                set_css_classes: &[css::classes::PILL],
+                                set_halign: gtk::Align::Center,
+                                set_label: &t!("cast_stop"),
+                                connect_clicked => PlayerInput::StopCast,
+                            },
+                        }
+                    },
+
                     add_overlay = &gtk::Revealer {
                         set_valign: gtk::Align::End,
                         set_transition_type: gtk::RevealerTransitionType::Crossfade,
 
                         #[watch]
-                        set_reveal_child: state.paused || !model.immersed,
+                        set_reveal_child: paused || !model.immersed,
 
                         gtk::Box {
                             add_css_class: relm4::css::classes::TOOLBAR,
@@ -214,7 +316,7 @@ impl SimpleComponent for Player {
                                 set_size_request: (45, 45),
 
                                 #[watch]
-                                set_icon_name: match state.paused {
+                                set_icon_name: match paused {
                                     true => "media-playback-start-symbolic",
                                     false => "media-playback-pause-symbolic",
                                 },
@@ -227,7 +329,7 @@ impl SimpleComponent for Player {
                                 set_icon_name: "skip-forward-large",
 
                                 #[watch]
-                                set_visible: player.next_stream.is_some(),
+                                set_visible: player.next_stream.is_some() || model.playlist_next().is_some(),
 
                                 connect_clicked => PlayerInput::PlayNext,
                             },
@@ -267,7 +369,7 @@ impl SimpleComponent for Player {
                                 set_width_request: 80,
 
                                 #[watch]
-                                set_label: &Self::ms_to_clock(state.time),
+                                set_label: &Self::ms_to_clock(time),
                             },
 
                             #[local_ref]
@@ -275,11 +377,11 @@ impl SimpleComponent for Player {
                                 set_hexpand: true,
 
                                 #[watch]
-                                set_range: (0.0, state.duration),
+                                set_range: (0.0, duration),
 
                                 #[watch]
                                 #[block_signal(time_handler)]
-                                set_value: state.time,
+                                set_value: time,
 
                                 connect_change_value[sender] => move |_, _, _| {
                                     sender.input(PlayerInput::Seek);
@@ -293,14 +395,15 @@ impl SimpleComponent for Player {
                                 set_width_request: 80,
 
                                 #[watch]
-                                set_label: &Self::ms_to_clock(state.duration),
+                                set_label: &Self::ms_to_clock(duration),
 
                                 #[watch]
-                                set_visible: state.duration.gt(&0.0),
+                                set_visible: duration.gt(&0.0),
                             },
 
                             model.text_tracks_menu.widget(),
                             model.audio_tracks_menu.widget(),
+                            model.statistics_menu.widget(),
 
                             gtk::Button {
                                 set_icon_name: "settings",
@@ -326,9 +429,13 @@ impl SimpleComponent for Player {
         let player = PLAYER_STATE.read_inner();
         let server = SERVER_STATE.read_inner();
         let state = VIDEO_STATE.read_inner();
+        let cast = CAST_STATE.read_inner();
+        let (paused, time, duration) = Self::playback(&state, &cast);
 
         CTX_STATE.subscribe(sender.input_sender(), |_| PlayerInput::UpdateVideo);
         PLAYER_STATE.subscribe(sender.input_sender(), |_| PlayerInput::UpdateVideo);
+        CAST_STATE.subscribe(sender.input_sender(), |_| PlayerInput::CastChanged);
+        EXTERNAL_PLAYER_STATE.subscribe(sender.input_sender(), |_| PlayerInput::ExternalChanged);
 
         let settings = gio::Settings::new(APP_ID);
 
@@ -362,6 +469,8 @@ impl SimpleComponent for Player {
                     TracksMenuOutput::TrackChanged(index) => PlayerInput::AudioTrackChanged(index),
                 });
 
+        let statistics_menu = StatisticsMenu::builder().launch(()).detach();
+
         let model = Player {
             settings,
             video,
@@ -374,8 +483,17 @@ impl SimpleComponent for Player {
             volume: volume.to_owned(),
             text_tracks_menu,
             audio_tracks_menu,
+            statistics_menu,
             statistics_task: None,
             default_window_size: None,
+            tracks_restored: false,
+            stream: None,
+            playlist: vec![],
+            pending_sync: None,
+            cast: None,
+            external: None,
+            external_pending: false,
+            prebuffer: None,
         };
 
         let play_pause_action = {
@@ -399,6 +517,34 @@ impl SimpleComponent for Player {
             })
         };
 
+        let seek_short_prev_action = {
+            let sender = sender.input_sender().clone();
+            RelmAction::<StepPrevAction>::new_stateless(move |_| {
+                sender.emit(PlayerInput::SeekShortPrev);
+            })
+        };
+
+        let seek_short_next_action = {
+            let sender = sender.input_sender().clone();
+            RelmAction::<StepNextAction>::new_stateless(move |_| {
+                sender.emit(PlayerInput::SeekShortNext);
+            })
+        };
+
+        let frame_step_prev_action = {
+            let sender = sender.input_sender().clone();
+            RelmAction::<FramePrevAction>::new_stateless(move |_| {
+                sender.emit(PlayerInput::FrameStepPrev);
+            })
+        };
+
+        let frame_step_next_action = {
+            let sender = sender.input_sender().clone();
+            RelmAction::<FrameNextAction>::new_stateless(move |_| {
+                sender.emit(PlayerInput::FrameStepNext);
+            })
+        };
+
         let volume_up_action = {
             let sender = sender.input_sender().clone();
             RelmAction::<VolumeUp>::new_stateless(move |_| {
@@ -431,6 +577,10 @@ impl SimpleComponent for Player {
         actions.add_action(play_pause_action);
         actions.add_action(seek_prev_action);
         actions.add_action(seek_next_action);
+        actions.add_action(seek_short_prev_action);
+        actions.add_action(seek_short_next_action);
+        actions.add_action(frame_step_prev_action);
+        actions.add_action(frame_step_next_action);
         actions.add_action(volume_up_action);
         actions.add_action(volume_down_action);
         actions.add_action(toggle_fullscreen_action);
@@ -465,25 +615,41 @@ impl SimpleComponent for Player {
         let player = PLAYER_STATE.read_inner();
         let server = SERVER_STATE.read_inner();
         let state = VIDEO_STATE.read_inner();
+        let cast = CAST_STATE.read_inner();
+        let (paused, time, duration) = Self::playback(&state, &cast);
     }
 
     fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         match message {
             PlayerInput::Load(stream) => {
-                models::player::load(*stream);
-            }
-            PlayerInput::Unload => {
-                models::player::unload();
-                self.video.emit(VideoInput::Unload);
+                if self.external.is_some() {
+                    self.unload();
+                }
 
-                if self.settings.boolean("player-resize-window") {
-                    if let Some((width, height)) = self.default_window_size.take() {
-                        if let Some(window) = relm4::main_application().active_window() {
-                            window.animate_size(width, height);
-                        }
+                if !self.playlist.contains(&stream) {
+                    self.playlist.clear();
+                }
+
+                Self::broadcast(PartyMessage::Stream {
+                    stream: stream.to_owned(),
+                });
+
+                self.load(*stream);
+            }
+            PlayerInput::LoadPlaylist((streams, index)) => {
+                if let Some(stream) = streams.get(index) {
+                    if self.stream.is_some() {
+                        self.unload();
                     }
+
+                    let stream = Box::new(stream.to_owned());
+                    self.playlist = streams;
+                    sender.input(PlayerInput::Load(stream));
                 }
             }
+            PlayerInput::Unload => {
+                self.unload();
+            }
             PlayerInput::UpdateVideo => {
                 let ctx = CTX_STATE.read_inner();
                 let player = PLAYER_STATE.read_inner();
@@ -498,7 +664,16 @@ impl SimpleComponent for Player {
                     self.create_statistics_task(info_hash, file_idx);
                 }
 
-                if !video.loaded {
+                if self.external_pending {
+                    if let Some(uri) = &player.uri {
+                        self.external_pending = false;
+                        self.launch_external(uri, &player.title, player.time, &sender);
+                    }
+                }
+
+                let external = self.external.is_some() || self.external_pending;
+
+                if !video.loaded && self.cast.is_none() && !external {
                     if let Some(uri) = &player.uri {
                         self.video
                             .emit(VideoInput::Load((uri.to_string(), player.time)));
@@ -550,17 +725,35 @@ impl SimpleComponent for Player {
                 }
             }
             PlayerInput::PlayPause => {
+                if let Some(cast) = &self.cast {
+                    match CAST_STATE.read_inner().paused {
+                        true => cast.play(),
+                        false => cast.pause(),
+                    }
+
+                    return;
+                }
+
                 let state = VIDEO_STATE.read_inner();
                 if state.paused {
                     self.video.emit(VideoInput::Play);
                 } else {
                     self.video.emit(VideoInput::Pause);
                 }
+
+                Self::broadcast(PartyMessage::Playback {
+                    paused: !state.paused,
+                    time: state.time,
+                    rate: state.speed,
+                });
             }
             PlayerInput::PlayNext => {
                 let player = PLAYER_STATE.read_inner();
 
-                if let Some(stream) = &player.next_stream {
+                if let Some(stream) = self.playlist_next().or(player.next_stream.as_ref()) {
+                    let stream = stream.to_owned();
+                    self.release_prebuffer(Some(&stream));
+
                     sender.input_sender().emit(PlayerInput::Unload);
                     sender
                         .input_sender()
@@ -568,25 +761,41 @@ impl SimpleComponent for Player {
                 }
             }
             PlayerInput::Seek => {
-                let time = self.seekbar.value();
-                let state = VIDEO_STATE.read_inner();
-
-                self.video.emit(VideoInput::Seek(time));
-                models::player::update_seek_time(time, state.duration);
+                self.seek_to(self.seekbar.value());
             }
             PlayerInput::SeekPrev => {
-                let state = VIDEO_STATE.read_inner();
-                let time = state.time - 10000.0;
-
-                self.video.emit(VideoInput::Seek(time));
-                models::player::update_seek_time(time, state.duration);
+                let ctx = CTX_STATE.read_inner();
+                self.seek_by(-(ctx.settings.seek_time_duration as f64));
             }
             PlayerInput::SeekNext => {
+                let ctx = CTX_STATE.read_inner();
+                self.seek_by(ctx.settings.seek_time_duration as f64);
+            }
+            PlayerInput::SeekBy(offset) => {
+                self.seek_by(offset);
+            }
+            PlayerInput::SeekTo(time) => {
+                self.seek_to(time);
+            }
+            PlayerInput::SeekShortPrev => {
+                let ctx = CTX_STATE.read_inner();
+                self.seek_by(-(ctx.settings.seek_short_time_duration as f64));
+            }
+            PlayerInput::SeekShortNext => {
+                let ctx = CTX_STATE.read_inner();
+                self.seek_by(ctx.settings.seek_short_time_duration as f64);
+            }
+            PlayerInput::FrameStepPrev => {
                 let state = VIDEO_STATE.read_inner();
-                let time = state.time + 10000.0;
-
-                self.video.emit(VideoInput::Seek(time));
-                models::player::update_seek_time(time, state.duration);
+                if state.paused {
+                    self.video.emit(VideoInput::FrameBackStep);
+                }
+            }
+            PlayerInput::FrameStepNext => {
+                let state = VIDEO_STATE.read_inner();
+                if state.paused {
+                    self.video.emit(VideoInput::FrameStep);
+                }
             }
             PlayerInput::Volume(amount) => {
                 let mut volume = (self.volume.value() + amount).clamp(0.0, VOLUME_MAX);
@@ -604,25 +813,48 @@ impl SimpleComponent for Player {
             PlayerInput::TextTrackChanged(id) => {
                 self.video.emit(VideoInput::TextTrack(id));
 
+                let video = VIDEO_STATE.read_inner();
+                let track = video.text_tracks.iter().find(|track| track.id == id);
+                let language = track.map(|track| track.lang.to_owned());
+                let title = track.and_then(|track| track.title.to_owned());
+
                 models::player::update_stream_state(|mut settings| {
                     settings.subtitle_track = Some(SubtitleTrack {
                         id: id.to_string(),
                         embedded: true,
-                        language: None,
+                        language: language.to_owned(),
                     });
                     settings
                 });
+
+                models::player::update_track_preference(|mut preference| {
+                    preference.subtitles_disabled = id == -1;
+                    preference.subtitles_language = language;
+                    preference.subtitles_title = title;
+                    preference
+                });
             }
             PlayerInput::AudioTrackChanged(id) => {
                 self.video.emit(VideoInput::AudioTrack(id));
 
+                let video = VIDEO_STATE.read_inner();
+                let track = video.audio_tracks.iter().find(|track| track.id == id);
+                let language = track.map(|track| track.lang.to_owned());
+                let title = track.and_then(|track| track.title.to_owned());
+
                 models::player::update_stream_state(|mut settings| {
                     settings.audio_track = Some(AudioTrack {
                         id: id.to_string(),
-                        language: None,
+                        language: language.to_owned(),
                     });
                     settings
                 });
+
+                models::player::update_track_preference(|mut preference| {
+                    preference.audio_language = language;
+                    preference.audio_title = title;
+                    preference
+                });
             }
             PlayerInput::Fullscreen => {
                 if let Some(window) = relm4::main_application().active_window() {
@@ -645,6 +877,21 @@ impl SimpleComponent for Player {
             }
             PlayerInput::TimeChanged(time, duration) => {
                 models::player::update_time(time, duration);
+
+                if duration > 0.0 && time >= duration * PREBUFFER_THRESHOLD {
+                    self.prebuffer_next();
+                }
+
+                if duration > 0.0 {
+                    if let Some((paused, time, rate, received)) = self.pending_sync.take() {
+                        let time = match paused {
+                            true => time,
+                            false => time + received.elapsed().as_secs_f64() * 1000.0 * rate,
+                        };
+
+                        self.sync_playback(paused, time, rate);
+                    }
+                }
             }
             PlayerInput::TracksChanged => {
                 let player = PLAYER_STATE.read_inner();
@@ -655,17 +902,15 @@ impl SimpleComponent for Player {
                 self.audio_tracks_menu
                     .emit(TracksMenuInput::Update(video.audio_tracks.to_owned()));
 
-                if let Some(state) = &player.stream_state {
-                    if let Some(track) = &state.subtitle_track {
-                        if let Ok(id) = track.id.parse::<i64>() {
-                            self.video.emit(VideoInput::TextTrack(id));
-                        }
+                if !self.tracks_restored && !video.audio_tracks.is_empty() {
+                    self.tracks_restored = true;
+
+                    if let Some(preference) = &player.track_preference {
+                        self.restore_track_preference(preference, &video);
                     }
 
-                    if let Some(track) = &state.audio_track {
-                        if let Ok(id) = track.id.parse::<i64>() {
-                            self.video.emit(VideoInput::AudioTrack(id));
-                        }
+                    if let Some(state) = &player.stream_state {
+                        self.restore_stream_state(state);
                     }
                 }
             }
@@ -684,8 +929,12 @@ impl SimpleComponent for Player {
                 let ctx = CTX_STATE.read_inner();
                 let player = PLAYER_STATE.read_inner();
 
-                match (&player.next_stream, ctx.settings.binge_watching) {
+                let next_stream = self.playlist_next().or(player.next_stream.as_ref());
+
+                match (next_stream.cloned(), ctx.settings.binge_watching) {
                     (Some(stream), true) => {
+                        self.release_prebuffer(Some(&stream));
+
                         sender.input_sender().emit(PlayerInput::Unload);
                         sender
                             .input_sender()
@@ -698,6 +947,115 @@ impl SimpleComponent for Player {
                 let message = t!("error_player").to_string();
                 APP_BROKER.send(AppMsg::Toast((message, 3)));
             }
+            PlayerInput::SyncStream(stream) => {
+                if self.stream.as_ref() != Some(&stream) {
+                    if self.stream.is_some() {
+                        self.unload();
+                    }
+
+                    self.load(*stream);
+                }
+            }
+            PlayerInput::SyncPlayback { paused, time, rate } => {
+                let state = VIDEO_STATE.read_inner();
+
+                // Remote state received before the video is ready is applied once it starts
+                if state.loaded && state.duration > 0.0 {
+                    drop(state);
+                    self.sync_playback(paused, time, rate);
+                } else {
+                    self.pending_sync = Some((paused, time, rate, Instant::now()));
+                }
+            }
+            PlayerInput::Cast(renderer) => {
+                let ctx = CTX_STATE.read_inner();
+                let player = PLAYER_STATE.read_inner();
+                let video = VIDEO_STATE.read_inner();
+
+                let url = self.stream.as_ref().and_then(|stream| {
+                    CoreStream::from(stream.to_owned())
+                        .streaming_url(Some(&ctx.settings.streaming_server_url))
+                });
+
+                match url {
+                    Some(url) => {
+                        let start_time = match video.loaded {
+                            true => video.time,
+                            false => player.time,
+                        };
+
+                        self.video.emit(VideoInput::Pause);
+                        self.cast = Some(CastSession::start(
+                            self.cast.take(),
+                            *renderer,
+                            url,
+                            player.title.to_owned(),
+                            start_time,
+                        ));
+                    }
+                    None => {
+                        let message = t!("error_cast").to_string();
+                        APP_BROKER.send(AppMsg::Toast((message, 3)));
+                    }
+                }
+            }
+            PlayerInput::StopCast => {
+                let time = CAST_STATE.read_inner().time;
+                self.cast = None;
+
+                // Resume locally from where the renderer stopped
+                if VIDEO_STATE.read_inner().loaded {
+                    self.seek_to(time);
+                } else {
+                    sender.input(PlayerInput::UpdateVideo);
+                }
+            }
+            PlayerInput::CastChanged => {
+                let state = CAST_STATE.read_inner();
+
+                if self.cast.is_some() && state.duration > 0.0 {
+                    models::player::update_time(state.time, state.duration);
+                }
+            }
+            PlayerInput::LoadExternal(stream) => {
+                if self.stream.is_some() {
+                    self.unload();
+                }
+
+                self.external_pending = true;
+                self.load(*stream);
+            }
+            PlayerInput::ExternalChanged => {
+                let state = EXTERNAL_PLAYER_STATE.read_inner();
+
+                if self.external.is_some() {
+                    match state.running {
+                        true if state.duration > 0.0 => {
+                            models::player::update_time(state.time, state.duration);
+                        }
+                        true => {}
+                        false => {
+                            drop(state);
+                            self.unload();
+                        }
+                    }
+                }
+            }
+            PlayerInput::ShareState => {
+                if let Some(stream) = &self.stream {
+                    Self::broadcast(PartyMessage::Stream {
+                        stream: Box::new(stream.to_owned()),
+                    });
+
+                    let state = VIDEO_STATE.read_inner();
+
+                    Self::broadcast(PartyMessage::Playback {
+                        paused: state.paused,
+                        time: state.time,
+                        rate: state.speed,
+                    });
+                }
+            }
         }
     }
 
@@ -708,6 +1066,98 @@ impl SimpleComponent for Player {
 }
 
 impl Player {
+    fn load(&mut self, stream: Stream) {
+        self.tracks_restored = false;
+        self.stream = Some(stream.to_owned());
+        models::player::load(stream);
+    }
+
+    fn unload(&mut self) {
+        self.stream = None;
+        self.pending_sync = None;
+        self.cast = None;
+        self.external = None;
+        self.external_pending = false;
+        self.release_prebuffer(None);
+
+        models::player::unload();
+        models::server::clear_statistics();
+        self.video.emit(VideoInput::Unload);
+
+        if self.settings.boolean("player-resize-window") {
+            if let Some((width, height)) = self.default_window_size.take() {
+                if let Some(window) = relm4::main_application().active_window() {
+                    window.animate_size(width, height);
+                }
+            }
+        }
+    }
+
+    fn prebuffer_next(&mut self) {
+        if self.prebuffer.is_some() || !self.settings.boolean("prebuffer-next") {
+            return;
+        }
+
+        let ctx = CTX_STATE.read_inner();
+        let player = PLAYER_STATE.read_inner();
+
+        if let Some(stream) = self.playlist_next().or(player.next_stream.as_ref()) {
+            let server_url = ctx.settings.streaming_server_url.to_owned();
+            self.prebuffer = Some(Prebuffer::start(
+                stream.to_owned(),
+                server_url,
+                self.stream.as_ref(),
+            ));
+        }
+    }
+
+    /// Keeps the prebuffered torrent on the server only if it is about to be played
+    fn release_prebuffer(&mut self, next: Option<&Stream>) {
+        if let Some(prebuffer) = self.prebuffer.take() {
+            prebuffer.cancel(next);
+        }
+    }
+
+    fn playlist_next(&self) -> Option<&Stream> {
+        let stream = self.stream.as_ref()?;
+        let position = self.playlist.iter().position(|item| item == stream)?;
+
+        self.playlist.get(position + 1)
+    }
+
+    fn launch_external(
+        &mut self,
+        url: &Url,
+        title: &str,
+        start_time: f64,
+        sender: &ComponentSender<Self>,
+    ) {
+        let command = self.settings.string("external-player");
+
+        let subtitles = self
+            .stream
+            .iter()
+            .flat_map(|stream| stream.subtitles.iter())
+            .map(|subtitles| subtitles.url.to_owned())
+            .collect_vec();
+
+        match ExternalPlayer::launch(&command, url, title, &subtitles, start_time) {
+            Ok(external) => {
+                self.external = Some(external);
+
+                let message = t!("external_player_opened").to_string();
+                APP_BROKER.send(AppMsg::Toast((message, 3)));
+            }
+            Err(e) => {
+                error!("Failed to launch external player {command}: {e}");
+                sender.input(PlayerInput::Unload);
+
+                let message = t!("error_external_player").to_string();
+                APP_BROKER.send(AppMsg::Toast((message, 3)));
+            }
+        }
+    }
+
     fn create_immersed_timeout(&mut self, sender: ComponentSender<Self>) {
         let task = tokio::spawn(async move {
             sleep(Duration::from_secs(1)).await;
@@ -740,6 +1190,127 @@ impl Player {
         }
     }
 
+    fn restore_track_preference(&self, preference: &TrackPreference, video: &VideoState) {
+        let audio_track = Self::find_track(
+            &video.audio_tracks,
+            &preference.audio_language,
+            &preference.audio_title,
+        );
+
+        if let Some(track) = audio_track {
+            self.video.emit(VideoInput::AudioTrack(track.id));
+        }
+
+        if preference.subtitles_disabled {
+            self.video.emit(VideoInput::TextTrack(-1));
+        } else {
+            let text_track = Self::find_track(
+                &video.text_tracks,
+                &preference.subtitles_language,
+                &preference.subtitles_title,
+            );
+
+            if let Some(track) = text_track {
+                self.video.emit(VideoInput::TextTrack(track.id));
+            }
+        }
+    }
+
+    fn restore_stream_state(&self, state: &StreamItemState) {
+        if let Some(track) = &state.subtitle_track {
+            if let Ok(id) = track.id.parse::<i64>() {
+                self.video.emit(VideoInput::TextTrack(id));
+            }
+        }
+
+        if let Some(track) = &state.audio_track {
+            if let Ok(id) = track.id.parse::<i64>() {
+                self.video.emit(VideoInput::AudioTrack(id));
+            }
+        }
+    }
+
+    fn find_track<'a>(
+        tracks: &'a [MediaTrack],
+        language: &Option<String>,
+        title: &Option<String>,
+    ) -> Option<&'a MediaTrack> {
+        let language = language.as_ref()?;
+        let candidates = tracks
+            .iter()
+            .filter(|track| track.id != -1 && &track.lang == language)
+            .collect_vec();
+
+        candidates
+            .iter()
+            .find(|track| &track.title == title)
+            .or(candidates.first())
+            .copied()
+    }
+
+    fn seek_by(&self, offset: f64) {
+        let time = VIDEO_STATE.read_inner().time;
+        self.seek_to(time + offset);
+    }
+
+    fn seek_to(&self, time: f64) {
+        if let Some(cast) = &self.cast {
+            cast.seek(Self::clamp_time(time, CAST_STATE.read_inner().duration));
+            return;
+        }
+
+        let state = VIDEO_STATE.read_inner();
+        let time = Self::clamp_time(time, state.duration);
+
+        self.video.emit(VideoInput::Seek(time));
+        models::player::update_seek_time(time, state.duration);
+
+        Self::broadcast(PartyMessage::Playback {
+            paused: state.paused,
+            time,
+            rate: state.speed,
+        });
+    }
+
+    /// The duration is 0 until known, only the lower bound applies then
+    fn clamp_time(time: f64, duration: f64) -> f64 {
+        match duration > 0.0 {
+            true => time.clamp(0.0, duration),
+            false => time.max(0.0),
+        }
+    }
+
+    fn sync_playback(&self, paused: bool, time: f64, rate: f64) {
+        let state = VIDEO_STATE.read_inner();
+
+        if state.paused != paused {
+            match paused {
+                true => self.video.emit(VideoInput::Pause),
+                false => self.video.emit(VideoInput::Play),
+            }
+        }
+
+        if (state.time - time).abs() > SYNC_THRESHOLD {
+            self.video.emit(VideoInput::Seek(time));
+            models::player::update_seek_time(time, state.duration);
+        }
+
+        if state.speed != rate {
+            self.video.emit(VideoInput::Speed(rate));
+        }
+    }
+
+    fn broadcast(message: PartyMessage) {
+        APP_BROKER.send(AppMsg::WatchPartySend(message));
+    }
+
+    fn playback(state: &VideoState, cast: &CastState) -> (bool, f64, f64) {
+        match cast.renderer {
+            Some(_) => (cast.paused, cast.time, cast.duration),
+            None => (state.paused, state.time, state.duration),
+        }
+    }
+
     fn ms_to_clock(ms: f64) -> String {
         let total_seconds = (ms / 1000.0).round() as u64;
         let hours = total_seconds / 3600;
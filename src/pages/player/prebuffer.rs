@@ -0,0 +1,131 @@
+use relm4::JoinHandle;
+use reqwest::{header, Client};
+use stremio_core_losange::{
+    stremio_core::types::resource::{Stream as CoreStream, StreamSource},
+    torrent,
+    types::stream::Stream,
+};
+use tracing::{debug, warn};
+use url::Url;
+
+// Enough for the player to start without waiting on the swarm
+const PREBUFFER_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Warms up the next stream while the current one is still playing.
+pub struct Prebuffer {
+    stream: Stream,
+    server_url: Url,
+    task: JoinHandle<()>,
+    created_torrent: bool,
+}
+
+impl Prebuffer {
+    pub fn start(stream: Stream, server_url: Url, current: Option<&Stream>) -> Self {
+        // The next episode of a pack is in the torrent already playing, which is not ours to remove
+        let created_torrent =
+            info_hash(&stream).is_some() && info_hash(&stream) != current.and_then(info_hash);
+
+        let task = relm4::spawn({
+            let stream = stream.to_owned();
+            let server_url = server_url.to_owned();
+
+            async move {
+                match prebuffer(stream, &server_url).await {
+                    Ok(()) => debug!("Next stream prebuffered"),
+                    Err(e) => warn!("Failed to prebuffer next stream: {e}"),
+                }
+            }
+        });
+
+        Self {
+            stream,
+            server_url,
+            task,
+            created_torrent,
+        }
+    }
+
+    /// Stops prebuffering and lets the server drop the torrent it created, unless the next
+    /// stream is in that torrent
+    pub fn cancel(self, next: Option<&Stream>) {
+        if !self.created_torrent {
+            return;
+        }
+
+        let Some(info_hash) = info_hash(&self.stream) else {
+            return;
+        };
+
+        if next.and_then(info_hash) == Some(info_hash) {
+            return;
+        }
+
+        let server_url = self.server_url.to_owned();
+
+        relm4::spawn(async move {
+            if let Err(e) = torrent::remove(&server_url, &info_hash).await {
+                debug!("Failed to remove prebuffered torrent: {e}");
+            }
+        });
+    }
+}
+
+fn info_hash(stream: &Stream) -> Option<[u8; 20]> {
+    match &stream.source {
+        StreamSource::Torrent { info_hash, .. } => Some(*info_hash),
+        _ => None,
+    }
+}
+
+impl Drop for Prebuffer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn prebuffer(stream: Stream, server_url: &Url) -> anyhow::Result<()> {
+    let client = Client::new();
+
+    match &stream.source {
+        StreamSource::Torrent {
+            info_hash,
+            file_idx,
+            announce,
+            ..
+        } => {
+            // Starts the peer search on the server
+            torrent::fetch_files(server_url, info_hash, announce).await?;
+
+            if file_idx.is_none() {
+                return Ok(());
+            }
+
+            let Some(url) = CoreStream::from(stream.to_owned()).streaming_url(Some(server_url))
+            else {
+                return Ok(());
+            };
+
+            let mut response = client
+                .get(url)
+                .header(header::RANGE, format!("bytes=0-{}", PREBUFFER_SIZE - 1))
+                .send()
+                .await?
+                .error_for_status()?;
+
+            let mut received = 0;
+            while let Some(chunk) = response.chunk().await? {
+                received += chunk.len() as u64;
+
+                if received >= PREBUFFER_SIZE {
+                    break;
+                }
+            }
+        }
+        StreamSource::Url { url } if matches!(url.scheme(), "http" | "https") => {
+            client.head(url.to_owned()).send().await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use gtk::glib;
+use relm4::{
+    css,
+    gtk::{self, cairo, prelude::*},
+    ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent,
+};
+use rust_i18n::t;
+use stremio_core_losange::models::{
+    player::PLAYER_STATE,
+    server::{StatisticsSample, SERVER_STATE},
+};
+
+const GRAPH_SIZE: (i32, i32) = (260, 80);
+
+#[derive(Debug)]
+pub enum StatisticsMenuInput {
+    Update,
+}
+
+pub struct StatisticsMenu {
+    graph: gtk::DrawingArea,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for StatisticsMenu {
+    type Init = ();
+    type Input = StatisticsMenuInput;
+    type Output = ();
+
+    view! {
+        gtk::MenuButton {
+            add_css_class: relm4::css::classes::OSD,
+            set_size_request: (45, 45),
+            set_icon_name: "network-transmit-receive-symbolic",
+            set_tooltip_text: Some(&t!("torrent_statistics")),
+
+            #[watch]
+            set_visible: player.torrent_info.is_some() && torrent.is_some(),
+
+            #[wrap(Some)]
+            set_popover = &gtk::Popover {
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_all: 6,
+
+                    gtk::Label {
+                        add_css_class: css::classes::HEADING,
+                        set_halign: gtk::Align::Start,
+                        set_ellipsize: gtk::pango::EllipsizeMode::Middle,
+                        set_max_width_chars: 30,
+
+                        #[watch]
+                        set_label: torrent.map(|torrent| torrent.name.as_str()).unwrap_or_default(),
+                    },
+
+                    #[local_ref]
+                    graph -> gtk::DrawingArea {
+                        set_content_width: GRAPH_SIZE.0,
+                        set_content_height: GRAPH_SIZE.1,
+                    },
+
+                    gtk::Grid {
+                        set_row_spacing: 6,
+                        set_column_spacing: 18,
+
+                        attach[0, 0, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+                            set_halign: gtk::Align::Start,
+                            set_label: &t!("download_speed"),
+                        },
+                        attach[1, 0, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::NUMERIC,
+                            set_halign: gtk::Align::End,
+                            set_hexpand: true,
+
+                            #[watch]
+                            set_label: &Self::speed(torrent.map_or(0.0, |torrent| torrent.download_speed)),
+                        },
+
+                        attach[0, 1, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+                            set_halign: gtk::Align::Start,
+                            set_label: &t!("upload_speed"),
+                        },
+                        attach[1, 1, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::NUMERIC,
+                            set_halign: gtk::Align::End,
+
+                            #[watch]
+                            set_label: &Self::speed(torrent.map_or(0.0, |torrent| torrent.upload_speed)),
+                        },
+
+                        attach[0, 2, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+                            set_halign: gtk::Align::Start,
+                            set_label: &t!("peers"),
+                        },
+                        attach[1, 2, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::NUMERIC,
+                            set_halign: gtk::Align::End,
+
+                            #[watch]
+                            set_label: &torrent.map(|torrent| t!(
+                                "peers_count",
+                                unchoked = torrent.unchoked,
+                                peers = torrent.peers,
+                                swarm = torrent.swarm_size
+                            ).to_string()).unwrap_or_default(),
+                        },
+
+                        attach[0, 3, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::DIM_LABEL,
+                            set_halign: gtk::Align::Start,
+                            set_label: &t!("downloaded"),
+                        },
+                        attach[1, 3, 1, 1] = &gtk::Label {
+                            add_css_class: css::classes::NUMERIC,
+                            set_halign: gtk::Align::End,
+
+                            #[watch]
+                            set_label: &torrent.map(|torrent| t!(
+                                "downloaded_amount",
+                                downloaded = glib::format_size(torrent.downloaded),
+                                progress = (torrent.stream_progress * 100.0).round()
+                            ).to_string()).unwrap_or_default(),
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let player = PLAYER_STATE.read_inner();
+        let server = SERVER_STATE.read_inner();
+        let torrent = server.torrent.as_ref();
+
+        SERVER_STATE.subscribe(sender.input_sender(), |_| StatisticsMenuInput::Update);
+
+        let graph = gtk::DrawingArea::default();
+        graph.set_draw_func(|area, context, width, height| {
+            let server = SERVER_STATE.read_inner();
+
+            if let Some(torrent) = &server.torrent {
+                let samples = torrent.history.iter().copied().collect::<Vec<_>>();
+                Self::draw_graph(area, context, width, height, &samples);
+            }
+        });
+
+        let model = StatisticsMenu { graph };
+
+        let graph = &model.graph;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn pre_view() {
+        let player = PLAYER_STATE.read_inner();
+        let server = SERVER_STATE.read_inner();
+        let torrent = server.torrent.as_ref();
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+        match message {
+            StatisticsMenuInput::Update => {
+                if self.graph.is_mapped() {
+                    self.graph.queue_draw();
+                }
+            }
+        }
+    }
+}
+
+impl StatisticsMenu {
+    fn speed(bytes: f64) -> String {
+        t!("speed_per_second", size = glib::format_size(bytes as u64)).to_string()
+    }
+
+    fn draw_graph(
+        area: &gtk::DrawingArea,
+        context: &cairo::Context,
+        width: i32,
+        height: i32,
+        samples: &[StatisticsSample],
+    ) {
+        let (width, height) = (width as f64, height as f64);
+        let color = area.color();
+
+        context.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            0.1,
+        );
+        context.rectangle(0.0, 0.0, width, height);
+        let _ = context.fill();
+
+        let max = samples
+            .iter()
+            .map(|sample| sample.download_speed.max(sample.upload_speed))
+            .fold(0.0, f64::max);
+
+        if samples.len() < 2 || max <= 0.0 {
+            return;
+        }
+
+        let step = width / (samples.len() - 1) as f64;
+        let y = |speed: f64| height - (speed / max) * (height - 2.0) - 1.0;
+
+        let series: [(fn(&StatisticsSample) -> f64, f64); 2] = [
+            (|sample| sample.upload_speed, 0.4),
+            (|sample| sample.download_speed, 1.0),
+        ];
+
+        for (value, alpha) in series {
+            context.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                alpha,
+            );
+            context.set_line_width(1.5);
+
+            for (i, sample) in samples.iter().enumerate() {
+                context.line_to(i as f64 * step, y(value(sample)));
+            }
+
+            let _ = context.stroke();
+        }
+    }
+}
@@ -19,6 +19,7 @@ struct Track {
 pub struct MediaTrack {
     pub id: i64,
     pub lang: String,
+    pub title: Option<String>,
     pub label: String,
     pub active: bool,
 }
@@ -28,6 +29,7 @@ pub struct VideoState {
     pub loaded: bool,
     pub paused: bool,
     pub volume: f64,
+    pub speed: f64,
     pub duration: f64,
     pub time: f64,
     pub buffering: bool,
@@ -46,7 +48,10 @@ pub enum VideoInput {
     Play,
     Pause,
     Seek(f64),
+    FrameStep,
+    FrameBackStep,
     Volume(f64),
+    Speed(f64),
     TextTrack(i64),
     AudioTrack(i64),
     SubtitlesSize(f64),
@@ -104,6 +109,9 @@ impl SimpleComponent for Video {
                         "volume" if let Some(value) = value.get::<f64>() => {
                             state.volume = value;
                         }
+                        "speed" if let Some(value) = value.get::<f64>() => {
+                            state.speed = value;
+                        }
                         "cache-buffering-state" if let Some(value) = value.get::<i64>() => {
                             state.buffering = value < 100;
                         }
@@ -154,6 +162,7 @@ impl SimpleComponent for Video {
         mpv.observe_property("time-pos");
         mpv.observe_property("duration");
         mpv.observe_property("volume");
+        mpv.observe_property("speed");
         mpv.observe_property("cache-buffering-state");
         mpv.observe_property("width");
         mpv.observe_property("height");
@@ -196,9 +205,18 @@ impl SimpleComponent for Video {
             VideoInput::Seek(time) => {
                 self.mpv.set_property("time-pos", time / SECOND);
             }
+            VideoInput::FrameStep => {
+                self.mpv.send_command("frame-step", &[]);
+            }
+            VideoInput::FrameBackStep => {
+                self.mpv.send_command("frame-back-step", &[]);
+            }
             VideoInput::Volume(volume) => {
                 self.mpv.set_property("volume", volume);
             }
+            VideoInput::Speed(speed) => {
+                self.mpv.set_property("speed", speed);
+            }
             VideoInput::TextTrack(id) => {
                 if id == -1 {
                     self.mpv.set_property("sid", "no");
@@ -252,6 +270,7 @@ impl Video {
                     MediaTrack {
                         id: track.id,
                         lang: code.to_string(),
+                        title: track.title.to_owned(),
                         label,
                         active: track.selected,
                     }
@@ -268,6 +287,7 @@ impl Video {
             MediaTrack {
                 id: -1,
                 lang: "und".to_owned(),
+                title: None,
                 label: "disabled".to_owned(),
                 active: text_track_disabled,
             },
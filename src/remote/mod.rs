@@ -0,0 +1,386 @@
+use std::{convert::Infallible, net::Ipv4Addr, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Incoming,
+    header::{self, HeaderValue},
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use itertools::Itertools;
+use relm4::{
+    gtk::{gio, prelude::SettingsExt},
+    Sender,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stremio_core_losange::models::{player::PLAYER_STATE, search::SEARCH_STATE};
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_tungstenite::{
+    tungstenite::{handshake::derive_accept_key, protocol::Role, Message},
+    WebSocketStream,
+};
+use tracing::{debug, error};
+use url::{form_urlencoded, Url};
+
+use crate::{
+    app::AppMsg,
+    common::net,
+    constants::APP_ID,
+    pages::player::{
+        video::{MediaTrack, VIDEO_STATE},
+        PlayerInput,
+    },
+    APP_BROKER,
+};
+
+const INDEX_HTML: &str = include_str!("index.html");
+const STATE_INTERVAL: Duration = Duration::from_millis(500);
+const SEARCH_MAX_RESULTS: usize = 30;
+const TOKEN_LENGTH: usize = 16;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    PlayPause,
+    Seek { time: f64 },
+    SeekBy { offset: f64 },
+    Volume { volume: f64 },
+    AudioTrack { id: i64 },
+    TextTrack { id: i64 },
+    Next,
+    Search { query: String },
+    OpenDetails { r#type: String, id: String },
+}
+
+struct Context {
+    token: String,
+    player: Sender<PlayerInput>,
+    shutdown: watch::Receiver<()>,
+}
+
+pub struct RemoteControl {
+    player: Sender<PlayerInput>,
+    task: Option<(JoinHandle<()>, watch::Sender<()>)>,
+}
+
+impl RemoteControl {
+    pub fn new(player: Sender<PlayerInput>) -> Self {
+        Self { player, task: None }
+    }
+
+    pub fn restart(&mut self) {
+        self.stop();
+
+        let settings = gio::Settings::new(APP_ID);
+        if !settings.boolean("remote-control") {
+            return;
+        }
+
+        let port = settings.uint("remote-control-port") as u16;
+        let (shutdown_sender, shutdown) = watch::channel(());
+
+        let context = Arc::new(Context {
+            token: token(&settings),
+            player: self.player.clone(),
+            shutdown,
+        });
+
+        let task = relm4::spawn(async move {
+            if let Err(e) = serve(port, context).await {
+                error!("Failed to start remote control server: {e}");
+            }
+        });
+
+        self.task = Some((task, shutdown_sender));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((task, _)) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+pub fn token(settings: &gio::Settings) -> String {
+    let token = settings.string("remote-control-token").to_string();
+
+    if token.is_empty() {
+        return reset_token(settings);
+    }
+
+    token
+}
+
+pub fn reset_token(settings: &gio::Settings) -> String {
+    let mut bytes = [0u8; TOKEN_LENGTH];
+    getrandom::getrandom(&mut bytes).expect("Failed to generate remote control token");
+
+    let token = hex::encode(bytes);
+    let _ = settings.set_string("remote-control-token", &token);
+
+    token
+}
+
+pub fn pairing_url(settings: &gio::Settings) -> String {
+    let address = net::local_ip();
+    let port = settings.uint("remote-control-port");
+
+    format!("http://{address}:{port}/#{}", token(settings))
+}
+
+async fn serve(port: u16, context: Arc<Context>) -> anyhow::Result<()> {
+    let listener = net::bind(Ipv4Addr::UNSPECIFIED.into(), port).await?;
+
+    loop {
+        let (stream, address) = listener.accept().await?;
+        let context = context.clone();
+
+        tokio::spawn(async move {
+            let service = service_fn(move |request| handle(request, context.clone()));
+
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await
+            {
+                debug!("Remote control connection with {address} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    context: Arc<Context>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    if request.method() == Method::GET && request.uri().path() == "/" {
+        return Ok(response(StatusCode::OK, "text/html", INDEX_HTML));
+    }
+
+    if !is_authorized(&request, &context.token) {
+        return Ok(status(StatusCode::UNAUTHORIZED));
+    }
+
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/api/state") => json_response(state()),
+        (&Method::GET, "/api/search") => json_response(search()),
+        (&Method::POST, "/api/command") => {
+            let body = request
+                .into_body()
+                .collect()
+                .await
+                .map(|body| body.to_bytes());
+
+            match body
+                .ok()
+                .and_then(|body| serde_json::from_slice(&body).ok())
+            {
+                Some(command) => {
+                    execute(command, &context.player);
+                    status(StatusCode::NO_CONTENT)
+                }
+                None => status(StatusCode::BAD_REQUEST),
+            }
+        }
+        (&Method::GET, "/api/ws") => upgrade(request, context),
+        _ => status(StatusCode::NOT_FOUND),
+    };
+
+    Ok(response)
+}
+
+fn upgrade(request: Request<Incoming>, context: Arc<Context>) -> Response<Full<Bytes>> {
+    let Some(key) = request.headers().get(header::SEC_WEBSOCKET_KEY) else {
+        return status(StatusCode::BAD_REQUEST);
+    };
+
+    let accept = derive_accept_key(key.as_bytes());
+
+    tokio::spawn(async move {
+        match hyper::upgrade::on(request).await {
+            Ok(upgraded) => {
+                let socket =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+
+                websocket(socket, context).await;
+            }
+            Err(e) => debug!("Remote control websocket upgrade failed: {e}"),
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "Upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept)
+        .body(Full::default())
+        .unwrap_or_default()
+}
+
+async fn websocket<S>(socket: WebSocketStream<S>, context: Arc<Context>)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut outgoing, mut incoming) = socket.split();
+    let mut shutdown = context.shutdown.clone();
+    let mut interval = tokio::time::interval(STATE_INTERVAL);
+    let mut last_state = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let state = state().to_string();
+
+                if last_state.as_ref() != Some(&state) {
+                    if outgoing.send(Message::text(state.to_owned())).await.is_err() {
+                        break;
+                    }
+
+                    last_state = Some(state);
+                }
+            }
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if let Ok(command) = serde_json::from_str(text.as_str()) {
+                        execute(command, &context.player);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+fn execute(command: Command, player: &Sender<PlayerInput>) {
+    match command {
+        Command::PlayPause => player.emit(PlayerInput::PlayPause),
+        Command::Seek { time } => player.emit(PlayerInput::SeekTo(time * 1000.0)),
+        Command::SeekBy { offset } => player.emit(PlayerInput::SeekBy(offset * 1000.0)),
+        Command::Volume { volume } => {
+            let current = VIDEO_STATE.read_inner().volume;
+            player.emit(PlayerInput::Volume(volume - current));
+        }
+        Command::AudioTrack { id } => player.emit(PlayerInput::AudioTrackChanged(id)),
+        Command::TextTrack { id } => player.emit(PlayerInput::TextTrackChanged(id)),
+        Command::Next => player.emit(PlayerInput::PlayNext),
+        Command::Search { query } => APP_BROKER.send(AppMsg::OpenSearch(Some(query))),
+        Command::OpenDetails { r#type, id } => APP_BROKER.send(AppMsg::OpenDetails((id, r#type))),
+    }
+}
+
+fn state() -> Value {
+    let player = PLAYER_STATE.read_inner();
+    let video = VIDEO_STATE.read_inner();
+
+    json!({
+        "loaded": video.loaded,
+        "title": player.title,
+        "image": player.image.as_ref().map(Url::as_str),
+        "paused": video.paused,
+        "buffering": video.buffering,
+        "time": video.time / 1000.0,
+        "duration": video.duration / 1000.0,
+        "volume": video.volume,
+        "audio_tracks": tracks(&video.audio_tracks),
+        "text_tracks": tracks(&video.text_tracks),
+        "has_next": player.next_stream.is_some(),
+    })
+}
+
+fn tracks(tracks: &[MediaTrack]) -> Value {
+    tracks
+        .iter()
+        .map(|track| {
+            json!({
+                "id": track.id,
+                "label": track.label,
+                "active": track.active,
+            })
+        })
+        .collect()
+}
+
+fn search() -> Value {
+    let state = SEARCH_STATE.read_inner();
+
+    let items = state
+        .library
+        .iter()
+        .chain(
+            state
+                .catalogs
+                .iter()
+                .flat_map(|catalog| catalog.items.iter()),
+        )
+        .unique_by(|item| (&item.r#type, &item.id))
+        .take(SEARCH_MAX_RESULTS)
+        .map(|item| {
+            json!({
+                "id": item.id,
+                "type": item.r#type,
+                "name": item.name,
+                "image": item.image.as_ref().map(Url::as_str),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "query": state.query,
+        "loading": state.loading,
+        "items": items,
+    })
+}
+
+fn is_authorized(request: &Request<Incoming>, token: &str) -> bool {
+    let header_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.to_owned());
+
+    // Browsers cannot set headers on websocket requests
+    let query_token = request.uri().query().and_then(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.to_string())
+    });
+
+    header_token
+        .or(query_token)
+        .is_some_and(|candidate| constant_time_eq(candidate.as_bytes(), token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn json_response(value: Value) -> Response<Full<Bytes>> {
+    response(StatusCode::OK, "application/json", value.to_string())
+}
+
+fn status(status: StatusCode) -> Response<Full<Bytes>> {
+    response(status, "text/plain", status.to_string())
+}
+
+fn response<T: Into<Bytes>>(
+    status: StatusCode,
+    content_type: &'static str,
+    body: T,
+) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+    response
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use itertools::Itertools;
+use relm4::gtk::{gio, glib, prelude::*};
+use rust_i18n::t;
+use stremio_core_losange::{
+    models::{self, search::SHELL_SEARCH_STATE},
+    types::item::Item,
+};
+use tracing::error;
+
+use crate::{
+    app::{App, AppMsg},
+    common::net,
+    APP_BROKER,
+};
+
+const OBJECT_PATH: &str = "/xyz/timtimtim/Losange/SearchProvider";
+const INTERFACE_NAME: &str = "org.gnome.Shell.SearchProvider2";
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="org.gnome.Shell.SearchProvider2">
+    <method name="GetInitialResultSet">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetSubsearchResultSet">
+      <arg type="as" name="previous_results" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetResultMetas">
+      <arg type="as" name="identifiers" direction="in"/>
+      <arg type="aa{sv}" name="metas" direction="out"/>
+    </method>
+    <method name="ActivateResult">
+      <arg type="s" name="identifier" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+    <method name="LaunchSearch">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+const SEARCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_RESULTS: usize = 10;
+
+static RESULTS: LazyLock<DashMap<String, Item>> = LazyLock::new(DashMap::new);
+
+pub fn register(connection: &gio::DBusConnection) {
+    let interface = gio::DBusNodeInfo::for_xml(INTERFACE_XML)
+        .ok()
+        .and_then(|node| node.lookup_interface(INTERFACE_NAME))
+        .expect("Failed to parse search provider interface");
+
+    let result = connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(|_, _, _, _, method, parameters, invocation| match method {
+            "GetInitialResultSet" => {
+                // A new search starts, results of the previous ones will not be asked for again
+                RESULTS.clear();
+
+                let terms = parameters.child_value(0).get::<Vec<String>>();
+                glib::spawn_future_local(async move {
+                    let results = search(terms.unwrap_or_default()).await;
+                    invocation.return_value(Some(&(results,).to_variant()));
+                });
+            }
+            "GetSubsearchResultSet" => {
+                let terms = parameters.child_value(1).get::<Vec<String>>();
+                glib::spawn_future_local(async move {
+                    let results = search(terms.unwrap_or_default()).await;
+                    invocation.return_value(Some(&(results,).to_variant()));
+                });
+            }
+            "GetResultMetas" => {
+                let identifiers = parameters.child_value(0).get::<Vec<String>>();
+                glib::spawn_future_local(async move {
+                    let metas = result_metas(identifiers.unwrap_or_default()).await;
+                    invocation.return_value(Some(&(metas,).to_variant()));
+                });
+            }
+            "ActivateResult" => {
+                let identifier = parameters.child_value(0).get::<String>();
+                if let Some((r#type, id)) = identifier.as_ref().and_then(|id| id.split_once(':')) {
+                    APP_BROKER.send(AppMsg::OpenDetails((id.to_owned(), r#type.to_owned())));
+                    present_window();
+                }
+
+                invocation.return_value(None);
+            }
+            "LaunchSearch" => {
+                let terms = parameters.child_value(0).get::<Vec<String>>();
+                APP_BROKER.send(AppMsg::OpenSearch(terms.map(|terms| terms.join(" "))));
+                present_window();
+
+                invocation.return_value(None);
+            }
+            _ => invocation.return_error(gio::DBusError::UnknownMethod, method),
+        })
+        .build();
+
+    if let Err(e) = result {
+        error!("Failed to register search provider: {e}");
+    }
+}
+
+async fn search(terms: Vec<String>) -> Vec<String> {
+    let query = terms.join(" ");
+    let started = Instant::now();
+
+    App::initialize_core().await;
+    models::search::load_shell(query.to_owned());
+
+    loop {
+        glib::timeout_future(SEARCH_POLL_INTERVAL).await;
+
+        let state = SHELL_SEARCH_STATE.read_inner();
+        let timed_out = started.elapsed() > SEARCH_TIMEOUT;
+
+        if state.query.as_ref() != Some(&query) {
+            if timed_out {
+                return vec![];
+            }
+
+            continue;
+        }
+
+        // Library matches are known locally, no need to wait for the addons
+        let ready = !state.library.is_empty() || (!state.loading && !state.catalogs.is_empty());
+
+        if ready || timed_out {
+            return state
+                .library
+                .iter()
+                .chain(
+                    state
+                        .catalogs
+                        .iter()
+                        .flat_map(|catalog| catalog.items.iter()),
+                )
+                .unique_by(|item| (&item.r#type, &item.id))
+                .take(MAX_RESULTS)
+                .map(|item| {
+                    let identifier = format!("{}:{}", item.r#type, item.id);
+                    RESULTS.insert(identifier.to_owned(), item.to_owned());
+                    identifier
+                })
+                .collect_vec();
+        }
+    }
+}
+
+async fn result_metas(identifiers: Vec<String>) -> Vec<HashMap<String, glib::Variant>> {
+    let mut metas = vec![];
+
+    for identifier in identifiers {
+        let Some(item) = RESULTS.get(&identifier).map(|item| item.value().to_owned()) else {
+            continue;
+        };
+
+        let mut meta = HashMap::new();
+        meta.insert("id".to_owned(), identifier.to_variant());
+        meta.insert("name".to_owned(), item.name.to_variant());
+
+        let type_name = t!(item.r#type.as_str()).to_string();
+        let description = item
+            .released
+            .as_ref()
+            .map_or(type_name.to_owned(), |released| {
+                format!("{type_name} · {released}")
+            });
+        meta.insert("description".to_owned(), description.to_variant());
+
+        if let Some(icon) = poster_icon(&item).await {
+            meta.insert("icon".to_owned(), icon);
+        }
+
+        metas.push(meta);
+    }
+
+    metas
+}
+
+async fn poster_icon(item: &Item) -> Option<glib::Variant> {
+    let image = item.image.to_owned()?;
+
+    let bytes = relm4::spawn(async move {
+        let response = net::fetch(image).await.ok()?;
+        response.bytes().await.ok()
+    })
+    .await
+    .ok()
+    .flatten()?;
+
+    let icon = gio::BytesIcon::new(&glib::Bytes::from_owned(bytes));
+    icon.serialize()
+}
+
+fn present_window() {
+    let application = relm4::main_application();
+
+    match application.windows().first() {
+        Some(window) => window.present(),
+        None => application.activate(),
+    }
+}
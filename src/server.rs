@@ -1,42 +1,596 @@
 use std::{
-    fs,
-    path::Path,
-    process::{Child, Command},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
-use anyhow::Context;
+use anyhow::{bail, Context};
+use relm4::{JoinHandle, SharedState};
+use rust_i18n::t;
+use sha2::{Digest, Sha256};
+use stremio_core_losange::models::{self, ctx::CTX_STATE, server::SERVER_STATE};
+use tokio::{sync::Notify, time::sleep};
+use tracing::{error, warn};
 use url::Url;
 
-use crate::constants::{SERVER_DOWNLOAD_ENDPOINT, SERVER_VERSION};
+use crate::{
+    app::AppMsg,
+    constants::{SERVER_CHECKSUMS, SERVER_DOWNLOAD_ENDPOINT, SERVER_VERSION},
+    engine::Engine,
+    node::{self, NodeError, NodeRuntime},
+    APP_BROKER,
+};
+
+const LOG_FILE: &str = "server.log";
+const LOG_MAX_SIZE: u64 = 1024 * 1024;
+const LOG_ROTATIONS: usize = 3;
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const HEALTH_CHECK_TICKS: u32 = 5;
+const HEALTH_MAX_FAILURES: u32 = 3;
+const STARTUP_GRACE: Duration = Duration::from_secs(30);
+const STABLE_DURATION: Duration = Duration::from_secs(60);
+const BACKOFF_MIN: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerStatus {
+    #[default]
+    Stopped,
+    Starting,
+    Running,
+    Restarting,
+    Failed,
+    Invalid,
+    NoRuntime,
+}
+
+#[derive(Default, Debug)]
+pub struct ServerProcessState {
+    pub status: ServerStatus,
+    pub restarts: u32,
+    pub runtime: Option<Result<NodeRuntime, NodeError>>,
+}
+
+pub static SERVER_PROCESS_STATE: SharedState<ServerProcessState> = SharedState::new();
+
+enum Exit {
+    Requested,
+    Crashed,
+    Unresponsive,
+}
+
+pub async fn initialize(data_location: &Path, node_path: &str) -> anyhow::Result<Server> {
+    let runtime = node::detect(node_path).await;
+    SERVER_PROCESS_STATE.write().runtime = Some(runtime.clone());
+
+    let runtime = runtime.inspect_err(|_| set_status(ServerStatus::NoRuntime))?;
+
+    let current = Installation::new(data_location, "server");
+    let previous = Installation::new(data_location, "server.previous");
+
+    let up_to_date = current.version().as_deref() == Some(SERVER_VERSION);
+
+    if !up_to_date || current.verify().is_err() {
+        match download().await {
+            Ok(bytes) => match verify_pinned(&bytes) {
+                Ok(()) => {
+                    // Keep the last good version around in case the new one is broken
+                    if current.verify().is_ok() {
+                        current
+                            .move_to(&previous)
+                            .context("Failed to keep previous server file")?;
+                    }
+
+                    current
+                        .install(&bytes, SERVER_VERSION)
+                        .context("Failed to write server file")?;
+                }
+                Err(e) => {
+                    error!("Rejected downloaded server file: {e}");
+                    set_status(ServerStatus::Invalid);
+                    notify_invalid();
+                }
+            },
+            Err(e) => error!("Failed to download server file: {e}"),
+        }
+    }
+
+    let (installation, checksum) = [&current, &previous]
+        .into_iter()
+        .find_map(|installation| {
+            installation
+                .verify()
+                .map_err(|e| warn!("{e}"))
+                .ok()
+                .map(|checksum| (installation, checksum))
+        })
+        .context("No valid server file available")?;
+
+    if installation.version().as_deref() != Some(SERVER_VERSION) {
+        warn!(
+            "Falling back to server version {:?}",
+            installation.version()
+        );
+    }
+
+    let log_path = Path::new(&data_location).join(LOG_FILE);
+
+    Ok(Server::start(
+        runtime.path,
+        installation.script.to_owned(),
+        checksum,
+        log_path,
+    ))
+}
+
+async fn download() -> anyhow::Result<Vec<u8>> {
+    let download_url = Url::parse(
+        SERVER_DOWNLOAD_ENDPOINT
+            .replace("VERSION", SERVER_VERSION)
+            .as_str(),
+    )?;
+
+    let latest_file = reqwest::get(download_url)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await
+        .context("Failed to fetch server file")?;
+
+    Ok(latest_file.to_vec())
+}
+
+fn verify_pinned(bytes: &[u8]) -> anyhow::Result<()> {
+    let pinned = pinned_checksum(SERVER_VERSION)?;
+    let checksum = sha256(bytes);
+
+    if checksum != pinned {
+        bail!("Checksum mismatch for version {SERVER_VERSION}: expected {pinned}, got {checksum}");
+    }
+
+    Ok(())
+}
+
+struct Installation {
+    script: PathBuf,
+    version: PathBuf,
+    checksum: PathBuf,
+}
+
+impl Installation {
+    fn new(data_location: &Path, name: &str) -> Self {
+        Self {
+            script: data_location.join(format!("{name}.js")),
+            version: data_location.join(format!("{name}_version")),
+            checksum: data_location.join(format!("{name}_checksum")),
+        }
+    }
+
+    fn version(&self) -> Option<String> {
+        fs::read_to_string(&self.version).ok()
+    }
+
+    /// Only versions with a pinned checksum are accepted.
+    fn verify(&self) -> anyhow::Result<String> {
+        let version = self.version().context("Missing server version")?;
+        let pinned = pinned_checksum(&version)?;
+
+        verify(&self.script, pinned)?;
+
+        // Installs from before checksums were recorded get theirs once verified
+        if !self.checksum.exists() {
+            write_atomic(&self.checksum, pinned.as_bytes())
+                .context("Failed to record server checksum")?;
+        }
+
+        Ok(pinned.to_owned())
+    }
+
+    fn install(&self, bytes: &[u8], version: &str) -> std::io::Result<()> {
+        write_atomic(&self.script, bytes)?;
+        write_atomic(&self.checksum, sha256(bytes).as_bytes())?;
+
+        // Written last, an interrupted install is never mistaken for a complete one
+        write_atomic(&self.version, version.as_bytes())
+    }
+
+    fn move_to(&self, other: &Installation) -> std::io::Result<()> {
+        fs::rename(&self.version, &other.version)?;
+        fs::rename(&self.script, &other.script)?;
+        fs::rename(&self.checksum, &other.checksum)
+    }
+}
+
+fn pinned_checksum(version: &str) -> anyhow::Result<&'static str> {
+    SERVER_CHECKSUMS
+        .iter()
+        .find(|(pinned_version, _)| *pinned_version == version)
+        .map(|(_, checksum)| *checksum)
+        .with_context(|| format!("No pinned checksum for server version {version}"))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn verify(path: &Path, expected: &str) -> anyhow::Result<()> {
+    let bytes = fs::read(path).context("Failed to read server file")?;
+    let checksum = sha256(&bytes);
+
+    if checksum != expected.trim() {
+        bail!(
+            "Checksum mismatch for {}: expected {expected}, got {checksum}",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_path = path.to_owned().into_os_string();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path)
+}
+
+fn notify_invalid() {
+    let message = t!("error_server_integrity").to_string();
+    APP_BROKER.send(AppMsg::Toast((message, 5)));
+}
+
+pub struct Server {
+    process: Arc<Mutex<Option<Child>>>,
+    restart: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl Server {
+    fn start(node_path: PathBuf, file_path: PathBuf, checksum: String, log_path: PathBuf) -> Self {
+        let process = Arc::new(Mutex::new(None));
+        let restart = Arc::new(Notify::new());
+        let log = Arc::new(Mutex::new(RotatingLog::new(log_path)));
+
+        let task = relm4::spawn(supervise(
+            node_path,
+            file_path,
+            checksum,
+            log,
+            process.clone(),
+            restart.clone(),
+        ));
+
+        Self {
+            process,
+            restart,
+            task,
+        }
+    }
+
+    /// Runs the built-in torrent engine instead of server.js
+    pub fn start_native(data_location: &Path) -> Self {
+        let restart = Arc::new(Notify::new());
+        SERVER_PROCESS_STATE.write().runtime = None;
+
+        let task = relm4::spawn(supervise_native(data_location.to_owned(), restart.clone()));
+
+        Self {
+            process: Arc::new(Mutex::new(None)),
+            restart,
+            task,
+        }
+    }
+
+    pub fn restart(&self) {
+        self.restart.notify_one();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.task.abort();
+
+        if let Some(mut child) = self.process.lock().expect("Failed to lock server").take() {
+            let _ = child.kill();
+        }
+
+        set_status(ServerStatus::Stopped);
+    }
+}
+
+async fn supervise(
+    node_path: PathBuf,
+    file_path: PathBuf,
+    checksum: String,
+    log: Arc<Mutex<RotatingLog>>,
+    process: Arc<Mutex<Option<Child>>>,
+    restart: Arc<Notify>,
+) {
+    let mut failures = 0;
+
+    loop {
+        set_status(ServerStatus::Starting);
+
+        // The file could have been replaced since it was installed
+        if let Err(e) = verify(&file_path, &checksum) {
+            error!("Refusing to start server: {e}");
+            set_status(ServerStatus::Invalid);
+            notify_invalid();
+            return;
+        }
+
+        match spawn(&node_path, &file_path, &log) {
+            Ok(child) => *process.lock().expect("Failed to lock server") = Some(child),
+            Err(e) => {
+                error!("Failed to start server: {e}");
+                set_status(ServerStatus::Failed);
+
+                failures += 1;
+                backoff(failures, &restart).await;
+                continue;
+            }
+        }
+
+        let started = Instant::now();
+        let exit = watch(&process, &restart, started).await;
+
+        if let Some(mut child) = process.lock().expect("Failed to lock server").take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        match exit {
+            Exit::Requested => failures = 0,
+            Exit::Crashed | Exit::Unresponsive => {
+                if started.elapsed() >= STABLE_DURATION {
+                    failures = 0;
+                }
+
+                failures += 1;
+
+                {
+                    let mut state = SERVER_PROCESS_STATE.write();
+                    state.status = ServerStatus::Restarting;
+                    state.restarts += 1;
+                }
+
+                backoff(failures, &restart).await;
+            }
+        }
+    }
+}
 
-pub async fn initialize(data_location: &Path) -> anyhow::Result<Child> {
-    let file_path = Path::new(&data_location).join("server.js");
-    let version_path = Path::new(&data_location).join("server_version");
+async fn supervise_native(data_location: PathBuf, restart: Arc<Notify>) {
+    let mut failures = 0;
 
-    let should_download = fs::read_to_string(&version_path)
-        .map_or(true, |current_version| current_version != SERVER_VERSION);
+    loop {
+        set_status(ServerStatus::Starting);
 
-    if should_download {
-        let download_url = Url::parse(
-            SERVER_DOWNLOAD_ENDPOINT
-                .replace("VERSION", SERVER_VERSION)
-                .as_str(),
-        )?;
+        let result = async {
+            let engine = Arc::new(Engine::new(&data_location).await?);
+            let listener = Engine::bind().await?;
 
-        let latest_file = reqwest::get(download_url)
-            .await?
-            .bytes()
-            .await
-            .context("Failed to fetch server file")?;
+            set_status(ServerStatus::Running);
+            models::server::reload();
 
-        fs::write(&file_path, latest_file).context("Failed to write server file")?;
-        fs::write(&version_path, SERVER_VERSION).context("Failed to write version file")?;
+            engine.serve(listener).await
+        };
+
+        tokio::select! {
+            _ = restart.notified() => failures = 0,
+            result = result => {
+                if let Err(e) = result {
+                    error!("Torrent engine stopped: {e}");
+                }
+
+                set_status(ServerStatus::Failed);
+
+                failures += 1;
+                backoff(failures, &restart).await;
+            }
+        }
     }
+}
+
+async fn watch(process: &Mutex<Option<Child>>, restart: &Notify, started: Instant) -> Exit {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut ticks = 0;
+    let mut health_failures = 0;
+
+    loop {
+        tokio::select! {
+            _ = restart.notified() => return Exit::Requested,
+            _ = interval.tick() => {}
+        }
+
+        let status = process
+            .lock()
+            .expect("Failed to lock server")
+            .as_mut()
+            .map(|child| child.try_wait());
+
+        if !matches!(status, Some(Ok(None))) {
+            warn!("Server exited: {status:?}");
+            return Exit::Crashed;
+        }
+
+        ticks += 1;
+        if ticks % HEALTH_CHECK_TICKS != 0 {
+            continue;
+        }
+
+        // The result of this reload is what the next health check reads
+        let online = SERVER_STATE.read_inner().online;
+        models::server::reload();
 
-    let process = Command::new("node")
+        if online {
+            health_failures = 0;
+            set_status(ServerStatus::Running);
+        } else if started.elapsed() >= STARTUP_GRACE && is_local_server() {
+            health_failures += 1;
+
+            if health_failures >= HEALTH_MAX_FAILURES {
+                warn!("Server is not responding");
+                return Exit::Unresponsive;
+            }
+        }
+    }
+}
+
+async fn backoff(failures: u32, restart: &Notify) {
+    let delay = BACKOFF_MIN
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(BACKOFF_MAX);
+
+    tokio::select! {
+        _ = restart.notified() => {}
+        _ = sleep(delay) => {}
+    }
+}
+
+fn spawn(
+    node_path: &Path,
+    file_path: &Path,
+    log: &Arc<Mutex<RotatingLog>>,
+) -> anyhow::Result<Child> {
+    let mut child = Command::new(node_path)
         .arg(file_path.as_os_str())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
         .context("Failed to start server")?;
 
-    Ok(process)
+    if let Some(stdout) = child.stdout.take() {
+        pipe(stdout, log.clone());
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        pipe(stderr, log.clone());
+    }
+
+    Ok(child)
+}
+
+fn pipe(output: impl Read + Send + 'static, log: Arc<Mutex<RotatingLog>>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            log.lock()
+                .expect("Failed to lock server log")
+                .write_line(&line);
+        }
+    });
+}
+
+fn set_status(status: ServerStatus) {
+    if SERVER_PROCESS_STATE.read_inner().status != status {
+        SERVER_PROCESS_STATE.write().status = status;
+    }
+}
+
+fn is_local_server() -> bool {
+    let ctx = CTX_STATE.read_inner();
+
+    match ctx.settings.streaming_server_url.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(address)) => address.is_loopback(),
+        Some(url::Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    }
+}
+
+struct RotatingLog {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingLog {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            size: 0,
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        let length = line.len() as u64 + 1;
+
+        if self.file.is_none() {
+            self.open();
+        }
+
+        if self.size + length > LOG_MAX_SIZE {
+            self.rotate();
+            self.open();
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            if writeln!(file, "{line}").is_ok() {
+                self.size += length;
+            }
+        }
+    }
+
+    fn open(&mut self) {
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| error!("Failed to open server log: {e}"))
+            .ok();
+
+        self.size = self
+            .file
+            .as_ref()
+            .and_then(|file| file.metadata().ok())
+            .map_or(0, |metadata| metadata.len());
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        self.size = 0;
+
+        for index in (1..LOG_ROTATIONS).rev() {
+            let _ = fs::rename(self.rotation(index), self.rotation(index + 1));
+        }
+
+        let _ = fs::rename(&self.path, self.rotation(1));
+    }
+
+    fn rotation(&self, index: usize) -> PathBuf {
+        let mut path = self.path.to_owned().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pinned_checksum;
+    use crate::constants::{SERVER_CHECKSUMS, SERVER_VERSION};
+
+    #[test]
+    fn current_version_is_pinned() {
+        assert!(pinned_checksum(SERVER_VERSION).is_ok());
+    }
+
+    #[test]
+    fn checksums_are_sha256() {
+        for (version, checksum) in SERVER_CHECKSUMS {
+            assert_eq!(checksum.len(), 64, "Invalid checksum for {version}");
+            assert!(
+                hex::decode(checksum).is_ok(),
+                "Invalid checksum for {version}"
+            );
+        }
+    }
 }
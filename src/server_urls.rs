@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use relm4::SharedState;
+use reqwest::Client;
+use stremio_core_losange::models::{self, ctx::CTX_STATE};
+use tracing::{info, warn};
+use url::Url;
+
+use crate::{app::AppMsg, APP_BROKER};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const FAILOVER_THRESHOLD: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerUrlStatus {
+    Online(Duration),
+    Offline,
+}
+
+#[derive(Default, Debug)]
+pub struct ServerUrlsState {
+    pub statuses: HashMap<Url, ServerUrlStatus>,
+}
+
+pub static SERVER_URLS_STATE: SharedState<ServerUrlsState> = SharedState::new();
+
+pub fn start_monitor() {
+    relm4::spawn(async {
+        let client = Client::builder()
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(PROBE_INTERVAL);
+        let mut failures = 0;
+
+        loop {
+            interval.tick().await;
+
+            let (urls, selected) = {
+                let ctx = CTX_STATE.read_inner();
+                (
+                    ctx.server_urls.to_owned(),
+                    ctx.settings.streaming_server_url.to_owned(),
+                )
+            };
+
+            let statuses = join_all(urls.iter().map(|url| probe(&client, url))).await;
+            let statuses = urls
+                .iter()
+                .cloned()
+                .zip(statuses)
+                .collect::<HashMap<_, _>>();
+
+            let selected_offline = statuses.get(&selected) == Some(&ServerUrlStatus::Offline);
+            let next = next_reachable(&urls, &statuses, &selected);
+
+            SERVER_URLS_STATE.write().statuses = statuses;
+
+            failures = match selected_offline {
+                true => failures + 1,
+                false => 0,
+            };
+
+            if failures >= FAILOVER_THRESHOLD {
+                if let Some(url) = next {
+                    warn!("{selected} is offline, {url} is reachable");
+                    failures = 0;
+                    APP_BROKER.send(AppMsg::ServerFailover(url.to_owned()));
+                }
+            }
+        }
+    });
+}
+
+pub fn select(url: Url) {
+    info!("Selecting streaming server {url}");
+
+    models::ctx::update_settings(|mut settings| {
+        settings.streaming_server_url = url;
+        settings
+    });
+}
+
+async fn probe(client: &Client, url: &Url) -> ServerUrlStatus {
+    let Ok(settings_url) = url.join("settings") else {
+        return ServerUrlStatus::Offline;
+    };
+
+    let start = Instant::now();
+
+    match client
+        .get(settings_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(_) => ServerUrlStatus::Online(start.elapsed()),
+        Err(_) => ServerUrlStatus::Offline,
+    }
+}
+
+fn next_reachable<'a>(
+    urls: &'a [Url],
+    statuses: &HashMap<Url, ServerUrlStatus>,
+    selected: &Url,
+) -> Option<&'a Url> {
+    let position = urls.iter().position(|url| url == selected).unwrap_or(0);
+
+    // Try the servers after the selected one first, then wrap around
+    urls.iter()
+        .cycle()
+        .skip(position + 1)
+        .take(urls.len())
+        .filter(|url| *url != selected)
+        .find(|url| matches!(statuses.get(*url), Some(ServerUrlStatus::Online(_))))
+}
@@ -0,0 +1,258 @@
+//! Mock UPnP MediaRenderer, useful to test casting without a real device.
+//!
+//! Usage:
+//!   cargo run --example mock_renderer -- [name]
+//!
+//! It answers SSDP searches, serves a device description and logs every AVTransport action it
+//! receives while simulating playback progress.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use stremio_core_losange::cast::{
+    element_text, format_time, header_value, parse_time, AV_TRANSPORT, MEDIA_RENDERER,
+    SSDP_ADDRESS, SSDP_PORT,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UdpSocket},
+};
+
+const DURATION: f64 = 45.0 * 60.0 * 1000.0;
+
+#[derive(Default)]
+struct Playback {
+    uri: Option<String>,
+    playing: bool,
+    time: f64,
+    updated: Option<Instant>,
+}
+
+impl Playback {
+    fn current_time(&self) -> f64 {
+        let elapsed = match (self.playing, self.updated) {
+            (true, Some(updated)) => updated.elapsed().as_secs_f64() * 1000.0,
+            _ => 0.0,
+        };
+
+        (self.time + elapsed).min(DURATION)
+    }
+
+    fn set_playing(&mut self, playing: bool) {
+        self.time = self.current_time();
+        self.playing = playing;
+        self.updated = Some(Instant::now());
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let name = std::env::args()
+        .nth(1)
+        .unwrap_or("Mock Renderer".to_owned());
+
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    let port = listener.local_addr()?.port();
+
+    let ssdp = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, SSDP_PORT)).await?;
+    ssdp.join_multicast_v4(SSDP_ADDRESS, Ipv4Addr::UNSPECIFIED)?;
+
+    println!("{name} listening on port {port}");
+
+    let playback = Arc::new(Mutex::new(Playback::default()));
+    let mut buffer = [0; 2048];
+
+    loop {
+        tokio::select! {
+            Ok((length, address)) = ssdp.recv_from(&mut buffer) => {
+                let request = String::from_utf8_lossy(&buffer[..length]);
+
+                let searched = header_value(&request, "st")
+                    .is_some_and(|target| target == MEDIA_RENDERER || target == "ssdp:all");
+
+                if request.starts_with("M-SEARCH") && searched {
+                    let response = search_response(address, port);
+                    ssdp.send_to(response.as_bytes(), address).await?;
+                }
+            }
+            Ok((stream, _)) = listener.accept() => {
+                let name = name.to_owned();
+                let playback = playback.clone();
+
+                tokio::spawn(async move {
+                    if let Err(e) = handle(stream, &name, playback).await {
+                        eprintln!("Request failed: {e}");
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn search_response(address: SocketAddr, port: u16) -> String {
+    // Answer from the interface the search came from
+    let host = match address.ip().is_loopback() {
+        true => Ipv4Addr::LOCALHOST.to_string(),
+        false => std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .and_then(|socket| socket.connect(address).map(|_| socket))
+            .and_then(|socket| socket.local_addr())
+            .map_or(Ipv4Addr::LOCALHOST.to_string(), |local| {
+                local.ip().to_string()
+            }),
+    };
+
+    format!(
+        "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=1800\r\nEXT:\r\nLOCATION: http://{host}:{port}/description.xml\r\nST: {MEDIA_RENDERER}\r\nUSN: uuid:losange-mock-renderer::{MEDIA_RENDERER}\r\n\r\n"
+    )
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    name: &str,
+    playback: Arc<Mutex<Playback>>,
+) -> std::io::Result<()> {
+    let mut request = vec![];
+    let mut buffer = [0; 4096];
+
+    // Read the headers, then as much body as announced
+    let body_start = loop {
+        let length = stream.read(&mut buffer).await?;
+        if length == 0 {
+            return Ok(());
+        }
+
+        request.extend_from_slice(&buffer[..length]);
+
+        if let Some(position) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..body_start]).to_string();
+    let content_length = header_value(&head, "content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while request.len() < body_start + content_length {
+        let length = stream.read(&mut buffer).await?;
+        if length == 0 {
+            break;
+        }
+
+        request.extend_from_slice(&buffer[..length]);
+    }
+
+    let body = String::from_utf8_lossy(&request[body_start..]).to_string();
+
+    let response = match head.split_whitespace().nth(1) {
+        Some("/description.xml") => description(name),
+        Some("/control") => {
+            let action = header_value(&head, "soapaction")
+                .and_then(|value| value.trim_matches('"').split_once('#'))
+                .map(|(_, action)| action.to_owned())
+                .unwrap_or_default();
+
+            control(&action, &body, &mut playback.lock().unwrap())
+        }
+        _ => String::new(),
+    };
+
+    let status = match response.is_empty() {
+        true => "404 Not Found",
+        false => "200 OK",
+    };
+
+    let message = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/xml; charset=\"utf-8\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+        response.len(),
+    );
+
+    stream.write_all(message.as_bytes()).await
+}
+
+fn control(action: &str, body: &str, playback: &mut Playback) -> String {
+    let arguments = match action {
+        "SetAVTransportURI" => {
+            playback.uri = element_text(body, "CurrentURI");
+            playback.set_playing(false);
+            playback.time = 0.0;
+            println!(
+                "SetAVTransportURI {}",
+                playback.uri.as_deref().unwrap_or("")
+            );
+            String::new()
+        }
+        "Play" => {
+            playback.set_playing(true);
+            println!("Play");
+            String::new()
+        }
+        "Pause" => {
+            playback.set_playing(false);
+            println!("Pause");
+            String::new()
+        }
+        "Stop" => {
+            playback.set_playing(false);
+            playback.time = 0.0;
+            println!("Stop");
+            String::new()
+        }
+        "Seek" => {
+            let target = element_text(body, "Target").unwrap_or_default();
+            playback.time = parse_time(&target).unwrap_or_default();
+            playback.updated = Some(Instant::now());
+            println!("Seek {target}");
+            String::new()
+        }
+        "GetTransportInfo" => {
+            let state = match (&playback.uri, playback.playing) {
+                (None, _) => "NO_MEDIA_PRESENT",
+                (Some(_), true) => "PLAYING",
+                (Some(_), false) => "PAUSED_PLAYBACK",
+            };
+
+            format!(
+                "<CurrentTransportState>{state}</CurrentTransportState><CurrentTransportStatus>OK</CurrentTransportStatus><CurrentSpeed>1</CurrentSpeed>"
+            )
+        }
+        "GetPositionInfo" => format!(
+            "<Track>1</Track><TrackDuration>{}</TrackDuration><RelTime>{}</RelTime>",
+            format_time(DURATION),
+            format_time(playback.current_time()),
+        ),
+        _ => return String::new(),
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/"><s:Body><u:{action}Response xmlns:u="{AV_TRANSPORT}:1">{arguments}</u:{action}Response></s:Body></s:Envelope>"#
+    )
+}
+
+fn description(name: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <specVersion><major>1</major><minor>0</minor></specVersion>
+  <device>
+    <deviceType>{MEDIA_RENDERER}</deviceType>
+    <friendlyName>{name}</friendlyName>
+    <manufacturer>Losange</manufacturer>
+    <modelName>Mock Renderer</modelName>
+    <UDN>uuid:losange-mock-renderer</UDN>
+    <serviceList>
+      <service>
+        <serviceType>{AV_TRANSPORT}:1</serviceType>
+        <serviceId>urn:upnp-org:serviceId:AVTransport</serviceId>
+        <controlURL>/control</controlURL>
+        <eventSubURL>/events</eventSubURL>
+        <SCPDURL>/avtransport.xml</SCPDURL>
+      </service>
+    </serviceList>
+  </device>
+</root>"#
+    )
+}
@@ -0,0 +1,118 @@
+//! Headless watch party peer, useful to simulate the other side of a session.
+//!
+//! Usage:
+//!   cargo run --example watch_party_peer -- host
+//!   cargo run --example watch_party_peer -- join <CODE>
+//!
+//! Then type `play`, `pause`, `seek <seconds>`, `rate <rate>` or `quit` on stdin.
+
+use std::{io::BufRead, time::Instant};
+
+use stremio_core_losange::watch_party::{PartyEvent, PartyMessage, WatchParty};
+use tokio::sync::mpsc;
+
+struct Playback {
+    paused: bool,
+    time: f64,
+    rate: f64,
+    updated: Instant,
+}
+
+impl Playback {
+    fn current_time(&self) -> f64 {
+        match self.paused {
+            true => self.time,
+            false => self.time + self.updated.elapsed().as_secs_f64() * 1000.0 * self.rate,
+        }
+    }
+
+    fn message(&self) -> PartyMessage {
+        PartyMessage::Playback {
+            paused: self.paused,
+            time: self.current_time(),
+            rate: self.rate,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let (party, mut events) = match args.as_slice() {
+        [command] if command == "host" => WatchParty::host().await?,
+        [command, code] if command == "join" => WatchParty::join(code).await?,
+        _ => {
+            eprintln!("Usage: watch_party_peer host | join <CODE>");
+            std::process::exit(1);
+        }
+    };
+
+    println!("Watch party {}", party.code);
+
+    let (commands, mut commands_receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+            if commands.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut playback = Playback {
+        paused: true,
+        time: 0.0,
+        rate: 1.0,
+        updated: Instant::now(),
+    };
+
+    loop {
+        tokio::select! {
+            Some(event) = events.recv() => match event {
+                PartyEvent::Message(PartyMessage::Playback { paused, time, rate }) => {
+                    playback = Playback { paused, time, rate, updated: Instant::now() };
+                    println!("< playback paused={paused} time={:.3}s rate={rate}", time / 1000.0);
+                }
+                PartyEvent::Message(PartyMessage::Stream { stream }) => {
+                    println!("< stream {}", stream.name);
+                }
+                PartyEvent::Message(message) => println!("< {message:?}"),
+                PartyEvent::PeersChanged(peers) => {
+                    println!("< peers {peers}");
+                    party.send(playback.message());
+                }
+                PartyEvent::Closed => {
+                    println!("< closed");
+                    break;
+                }
+            },
+            Some(line) = commands_receiver.recv() => {
+                let mut words = line.split_whitespace();
+                let time = playback.current_time();
+
+                match (words.next(), words.next().and_then(|value| value.parse::<f64>().ok())) {
+                    (Some("play"), _) => playback.paused = false,
+                    (Some("pause"), _) => playback.paused = true,
+                    (Some("seek"), Some(seconds)) => playback.time = seconds * 1000.0,
+                    (Some("rate"), Some(rate)) => playback.rate = rate,
+                    (Some("quit"), _) => break,
+                    _ => {
+                        eprintln!("Unknown command: {line}");
+                        continue;
+                    }
+                }
+
+                if !line.starts_with("seek") {
+                    playback.time = time;
+                }
+
+                playback.updated = Instant::now();
+                party.send(playback.message());
+            }
+            else => break,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::io;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use stremio_core::types::addon::{Descriptor, DescriptorFlags, Manifest};
+use url::Url;
+
+const MANIFEST_PATH: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFieldType {
+    Text,
+    Number,
+    Password,
+    Checkbox,
+    Select,
+}
+
+/// A setting declared in the `config` field of an addon manifest
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigField {
+    pub key: String,
+    pub r#type: ConfigFieldType,
+    pub title: Option<String>,
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl ConfigField {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.key)
+    }
+}
+
+/// Fetches the configuration fields an addon declares in its manifest
+pub async fn fetch_fields(transport_url: &Url) -> io::Result<Vec<ConfigField>> {
+    let mut manifest = fetch_manifest(transport_url).await?;
+
+    let fields = match manifest.get_mut("config").map(Value::take) {
+        Some(config) => serde_json::from_value(config).map_err(io::Error::other)?,
+        None => vec![],
+    };
+
+    Ok(fields)
+}
+
+/// Fetches the descriptor of an addon as served at the given transport url
+pub async fn fetch_descriptor(transport_url: &Url) -> io::Result<Descriptor> {
+    let manifest = fetch_manifest(transport_url).await?;
+    let manifest = serde_json::from_value::<Manifest>(manifest).map_err(io::Error::other)?;
+
+    Ok(Descriptor {
+        manifest,
+        transport_url: transport_url.to_owned(),
+        flags: DescriptorFlags::default(),
+    })
+}
+
+/// The configuration encoded in a transport url, as done by addons built with the addon SDK
+pub fn current_values(transport_url: &Url) -> Map<String, Value> {
+    base_segments(transport_url)
+        .and_then(|(_, config)| config)
+        .unwrap_or_default()
+}
+
+/// The transport url of the addon configured with the given values
+pub fn configured_url(transport_url: &Url, values: &Map<String, Value>) -> Option<Url> {
+    let (segments, _) = base_segments(transport_url)?;
+    let config = serde_json::to_string(values).ok()?;
+
+    let mut url = transport_url.to_owned();
+    url.path_segments_mut()
+        .ok()?
+        .clear()
+        .extend(segments.iter().filter(|segment| !segment.is_empty()))
+        .push(&config)
+        .push(MANIFEST_PATH);
+
+    Some(url)
+}
+
+/// Path segments before the configuration, and the configuration when there is one
+fn base_segments(transport_url: &Url) -> Option<(Vec<String>, Option<Map<String, Value>>)> {
+    let mut segments = transport_url
+        .path_segments()?
+        .map(|segment| {
+            percent_encoding::percent_decode_str(segment)
+                .decode_utf8_lossy()
+                .to_string()
+        })
+        .collect_vec();
+
+    if segments.pop()? != MANIFEST_PATH {
+        return None;
+    }
+
+    let config = segments
+        .last()
+        .and_then(|segment| serde_json::from_str::<Map<String, Value>>(segment).ok());
+
+    if config.is_some() {
+        segments.pop();
+    }
+
+    Some((segments, config))
+}
+
+async fn fetch_manifest(transport_url: &Url) -> io::Result<Value> {
+    reqwest::get(transport_url.to_owned())
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(io::Error::other)?
+        .json::<Value>()
+        .await
+        .map_err(io::Error::other)
+}
@@ -0,0 +1,341 @@
+use std::{io, net::Ipv4Addr, time::Duration};
+
+use lazy_static::lazy_static;
+use reqwest::{header, Client};
+use tokio::{
+    net::UdpSocket,
+    time::{timeout_at, Instant},
+};
+use tracing::debug;
+use url::Url;
+use xml::{
+    escape::escape_str_pcdata,
+    reader::{EventReader, XmlEvent},
+};
+
+pub const SSDP_ADDRESS: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+pub const SSDP_PORT: u16 = 1900;
+pub const MEDIA_RENDERER: &str = "urn:schemas-upnp-org:device:MediaRenderer:1";
+pub const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const SEARCH_ATTEMPTS: u32 = 2;
+
+lazy_static! {
+    static ref CLIENT: Client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransportState {
+    Playing,
+    Paused,
+    Stopped,
+    Transitioning,
+    #[default]
+    Unknown,
+}
+
+impl From<&str> for TransportState {
+    fn from(value: &str) -> Self {
+        match value {
+            "PLAYING" => Self::Playing,
+            "PAUSED_PLAYBACK" => Self::Paused,
+            "STOPPED" | "NO_MEDIA_PRESENT" => Self::Stopped,
+            "TRANSITIONING" => Self::Transitioning,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaybackState {
+    pub state: TransportState,
+    pub time: f64,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renderer {
+    pub name: String,
+    pub location: Url,
+    pub control_url: Url,
+    pub service_type: String,
+}
+
+impl Renderer {
+    pub async fn from_location(location: Url) -> io::Result<Option<Self>> {
+        let description = CLIENT
+            .get(location.to_owned())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(io::Error::other)?
+            .text()
+            .await
+            .map_err(io::Error::other)?;
+
+        let mut name = None;
+        let mut base = None;
+        let mut service_type = None;
+        let mut control_url = None;
+        let mut av_transport = None;
+        let mut text = String::new();
+
+        for event in EventReader::from_str(&description) {
+            match event.map_err(io::Error::other)? {
+                XmlEvent::StartElement { .. } => text.clear(),
+                XmlEvent::Characters(characters) => text.push_str(&characters),
+                XmlEvent::EndElement { name: element } => match element.local_name.as_str() {
+                    // The first one belongs to the root device, embedded devices come after
+                    "friendlyName" if name.is_none() => name = Some(text.trim().to_owned()),
+                    "URLBase" => base = Url::parse(text.trim()).ok(),
+                    "serviceType" => service_type = Some(text.trim().to_owned()),
+                    "controlURL" => control_url = Some(text.trim().to_owned()),
+                    "service" => {
+                        let service = service_type.take().zip(control_url.take());
+
+                        if let Some((service_type, control_url)) = service {
+                            if av_transport.is_none() && service_type.starts_with(AV_TRANSPORT) {
+                                av_transport = Some((service_type, control_url));
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        let Some((service_type, control_url)) = av_transport else {
+            return Ok(None);
+        };
+
+        let control_url = base
+            .as_ref()
+            .unwrap_or(&location)
+            .join(&control_url)
+            .map_err(io::Error::other)?;
+
+        let name = name
+            .filter(|name| !name.is_empty())
+            .or(location.host_str().map(ToOwned::to_owned))
+            .unwrap_or_default();
+
+        Ok(Some(Self {
+            name,
+            location,
+            control_url,
+            service_type,
+        }))
+    }
+
+    pub async fn load(&self, url: &Url, title: &str) -> io::Result<()> {
+        let metadata = format!(
+            concat!(
+                r#"<DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" "#,
+                r#"xmlns:dc="http://purl.org/dc/elements/1.1/" "#,
+                r#"xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/">"#,
+                r#"<item id="0" parentID="-1" restricted="1">"#,
+                r#"<dc:title>{}</dc:title>"#,
+                r#"<upnp:class>object.item.videoItem</upnp:class>"#,
+                r#"<res protocolInfo="http-get:*:video/*:*">{}</res>"#,
+                r#"</item></DIDL-Lite>"#,
+            ),
+            escape_str_pcdata(title),
+            escape_str_pcdata(url.as_str()),
+        );
+
+        self.call(
+            "SetAVTransportURI",
+            &[
+                ("CurrentURI", url.as_str()),
+                ("CurrentURIMetaData", &metadata),
+            ],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn play(&self) -> io::Result<()> {
+        self.call("Play", &[("Speed", "1")]).await.map(|_| ())
+    }
+
+    pub async fn pause(&self) -> io::Result<()> {
+        self.call("Pause", &[]).await.map(|_| ())
+    }
+
+    pub async fn stop(&self) -> io::Result<()> {
+        self.call("Stop", &[]).await.map(|_| ())
+    }
+
+    pub async fn seek(&self, time: f64) -> io::Result<()> {
+        self.call(
+            "Seek",
+            &[("Unit", "REL_TIME"), ("Target", &format_time(time))],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn playback_state(&self) -> io::Result<PlaybackState> {
+        let transport_info = self.call("GetTransportInfo", &[]).await?;
+        let position_info = self.call("GetPositionInfo", &[]).await?;
+
+        let state = element_text(&transport_info, "CurrentTransportState")
+            .map_or(TransportState::Unknown, |state| {
+                TransportState::from(state.as_str())
+            });
+
+        let time = element_text(&position_info, "RelTime").and_then(|time| parse_time(&time));
+        let duration =
+            element_text(&position_info, "TrackDuration").and_then(|time| parse_time(&time));
+
+        Ok(PlaybackState {
+            state,
+            time: time.unwrap_or_default(),
+            duration: duration.unwrap_or_default(),
+        })
+    }
+
+    async fn call(&self, action: &str, arguments: &[(&str, &str)]) -> io::Result<String> {
+        let service_type = &self.service_type;
+
+        let arguments = arguments
+            .iter()
+            .map(|(name, value)| format!("<{name}>{}</{name}>", escape_str_pcdata(value)))
+            .collect::<String>();
+
+        let body = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8"?>"#,
+                r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" "#,
+                r#"s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">"#,
+                r#"<s:Body><u:{action} xmlns:u="{service_type}">"#,
+                r#"<InstanceID>0</InstanceID>{arguments}"#,
+                r#"</u:{action}></s:Body></s:Envelope>"#,
+            ),
+            action = action,
+            service_type = service_type,
+            arguments = arguments,
+        );
+
+        let response = CLIENT
+            .post(self.control_url.to_owned())
+            .header(header::CONTENT_TYPE, r#"text/xml; charset="utf-8""#)
+            .header("SOAPAction", format!(r#""{service_type}#{action}""#))
+            .body(body)
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(io::Error::other)?;
+
+        if !status.is_success() {
+            let error = element_text(&text, "errorDescription").unwrap_or(status.to_string());
+            return Err(io::Error::other(format!("{action} failed: {error}")));
+        }
+
+        Ok(text)
+    }
+}
+
+pub async fn discover(duration: Duration) -> io::Result<Vec<Renderer>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {SSDP_ADDRESS}:{SSDP_PORT}\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: {MEDIA_RENDERER}\r\n\r\n",
+        duration.as_secs().max(1),
+    );
+
+    // SSDP runs over UDP, a second request makes up for lost packets
+    for _ in 0..SEARCH_ATTEMPTS {
+        socket
+            .send_to(request.as_bytes(), (SSDP_ADDRESS, SSDP_PORT))
+            .await?;
+    }
+
+    let deadline = Instant::now() + duration;
+    let mut locations: Vec<Url> = vec![];
+    let mut buffer = [0; 2048];
+
+    while let Ok(Ok((length, _))) = timeout_at(deadline, socket.recv_from(&mut buffer)).await {
+        let response = String::from_utf8_lossy(&buffer[..length]);
+
+        let location = header_value(&response, "location").and_then(|value| Url::parse(value).ok());
+
+        if let Some(location) = location {
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+
+    let mut renderers = vec![];
+
+    for location in locations {
+        match Renderer::from_location(location.to_owned()).await {
+            Ok(Some(renderer)) => renderers.push(renderer),
+            Ok(None) => debug!("{location} has no AVTransport service"),
+            Err(e) => debug!("Failed to describe renderer at {location}: {e}"),
+        }
+    }
+
+    Ok(renderers)
+}
+
+pub fn header_value<'a>(message: &'a str, name: &str) -> Option<&'a str> {
+    message.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+pub fn element_text(document: &str, name: &str) -> Option<String> {
+    let mut text: Option<String> = None;
+
+    for event in EventReader::from_str(document) {
+        match event.ok()? {
+            XmlEvent::StartElement { name: element, .. } if element.local_name == name => {
+                text = Some(String::new());
+            }
+            XmlEvent::Characters(characters) => {
+                if let Some(text) = text.as_mut() {
+                    text.push_str(&characters);
+                }
+            }
+            XmlEvent::EndElement { name: element } if element.local_name == name => {
+                return text;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+pub fn format_time(ms: f64) -> String {
+    let total_seconds = (ms / 1000.0).max(0.0).round() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    format!("{hours}:{minutes:02}:{seconds:02}")
+}
+
+pub fn parse_time(value: &str) -> Option<f64> {
+    let mut parts = value.trim().split(':').rev();
+
+    let seconds = parts.next()?.parse::<f64>().ok()?;
+    let minutes = parts
+        .next()
+        .map_or(Some(0.0), |part| part.parse::<f64>().ok())?;
+    let hours = parts
+        .next()
+        .map_or(Some(0.0), |part| part.parse::<f64>().ok())?;
+
+    Some((hours * 3600.0 + minutes * 60.0 + seconds) * 1000.0)
+}
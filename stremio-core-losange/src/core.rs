@@ -29,6 +29,8 @@ use crate::{
     emitter::Emitter,
     env::LosangeEnv,
     model::{LosangeModel, LosangeModelField},
+    models::{self, player::TRACKS_STORAGE_KEY},
+    types::tracks::TracksBucket,
 };
 
 lazy_static! {
@@ -59,6 +61,7 @@ pub async fn initialize(data_location: &Path) {
                 LosangeEnv::get_storage::<NotificationsBucket>(NOTIFICATIONS_STORAGE_KEY),
                 LosangeEnv::get_storage::<SearchHistoryBucket>(SEARCH_HISTORY_STORAGE_KEY),
                 LosangeEnv::get_storage::<DismissedEventsBucket>(DISMISSED_EVENTS_STORAGE_KEY),
+                LosangeEnv::get_storage::<TracksBucket>(TRACKS_STORAGE_KEY),
             );
 
             match storage_result {
@@ -71,6 +74,7 @@ pub async fn initialize(data_location: &Path) {
                     notifications_bucket,
                     search_history_bucket,
                     dismissed_events_bucket,
+                    tracks_bucket,
                 )) => {
                     let profile = profile.unwrap_or_default();
                     let mut library = LibraryBucket::new(profile.uid(), vec![]);
@@ -98,6 +102,8 @@ pub async fn initialize(data_location: &Path) {
                     let dismissed_events_bucket = dismissed_events_bucket
                         .unwrap_or(DismissedEventsBucket::new(profile.uid()));
 
+                    models::player::init_tracks(tracks_bucket.unwrap_or_default());
+
                     let (model, effects) = LosangeModel::new(
                         profile,
                         library,
@@ -162,3 +168,25 @@ pub fn dispatch(action: Action, field: Option<LosangeModelField>) {
 
     runtime.dispatch(RuntimeAction { action, field });
 }
+
+/// Reads the profile from the model, derived states can lag behind dispatched actions
+pub fn profile() -> Option<Profile> {
+    let runtime = RUNTIME.read().expect("runtime read failed");
+    let Some(Loadable::Ready(runtime)) = runtime.as_ref() else {
+        return None;
+    };
+
+    let model = runtime.model().expect("model read failed");
+    Some(model.ctx.profile.to_owned())
+}
+
+/// Derives the state of the given fields again, for changes that live outside of the core
+pub fn refresh(fields: Vec<LosangeModelField>) {
+    let runtime = RUNTIME.read().expect("runtime read failed");
+    let Some(Loadable::Ready(runtime)) = runtime.as_ref() else {
+        return;
+    };
+
+    let model = runtime.model().expect("model read failed");
+    model.update(fields);
+}
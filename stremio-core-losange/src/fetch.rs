@@ -8,8 +8,14 @@ use http_cache_reqwest::{CACacheManager, Cache, CacheMode, HttpCache, HttpCacheO
 use reqwest::{Body, Client};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use serde::{Deserialize, Serialize};
-use serde_json::Deserializer;
+use serde_json::{Deserializer, Value};
 use stremio_core::runtime::{EnvError, EnvFutureExt, TryEnvFuture};
+use url::Url;
+
+use crate::in_process_addon;
+
+const ADDON_COLLECTION_GET: &str = "addonCollectionGet";
+const ADDON_COLLECTION_SET: &str = "addonCollectionSet";
 
 pub struct Fetch {
     client: Arc<ClientWithMiddleware>,
@@ -46,8 +52,25 @@ impl Fetch {
         OUT: for<'de> Deserialize<'de> + Send + 'static,
     {
         let (parts, body) = request.into_parts();
-        let body = match serde_json::to_string(&body) {
-            Ok(body) if body != "null" && parts.method != Method::GET => Body::from(body),
+
+        // In-process addons answer directly instead of going through the network
+        if let Ok(url) = Url::parse(&parts.uri.to_string()) {
+            if in_process_addon::is_in_process(&url) {
+                return in_process_addon::fetch(&url);
+            }
+        }
+
+        let api_method = parts.uri.path().rsplit('/').next().unwrap_or_default();
+        let pulls_addons = api_method == ADDON_COLLECTION_GET;
+
+        let body = match serde_json::to_value(&body) {
+            Ok(mut body) if !body.is_null() && parts.method != Method::GET => {
+                if api_method == ADDON_COLLECTION_SET {
+                    in_process_addon::strip_synced(&mut body);
+                }
+
+                Body::from(body.to_string())
+            }
             Ok(_) => Body::from(vec![]),
             Err(error) => return future::err(EnvError::Serde(error.to_string())).boxed_env(),
         };
@@ -78,6 +101,15 @@ impl Fetch {
                 .await
                 .map_err(|error| EnvError::Fetch(error.to_string()))?;
 
+            if pulls_addons {
+                let mut response = serde_json::from_slice::<Value>(&body)
+                    .map_err(|error| EnvError::Serde(error.to_string()))?;
+                in_process_addon::merge_pulled(&mut response);
+
+                return serde_json::from_value(response)
+                    .map_err(|error| EnvError::Serde(error.to_string()));
+            }
+
             let mut deserializer = Deserializer::from_slice(&body);
             cfg_if::cfg_if! {
                 if #[cfg(debug_assertions)] {
@@ -0,0 +1,318 @@
+use std::sync::{Arc, RwLock};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stremio_core::{
+    runtime::{EnvError, EnvFutureExt, TryEnvFuture},
+    types::{
+        addon::{Descriptor, DescriptorFlags, ExtraValue, Manifest},
+        resource::{MetaItem, MetaItemPreview, Stream, Subtitles},
+    },
+};
+use url::{form_urlencoded, Url};
+
+use crate::models::ctx::CTX_STATE;
+
+/// Transport urls of in-process addons use this scheme so they never reach the network
+pub const ADDON_SCHEME: &str = "losange-addon";
+
+const ADDON_HOST: &str = "addons";
+const MANIFEST_PATH: &str = "manifest.json";
+
+lazy_static! {
+    static ref ADDONS: RwLock<Vec<(Url, Arc<dyn InProcessAddon>)>> = Default::default();
+}
+
+/// An addon running inside the app instead of behind an HTTP server.
+///
+/// Handlers return `None` for requests they do not serve, which the core sees as a missing
+/// resource. They are called from the core runtime, so anything slow should be prepared ahead of
+/// time.
+pub trait InProcessAddon: Send + Sync + 'static {
+    fn manifest(&self) -> Manifest;
+
+    fn catalog(
+        &self,
+        _type: &str,
+        _id: &str,
+        _extra: &[ExtraValue],
+    ) -> Option<Vec<MetaItemPreview>> {
+        None
+    }
+
+    fn meta(&self, _type: &str, _id: &str) -> Option<MetaItem> {
+        None
+    }
+
+    fn streams(&self, _type: &str, _id: &str) -> Option<Vec<Stream>> {
+        None
+    }
+
+    fn subtitles(&self, _type: &str, _id: &str, _extra: &[ExtraValue]) -> Option<Vec<Subtitles>> {
+        None
+    }
+}
+
+/// Makes the addon reachable from the core and returns its descriptor, ready to be installed
+pub fn register<A: InProcessAddon>(addon: A) -> Descriptor {
+    let descriptor = descriptor(&addon);
+
+    let mut addons = ADDONS.write().expect("ADDONS write failed");
+    addons.retain(|(transport_url, _)| *transport_url != descriptor.transport_url);
+    addons.push((descriptor.transport_url.to_owned(), Arc::new(addon)));
+
+    descriptor
+}
+
+pub fn descriptor<A: InProcessAddon + ?Sized>(addon: &A) -> Descriptor {
+    let manifest = addon.manifest();
+
+    Descriptor {
+        transport_url: transport_url(&manifest.id),
+        manifest,
+        flags: DescriptorFlags::default(),
+    }
+}
+
+/// The id goes in the path, percent-encoded, so that any manifest id makes a valid url
+pub fn transport_url(id: &str) -> Url {
+    let id = utf8_percent_encode(id, NON_ALPHANUMERIC);
+
+    Url::parse(&format!(
+        "{ADDON_SCHEME}://{ADDON_HOST}/{id}/{MANIFEST_PATH}"
+    ))
+    .expect("Percent-encoded addon url is always valid")
+}
+
+pub fn is_in_process(url: &Url) -> bool {
+    url.scheme() == ADDON_SCHEME
+}
+
+/// In-process addons only exist on this device, they are kept out of the addons pushed to the API
+pub fn strip_synced(request: &mut Value) {
+    if let Some(addons) = request.get_mut("addons").and_then(Value::as_array_mut) {
+        addons.retain(|addon| {
+            !addon
+                .get("transportUrl")
+                .and_then(Value::as_str)
+                .and_then(|url| Url::parse(url).ok())
+                .is_some_and(|url| is_in_process(&url))
+        });
+    }
+}
+
+/// Keeps the installed in-process addons when the addons are pulled from the API
+pub fn merge_pulled(response: &mut Value) {
+    let Some(addons) = response
+        .pointer_mut("/result/addons")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    let installed = CTX_STATE
+        .read_inner()
+        .addons
+        .iter()
+        .filter(|addon| is_in_process(&addon.transport_url))
+        .filter_map(|addon| serde_json::to_value(addon).ok())
+        .collect_vec();
+
+    addons.extend(installed);
+}
+
+/// Answers a request made to an in-process addon the way an HTTP addon would
+pub fn fetch<OUT>(url: &Url) -> TryEnvFuture<OUT>
+where
+    OUT: for<'de> Deserialize<'de> + Send + 'static,
+{
+    let addon =
+        ADDONS
+            .read()
+            .expect("ADDONS read failed")
+            .iter()
+            .find_map(|(transport_url, addon)| {
+                let base = transport_url.path().strip_suffix(MANIFEST_PATH)?;
+                let path = url.path().strip_prefix(base)?;
+                Some((path.to_owned(), addon.to_owned()))
+            });
+
+    let url = url.to_owned();
+
+    async move {
+        let (path, addon) = addon.ok_or_else(|| not_found(&url))?;
+        let response = respond(addon.as_ref(), &path).ok_or_else(|| not_found(&url))?;
+
+        serde_json::from_value(response).map_err(|error| EnvError::Serde(error.to_string()))
+    }
+    .boxed_env()
+}
+
+/// Paths are relative to the transport url of the addon
+fn respond(addon: &dyn InProcessAddon, path: &str) -> Option<Value> {
+    if path == MANIFEST_PATH {
+        return serde_json::to_value(addon.manifest()).ok();
+    }
+
+    let segments = path.strip_suffix(".json")?.split('/').collect_vec();
+
+    let [resource, r#type, id, extra @ ..] = segments.as_slice() else {
+        return None;
+    };
+
+    let decode = |segment: &str| percent_decode_str(segment).decode_utf8_lossy().to_string();
+    let (resource, r#type, id) = (decode(resource), decode(r#type), decode(id));
+
+    // Extra stays encoded until split, values can contain an encoded `&`
+    let extra = extra
+        .first()
+        .map(|extra| parse_extra(extra))
+        .unwrap_or_default();
+
+    let response = match resource.as_str() {
+        "catalog" => json!({ "metas": addon.catalog(&r#type, &id, &extra)? }),
+        "meta" => json!({ "meta": addon.meta(&r#type, &id)? }),
+        "stream" => json!({ "streams": addon.streams(&r#type, &id)? }),
+        "subtitles" => json!({ "subtitles": addon.subtitles(&r#type, &id, &extra)? }),
+        _ => return None,
+    };
+
+    Some(response)
+}
+
+fn parse_extra(extra: &str) -> Vec<ExtraValue> {
+    form_urlencoded::parse(extra.as_bytes())
+        .map(|(name, value)| ExtraValue {
+            name: name.into_owned(),
+            value: value.into_owned(),
+        })
+        .collect()
+}
+
+fn not_found(url: &Url) -> EnvError {
+    EnvError::Fetch(format!("Unexpected HTTP status code 404 for {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::{json, Value};
+    use stremio_core::types::{
+        addon::{ExtraValue, Manifest},
+        resource::MetaItemPreview,
+    };
+    use url::Url;
+
+    use super::{
+        descriptor, fetch, merge_pulled, register, strip_synced, transport_url, InProcessAddon,
+    };
+    use crate::models::ctx::CTX_STATE;
+
+    struct TestAddon(&'static str);
+
+    impl InProcessAddon for TestAddon {
+        fn manifest(&self) -> Manifest {
+            serde_json::from_value(json!({
+                "id": self.0,
+                "version": "1.0.0",
+                "name": "Test",
+                "types": ["movie"],
+                "resources": ["catalog"],
+                "catalogs": [],
+            }))
+            .unwrap()
+        }
+
+        fn catalog(
+            &self,
+            r#type: &str,
+            id: &str,
+            extra: &[ExtraValue],
+        ) -> Option<Vec<MetaItemPreview>> {
+            let name = extra
+                .iter()
+                .map(|extra| format!("{}={}", extra.name, extra.value))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            serde_json::from_value(json!([{ "id": id, "type": r#type, "name": name }])).ok()
+        }
+    }
+
+    fn resource_url(id: &str, path: &str) -> Url {
+        transport_url(id).join(path).unwrap()
+    }
+
+    #[test]
+    fn encoded_id() {
+        let url = transport_url("test addon/ä");
+
+        assert_eq!(
+            url.as_str(),
+            "losange-addon://addons/test%20addon%2F%C3%A4/manifest.json"
+        );
+        assert!(super::is_in_process(&url));
+    }
+
+    #[test]
+    fn manifest() {
+        let descriptor = register(TestAddon("test.manifest"));
+        let manifest = block_on(fetch::<Value>(&descriptor.transport_url)).unwrap();
+
+        assert_eq!(manifest["id"], "test.manifest");
+    }
+
+    #[test]
+    fn catalog_with_extra() {
+        register(TestAddon("test catalog/1"));
+        let url = resource_url(
+            "test catalog/1",
+            "catalog/movie/top%20rated/genre=Action&search=a%26b+c.json",
+        );
+
+        let response = block_on(fetch::<Value>(&url)).unwrap();
+
+        assert_eq!(response["metas"][0]["id"], "top rated");
+        assert_eq!(response["metas"][0]["type"], "movie");
+        assert_eq!(response["metas"][0]["name"], "genre=Action,search=a&b c");
+    }
+
+    #[test]
+    fn missing_resource() {
+        register(TestAddon("test.missing"));
+
+        let url = resource_url("test.missing", "meta/movie/tt1.json");
+        assert!(block_on(fetch::<Value>(&url)).is_err());
+
+        let url = resource_url("test.missing", "unknown.json");
+        assert!(block_on(fetch::<Value>(&url)).is_err());
+
+        let url = resource_url("test.unregistered", "catalog/movie/top.json");
+        assert!(block_on(fetch::<Value>(&url)).is_err());
+    }
+
+    #[test]
+    fn synced_addons() {
+        let in_process = descriptor(&TestAddon("test.synced"));
+        let remote = json!({ "transportUrl": "https://example.com/manifest.json" });
+
+        let mut request = json!({
+            "type": "AddonCollectionSet",
+            "addons": [serde_json::to_value(&in_process).unwrap(), remote],
+        });
+        strip_synced(&mut request);
+        assert_eq!(request["addons"], json!([remote]));
+
+        CTX_STATE.write().addons = vec![in_process.to_owned()];
+
+        let mut response = json!({ "result": { "addons": request["addons"] } });
+        merge_pulled(&mut response);
+        assert_eq!(
+            response["result"]["addons"],
+            json!([remote, serde_json::to_value(&in_process).unwrap()]),
+        );
+    }
+}
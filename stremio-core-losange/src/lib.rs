@@ -1,9 +1,16 @@
+pub mod addon_config;
+pub mod cast;
 pub mod core;
 pub mod emitter;
 pub mod env;
 pub mod fetch;
+pub mod in_process_addon;
+pub mod local_addon;
+pub mod local_streams;
 pub mod model;
 pub mod models;
 pub mod storage;
+pub mod torrent;
 pub mod types;
+pub mod watch_party;
 pub use stremio_core;
@@ -0,0 +1,604 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use futures::StreamExt;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use stremio_core::types::{
+    addon::{ExtraValue, Manifest},
+    resource::{MetaItem, MetaItemPreview, Stream},
+};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::{in_process_addon::InProcessAddon, torrent::VIDEO_EXTENSIONS};
+
+const ID_PREFIX: &str = "local:";
+const CATALOG_ID: &str = "local";
+const CATALOG_PAGE_SIZE: usize = 100;
+const CINEMETA_URL: &str = "https://v3-cinemeta.strem.io/";
+const POSTER_URL: &str = "https://images.metahub.space/poster/medium/";
+const MATCHES_FILE: &str = "local-matches.json";
+const MATCH_CONCURRENCY: usize = 4;
+const RELEASE_TAGS: &[&str] = &[
+    "480p", "576p", "720p", "1080p", "2160p", "4k", "uhd", "bluray", "bdrip", "brrip", "webrip",
+    "web-dl", "webdl", "web", "hdtv", "dvdrip", "remux", "x264", "x265", "h264", "h265", "hevc",
+    "hdr", "proper", "repack", "extended", "multi",
+];
+
+lazy_static! {
+    static ref FILES: RwLock<Vec<LocalFile>> = Default::default();
+}
+
+/// What a video file name tells about its content
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedName {
+    pub title: String,
+    pub year: Option<u32>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+}
+
+impl ParsedName {
+    pub fn parse(file_name: &str) -> Self {
+        let stem = file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem);
+
+        let words = stem
+            .split(|c: char| c.is_whitespace() || matches!(c, '.' | '_'))
+            .map(|word| word.trim_matches(|c| matches!(c, '(' | ')' | '[' | ']' | '-')))
+            .filter(|word| !word.is_empty())
+            .collect_vec();
+
+        let mut title = vec![];
+        let mut year = None;
+        let mut episode = None;
+        let mut title_ended = false;
+
+        for (index, word) in words.iter().enumerate() {
+            if let Some(marker) = Self::episode_marker(word) {
+                episode = Some(marker);
+                break;
+            }
+
+            // A leading number is part of the title, as in "1917" or "2001 A Space Odyssey"
+            if let Some(value) = Self::year(word).filter(|_| index > 0) {
+                year = year.or(Some(value));
+                title_ended = true;
+                continue;
+            }
+
+            // Episode markers can still follow, as in "Show 2019 1080p S01E02"
+            if RELEASE_TAGS.contains(&word.to_ascii_lowercase().as_str()) {
+                title_ended = true;
+            }
+
+            if !title_ended {
+                title.push(*word);
+            }
+        }
+
+        let title = match title.is_empty() {
+            true => stem.to_owned(),
+            false => title.join(" "),
+        };
+
+        Self {
+            title,
+            year,
+            season: episode.map(|(season, _)| season),
+            episode: episode.map(|(_, episode)| episode),
+        }
+    }
+
+    pub fn r#type(&self) -> &'static str {
+        match self.episode {
+            Some(_) => "series",
+            None => "movie",
+        }
+    }
+
+    fn key(&self) -> String {
+        let year = self.year.map(|year| year.to_string()).unwrap_or_default();
+        format!("{}:{}:{year}", self.r#type(), self.title.to_lowercase())
+    }
+
+    fn year(word: &str) -> Option<u32> {
+        (word.len() == 4)
+            .then(|| word.parse().ok())
+            .flatten()
+            .filter(|year| (1900..=2099).contains(year))
+    }
+
+    fn episode_marker(word: &str) -> Option<(u32, u32)> {
+        let word = word.to_ascii_lowercase();
+
+        let (season, episode) = match word.strip_prefix('s') {
+            Some(rest) => rest.split_once('e')?,
+            // Limited to 2 digits each so that resolutions like 1920x1080 are not episodes
+            None => word.split_once('x').filter(|(season, episode)| {
+                (1..=2).contains(&season.len()) && episode.len() == 2
+            })?,
+        };
+
+        Some((season.parse().ok()?, episode.parse().ok()?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalFile {
+    pub id: String,
+    pub path: PathBuf,
+    pub name: ParsedName,
+}
+
+#[derive(Deserialize)]
+struct CinemetaResponse {
+    #[serde(default)]
+    metas: Vec<CinemetaItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CinemetaItem {
+    id: String,
+    name: String,
+    release_info: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Matches(HashMap<String, Option<String>>);
+
+/// Indexes the video files of the given folders and matches them to Cinemeta ids.
+///
+/// Matches are cached in the data location so that known files keep their ids offline.
+pub async fn scan(data_location: &Path, folders: Vec<PathBuf>) -> io::Result<usize> {
+    let paths = tokio::task::spawn_blocking(move || {
+        folders
+            .iter()
+            .flat_map(|folder| video_files(folder))
+            .collect_vec()
+    })
+    .await
+    .map_err(io::Error::other)?;
+
+    let matches_path = data_location.join(MATCHES_FILE);
+    let mut matches = fs::read(&matches_path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<Matches>(&contents).ok())
+        .unwrap_or_default();
+
+    let names = paths
+        .iter()
+        .map(|path| ParsedName::parse(&path.file_name().unwrap_or_default().to_string_lossy()))
+        .collect_vec();
+
+    let client = reqwest::Client::new();
+
+    let unknown = names
+        .iter()
+        .unique_by(|name| name.key())
+        .filter(|name| !matches.0.contains_key(&name.key()))
+        .cloned()
+        .collect_vec();
+
+    let found = futures::stream::iter(unknown)
+        .map(|name| {
+            let client = client.to_owned();
+            async move {
+                let result = match_cinemeta(&client, &name).await;
+                (name.key(), result)
+            }
+        })
+        .buffer_unordered(MATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    for (key, result) in found {
+        match result {
+            Ok(id) => {
+                matches.0.insert(key, id);
+            }
+            // Tried again on the next scan
+            Err(e) => debug!("Failed to match {key} on Cinemeta: {e}"),
+        }
+    }
+
+    if let Err(e) = serde_json::to_vec(&matches)
+        .map_err(io::Error::other)
+        .and_then(|contents| fs::write(&matches_path, contents))
+    {
+        warn!("Failed to save local file matches: {e}");
+    }
+
+    let files = paths
+        .into_iter()
+        .zip(names)
+        .map(|(path, name)| LocalFile {
+            id: matches
+                .0
+                .get(&name.key())
+                .cloned()
+                .flatten()
+                .unwrap_or_else(|| local_id(&name)),
+            path,
+            name,
+        })
+        .collect_vec();
+
+    info!("Found {} local files", files.len());
+
+    let count = files.len();
+    *FILES.write().expect("FILES write failed") = files;
+
+    Ok(count)
+}
+
+/// Built-in addon serving the video files found by [`scan`]
+pub struct LocalAddon;
+
+impl InProcessAddon for LocalAddon {
+    fn manifest(&self) -> Manifest {
+        serde_json::from_value(manifest()).expect("Failed to build local addon manifest")
+    }
+
+    fn catalog(
+        &self,
+        r#type: &str,
+        id: &str,
+        extra: &[ExtraValue],
+    ) -> Option<Vec<MetaItemPreview>> {
+        if id != CATALOG_ID {
+            return None;
+        }
+
+        let skip = extra
+            .iter()
+            .find(|extra| extra.name == "skip")
+            .and_then(|extra| extra.value.parse().ok())
+            .unwrap_or(0);
+
+        let files = FILES.read().expect("FILES read failed");
+        serde_json::from_value(catalog(&files, r#type, skip)).ok()
+    }
+
+    fn meta(&self, r#type: &str, id: &str) -> Option<MetaItem> {
+        let files = FILES.read().expect("FILES read failed");
+        serde_json::from_value(meta(&files, r#type, id)?).ok()
+    }
+
+    fn streams(&self, _type: &str, id: &str) -> Option<Vec<Stream>> {
+        let files = FILES.read().expect("FILES read failed");
+        serde_json::from_value(streams(&files, id)).ok()
+    }
+}
+
+fn manifest() -> Value {
+    json!({
+        "id": "xyz.timtimtim.losange.local",
+        "version": "1.0.0",
+        "name": "Local",
+        "description": "Video files found in your local folders",
+        "types": ["movie", "series"],
+        "resources": [
+            "catalog",
+            { "name": "meta", "types": ["movie", "series"], "idPrefixes": [ID_PREFIX] },
+            { "name": "stream", "types": ["movie", "series"], "idPrefixes": ["tt", ID_PREFIX] },
+        ],
+        "catalogs": [
+            { "type": "movie", "id": CATALOG_ID, "name": "Local", "extra": [{ "name": "skip" }] },
+            { "type": "series", "id": CATALOG_ID, "name": "Local", "extra": [{ "name": "skip" }] },
+        ],
+        "behaviorHints": {},
+    })
+}
+
+fn catalog(files: &[LocalFile], r#type: &str, skip: usize) -> Value {
+    let metas = files
+        .iter()
+        .filter(|file| file.name.r#type() == r#type)
+        .unique_by(|file| file.id.to_owned())
+        .sorted_by_key(|file| file.name.title.to_lowercase())
+        .skip(skip)
+        .take(CATALOG_PAGE_SIZE)
+        .map(preview)
+        .collect_vec();
+
+    json!(metas)
+}
+
+fn meta(files: &[LocalFile], r#type: &str, id: &str) -> Option<Value> {
+    let episodes = files
+        .iter()
+        .filter(|file| file.id == id && file.name.r#type() == r#type)
+        .collect_vec();
+
+    let mut meta = preview(episodes.first()?);
+
+    let videos = episodes
+        .iter()
+        .filter_map(|file| Some((file, file.name.season?, file.name.episode?)))
+        .unique_by(|(_, season, episode)| (*season, *episode))
+        .sorted_by_key(|(_, season, episode)| (*season, *episode))
+        .map(|(file, season, episode)| {
+            json!({
+                "id": format!("{id}:{season}:{episode}"),
+                "title": file.path.file_name().map(|name| name.to_string_lossy()),
+                "released": null,
+                "season": season,
+                "episode": episode,
+            })
+        })
+        .collect_vec();
+
+    meta["videos"] = json!(videos);
+
+    Some(meta)
+}
+
+fn streams(files: &[LocalFile], id: &str) -> Value {
+    // Series videos are identified as "{meta id}:{season}:{episode}"
+    let episode = id.rsplitn(3, ':').collect_vec();
+    let episode = match episode.as_slice() {
+        [episode, season, meta_id] => episode
+            .parse::<u32>()
+            .ok()
+            .zip(season.parse::<u32>().ok())
+            .map(|(episode, season)| (*meta_id, season, episode)),
+        _ => None,
+    };
+
+    let streams = files
+        .iter()
+        .filter(|file| match episode {
+            Some((meta_id, season, episode)) => {
+                file.id == meta_id
+                    && file.name.season == Some(season)
+                    && file.name.episode == Some(episode)
+            }
+            None => file.id == id && file.name.episode.is_none(),
+        })
+        .filter_map(|file| {
+            let url = Url::from_file_path(&file.path).ok()?;
+            let file_name = file.path.file_name()?.to_string_lossy();
+
+            Some(json!({
+                "url": url.as_str(),
+                "name": "Local",
+                "description": file_name,
+                "behaviorHints": {
+                    "bingeGroup": format!("local-{}", file.id),
+                    "filename": file_name,
+                },
+            }))
+        })
+        .collect_vec();
+
+    json!(streams)
+}
+
+fn preview(file: &LocalFile) -> Value {
+    let poster = file
+        .id
+        .starts_with("tt")
+        .then(|| format!("{POSTER_URL}{}/img", file.id));
+
+    json!({
+        "id": file.id,
+        "type": file.name.r#type(),
+        "name": file.name.title,
+        "poster": poster,
+        "posterShape": "poster",
+        "releaseInfo": file.name.year.map(|year| year.to_string()),
+    })
+}
+
+async fn match_cinemeta(
+    client: &reqwest::Client,
+    name: &ParsedName,
+) -> reqwest::Result<Option<String>> {
+    let mut url = Url::parse(CINEMETA_URL).expect("Failed to parse Cinemeta url");
+    url.path_segments_mut()
+        .expect("Failed to build Cinemeta url")
+        .extend([
+            "catalog",
+            name.r#type(),
+            "top",
+            &format!("search={}.json", name.title),
+        ]);
+
+    let response = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<CinemetaResponse>()
+        .await?;
+
+    let title = name.title.to_lowercase();
+    let year = name.year.map(|year| year.to_string());
+
+    let candidates = response
+        .metas
+        .into_iter()
+        .filter(|item| item.id.starts_with("tt"))
+        .collect_vec();
+
+    let matching = candidates.iter().find(|item| match &year {
+        Some(year) => item
+            .release_info
+            .as_ref()
+            .is_some_and(|release_info| release_info.starts_with(year.as_str())),
+        None => item.name.to_lowercase() == title,
+    });
+
+    Ok(matching
+        .or(candidates.first().filter(|_| year.is_none()))
+        .map(|item| item.id.to_owned()))
+}
+
+fn local_id(name: &ParsedName) -> String {
+    let hash = Sha1::digest(name.key().as_bytes());
+    format!("{ID_PREFIX}{}", hex::encode(&hash[..8]))
+}
+
+fn video_files(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        warn!("Failed to read local folder {}", folder.display());
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .flat_map(|entry| {
+            let path = entry.path();
+
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => video_files(&path),
+                Ok(_) if is_video(&path) => vec![path],
+                _ => vec![],
+            }
+        })
+        .collect()
+}
+
+fn is_video(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        VIDEO_EXTENSIONS.contains(&extension.to_string_lossy().to_ascii_lowercase().as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+
+    use stremio_core::types::resource::StreamSource;
+    use url::Url;
+
+    use super::{scan, LocalAddon, Matches, ParsedName, MATCHES_FILE};
+    use crate::in_process_addon::InProcessAddon;
+
+    #[test]
+    fn leading_number_is_part_of_the_title() {
+        let parsed = ParsedName::parse("1917.mkv");
+        assert_eq!(parsed.title, "1917");
+        assert_eq!(parsed.year, None);
+        assert_eq!(parsed.r#type(), "movie");
+
+        let parsed = ParsedName::parse("2001 A Space Odyssey.mkv");
+        assert_eq!(parsed.title, "2001 A Space Odyssey");
+        assert_eq!(parsed.year, None);
+    }
+
+    #[test]
+    fn episode_marker_after_year_and_tags() {
+        let parsed = ParsedName::parse("Show 2019 1080p S01E02.mkv");
+        assert_eq!(parsed.title, "Show");
+        assert_eq!(parsed.year, Some(2019));
+        assert_eq!(parsed.season, Some(1));
+        assert_eq!(parsed.episode, Some(2));
+        assert_eq!(parsed.r#type(), "series");
+    }
+
+    #[test]
+    fn short_episode_marker() {
+        let parsed = ParsedName::parse("Show.1x02.mkv");
+        assert_eq!(parsed.title, "Show");
+        assert_eq!(parsed.season, Some(1));
+        assert_eq!(parsed.episode, Some(2));
+    }
+
+    #[test]
+    fn resolution_is_not_an_episode_marker() {
+        let parsed = ParsedName::parse("Movie.2010.1920x1080.mkv");
+        assert_eq!(parsed.title, "Movie");
+        assert_eq!(parsed.year, Some(2010));
+        assert_eq!(parsed.season, None);
+        assert_eq!(parsed.episode, None);
+        assert_eq!(parsed.r#type(), "movie");
+    }
+
+    #[tokio::test]
+    async fn scanned_files_are_served() {
+        let root = std::env::temp_dir().join(format!("losange-local-addon-{}", std::process::id()));
+        let folder = root.join("videos");
+        fs::create_dir_all(folder.join("Big Buck Bunny")).unwrap();
+
+        for name in [
+            "Sintel.2010.1080p.mkv",
+            "Big Buck Bunny/Big Buck Bunny S01E02.mp4",
+            "Big Buck Bunny/Big Buck Bunny S01E01.mp4",
+            "notes.txt",
+            ".hidden.mkv",
+        ] {
+            fs::write(folder.join(name), []).unwrap();
+        }
+
+        // Every name is already matched, so that nothing is looked up on Cinemeta
+        let matches = Matches(HashMap::from([
+            ("movie:sintel:2010".to_owned(), Some("tt1727587".to_owned())),
+            ("series:big buck bunny:".to_owned(), None),
+        ]));
+        fs::write(
+            root.join(MATCHES_FILE),
+            serde_json::to_vec(&matches).unwrap(),
+        )
+        .unwrap();
+
+        let count = scan(&root, vec![folder.to_owned()]).await.unwrap();
+        assert_eq!(count, 3);
+
+        let movies = LocalAddon.catalog("movie", "local", &[]).unwrap();
+        assert_eq!(movies.len(), 1);
+        assert_eq!(movies[0].id, "tt1727587");
+        assert_eq!(movies[0].name, "Sintel");
+
+        let series = LocalAddon.catalog("series", "local", &[]).unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].name, "Big Buck Bunny");
+        let series_id = series[0].id.to_owned();
+        assert!(series_id.starts_with("local:"));
+
+        let meta = LocalAddon.meta("series", &series_id).unwrap();
+        let videos = meta
+            .videos
+            .iter()
+            .map(|video| video.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            videos,
+            [format!("{series_id}:1:1"), format!("{series_id}:1:2")]
+        );
+        assert!(LocalAddon.meta("movie", &series_id).is_none());
+
+        let episode =
+            Url::from_file_path(folder.join("Big Buck Bunny/Big Buck Bunny S01E02.mp4")).unwrap();
+        let streams = LocalAddon
+            .streams("series", &format!("{series_id}:1:2"))
+            .unwrap();
+        assert!(matches!(
+            &streams[..],
+            [stream] if stream.source == StreamSource::Url { url: episode.to_owned() }
+        ));
+
+        let movie = Url::from_file_path(folder.join("Sintel.2010.1080p.mkv")).unwrap();
+        let streams = LocalAddon.streams("movie", "tt1727587").unwrap();
+        assert!(matches!(
+            &streams[..],
+            [stream] if stream.source == StreamSource::Url { url: movie.to_owned() }
+        ));
+
+        assert!(LocalAddon.streams("movie", "tt0000000").unwrap().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
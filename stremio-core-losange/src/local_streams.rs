@@ -0,0 +1,112 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use stremio_core::types::{
+    addon::Manifest,
+    resource::{MetaItem, Stream as CoreStream},
+};
+
+use crate::{in_process_addon::InProcessAddon, types::stream::Stream};
+
+pub const ADDON_ID: &str = "xyz.timtimtim.losange.streams";
+pub const LOCAL_TYPE: &str = "other";
+
+lazy_static! {
+    static ref STREAMS: RwLock<HashMap<String, Stream>> = Default::default();
+}
+
+/// Makes a stream opened outside of addons, like a url, a magnet or a file, known to the addon
+pub fn add(id: &str, stream: &Stream) {
+    STREAMS
+        .write()
+        .expect("STREAMS write failed")
+        .insert(id.to_owned(), stream.to_owned());
+}
+
+/// Built-in addon answering the meta and stream requests of opened streams, so that the core
+/// creates library items and tracks progress for them like for any other stream
+pub struct LocalStreamsAddon;
+
+impl InProcessAddon for LocalStreamsAddon {
+    fn manifest(&self) -> Manifest {
+        serde_json::from_value(manifest()).expect("Failed to build local streams addon manifest")
+    }
+
+    fn meta(&self, r#type: &str, id: &str) -> Option<MetaItem> {
+        if r#type != LOCAL_TYPE {
+            return None;
+        }
+
+        let streams = STREAMS.read().expect("STREAMS read failed");
+        serde_json::from_value(meta(id, streams.get(id)?)).ok()
+    }
+
+    fn streams(&self, r#type: &str, id: &str) -> Option<Vec<CoreStream>> {
+        if r#type != LOCAL_TYPE {
+            return None;
+        }
+
+        let streams = STREAMS.read().expect("STREAMS read failed");
+        let stream = streams.get(id)?.to_owned();
+
+        Some(vec![CoreStream::from(stream)])
+    }
+}
+
+fn manifest() -> Value {
+    json!({
+        "id": ADDON_ID,
+        "version": "1.0.0",
+        "name": "Opened",
+        "description": "Links and files opened directly",
+        "types": [LOCAL_TYPE],
+        "resources": ["meta", "stream"],
+        "catalogs": [],
+        "behaviorHints": {},
+    })
+}
+
+fn meta(id: &str, stream: &Stream) -> Value {
+    json!({
+        "id": id,
+        "type": LOCAL_TYPE,
+        "name": stream.name,
+        "posterShape": "landscape",
+        "videos": [],
+        "behaviorHints": { "defaultVideoId": id },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use stremio_core::types::resource::StreamSource;
+    use url::Url;
+
+    use super::{LocalStreamsAddon, LOCAL_TYPE};
+    use crate::{in_process_addon::InProcessAddon, types::stream::Stream};
+
+    #[test]
+    fn opened_stream_has_meta_and_stream() {
+        let url = Url::parse("https://example.com/videos/Video%20File.mp4").unwrap();
+        let stream = Stream::from_url(&url).unwrap();
+        let id = stream.stream_request.path.id.to_owned();
+
+        let meta = LocalStreamsAddon.meta(LOCAL_TYPE, &id).unwrap();
+        assert_eq!(meta.preview.id, id);
+        assert_eq!(meta.preview.name, "Video File.mp4");
+
+        let streams = LocalStreamsAddon.streams(LOCAL_TYPE, &id).unwrap();
+        assert!(matches!(
+            &streams[..],
+            [stream] if stream.source == StreamSource::Url { url }
+        ));
+    }
+
+    #[test]
+    fn unknown_stream_is_missing() {
+        assert!(LocalStreamsAddon.meta(LOCAL_TYPE, "unknown").is_none());
+        assert!(LocalStreamsAddon.streams(LOCAL_TYPE, "unknown").is_none());
+        assert!(LocalStreamsAddon.meta("movie", "unknown").is_none());
+    }
+}
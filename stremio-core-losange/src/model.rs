@@ -32,6 +32,7 @@ pub struct LosangeModel {
     pub discover: CatalogWithFilters<MetaItemPreview>,
     pub library: LibraryWithFilters<NotRemovedFilter>,
     pub search: CatalogsWithExtra,
+    pub shell_search: CatalogsWithExtra,
     pub meta_details: MetaDetails,
     pub installed_addons: InstalledAddonsWithFilters,
     pub remote_addons: CatalogWithFilters<Descriptor>,
@@ -77,6 +78,7 @@ impl LosangeModel {
             discover,
             library: library_,
             search: Default::default(),
+            shell_search: Default::default(),
             meta_details: Default::default(),
             player: Default::default(),
             installed_addons,
@@ -106,6 +108,9 @@ impl LosangeModel {
                 LosangeModelField::Discover => models::discover::update(&self.discover),
                 LosangeModelField::Library => models::library::update(&self.library),
                 LosangeModelField::Search => models::search::update(&self.search, &self.ctx),
+                LosangeModelField::ShellSearch => {
+                    models::search::update_shell(&self.shell_search, &self.ctx)
+                }
                 LosangeModelField::MetaDetails => {
                     models::meta_details::update(&self.meta_details, &self.ctx)
                 }
@@ -1,19 +1,33 @@
+use itertools::Itertools;
 use relm4::SharedState;
 use stremio_core::{
     models::ctx::Ctx,
-    runtime::msg::{Action, ActionCtx},
+    runtime::{
+        msg::{Action, ActionCtx},
+        Env,
+    },
     types::{
-        api::AuthRequest,
+        addon::Descriptor,
+        api::{fetch_api, APIRequest, AuthRequest, SuccessResponse},
         profile::{Auth, Settings},
     },
 };
+use tracing::error;
+use url::Url;
 
-use crate::{core::dispatch, model::LosangeModelField};
+use crate::{
+    core::{self, dispatch, refresh},
+    env::LosangeEnv,
+    model::LosangeModelField,
+};
 
 #[derive(Default)]
 pub struct CtxState {
     pub auth: Option<Auth>,
     pub settings: Settings,
+    pub server_urls: Vec<Url>,
+    pub addons: Vec<Descriptor>,
+    pub disabled_addons: Vec<Url>,
 }
 
 pub static CTX_STATE: SharedState<CtxState> = SharedState::new();
@@ -23,9 +37,20 @@ pub fn update(ctx: &Ctx) {
 
     let auth = ctx.profile.auth.to_owned();
     let settings = ctx.profile.settings.to_owned();
+    let addons = ctx.profile.addons.to_owned();
+
+    let server_urls = ctx
+        .streaming_server_urls
+        .items
+        .iter()
+        .sorted_by_key(|(_, added)| *added)
+        .map(|(url, _)| url.to_owned())
+        .collect_vec();
 
     state.auth = auth;
     state.settings = settings;
+    state.server_urls = server_urls;
+    state.addons = addons;
 }
 
 pub fn sync_with_api() {
@@ -72,3 +97,140 @@ pub fn update_settings<T: FnOnce(Settings) -> Settings>(update: T) {
         Some(LosangeModelField::Ctx),
     );
 }
+
+pub fn add_server_url(url: Url) {
+    dispatch(
+        Action::Ctx(ActionCtx::AddServerUrl(url)),
+        Some(LosangeModelField::Ctx),
+    );
+}
+
+pub fn delete_server_url(url: Url) {
+    dispatch(
+        Action::Ctx(ActionCtx::DeleteServerUrl(url)),
+        Some(LosangeModelField::Ctx),
+    );
+}
+
+pub fn install_addon(descriptor: Descriptor) {
+    dispatch(
+        Action::Ctx(ActionCtx::InstallAddon(descriptor)),
+        Some(LosangeModelField::Ctx),
+    );
+}
+
+pub fn uninstall_addon(descriptor: Descriptor) {
+    dispatch(
+        Action::Ctx(ActionCtx::UninstallAddon(descriptor)),
+        Some(LosangeModelField::Ctx),
+    );
+}
+
+/// Installs the addon, taking the place of an installed addon with the same id
+pub fn replace_addon(descriptor: Descriptor) {
+    let Some(mut addons) = core::profile().map(|profile| profile.addons) else {
+        return;
+    };
+
+    match addons
+        .iter()
+        .position(|addon| addon.manifest.id == descriptor.manifest.id)
+    {
+        Some(position) => {
+            addons[position] = descriptor;
+            set_addons(addons);
+        }
+        None => install_addon(descriptor),
+    }
+}
+
+/// Makes the installed addons match the given list.
+///
+/// The core has no action to reorder addons. When logged in, the list is pushed to the API once
+/// and pulled back, which replaces the addons in a single profile update.
+pub fn set_addons(addons: Vec<Descriptor>) {
+    let Some(profile) = core::profile() else {
+        return;
+    };
+
+    let Some(auth_key) = profile.auth_key().cloned() else {
+        return reinstall_addons(profile.addons, addons);
+    };
+
+    LosangeEnv::exec_concurrent(async move {
+        let request = APIRequest::AddonCollectionSet { auth_key, addons };
+
+        // Pulling also restores the previous list if the push failed
+        if let Err(e) = fetch_api::<LosangeEnv, _, _, SuccessResponse>(&request).await {
+            error!("Failed to push addons to the API: {e:?}");
+        }
+
+        dispatch(
+            Action::Ctx(ActionCtx::PullAddonsFromAPI),
+            Some(LosangeModelField::Ctx),
+        );
+    });
+}
+
+/// Without an account there is nothing to pull from, so the fewest addons are uninstalled and
+/// installed again at the end. Addons left in place keep their order ahead of the reinstalled
+/// ones, they are the longest start of the new list found in order in the current one.
+fn reinstall_addons(current: Vec<Descriptor>, addons: Vec<Descriptor>) {
+    let mut remaining = current.iter();
+    let kept = addons
+        .iter()
+        .take_while(|addon| remaining.any(|current| current == *addon))
+        .count();
+
+    let (kept, reinstalled) = addons.split_at(kept);
+
+    // Protected addons cannot be uninstalled, the order could not be applied
+    if reinstalled.iter().any(|addon| addon.flags.protected) {
+        error!("Cannot move addons across a protected addon");
+        return;
+    }
+
+    for descriptor in current.iter().filter(|addon| !kept.contains(addon)) {
+        uninstall_addon(descriptor.to_owned());
+    }
+
+    for descriptor in reinstalled {
+        install_addon(descriptor.to_owned());
+    }
+}
+
+/// Moves an installed addon to the place of another one
+pub fn move_addon(transport_url: &Url, target_url: &Url) {
+    let Some(mut addons) = core::profile().map(|profile| profile.addons) else {
+        return;
+    };
+
+    let position = |url: &Url| addons.iter().position(|addon| addon.transport_url == *url);
+
+    if let (Some(from), Some(to)) = (position(transport_url), position(target_url)) {
+        let addon = addons.remove(from);
+        addons.insert(to, addon);
+        set_addons(addons);
+    }
+}
+
+/// Hides the catalogs and streams of the given addons without uninstalling them
+pub fn set_disabled_addons(transport_urls: Vec<Url>) {
+    CTX_STATE.write().disabled_addons = transport_urls;
+
+    refresh(vec![
+        LosangeModelField::Home,
+        LosangeModelField::Search,
+        LosangeModelField::ShellSearch,
+        LosangeModelField::Discover,
+        LosangeModelField::MetaDetails,
+        LosangeModelField::InstalledAddons,
+    ]);
+}
+
+pub fn is_addon_disabled(transport_url: &Url) -> bool {
+    CTX_STATE
+        .read_inner()
+        .disabled_addons
+        .contains(transport_url)
+}
@@ -8,7 +8,9 @@ use stremio_core::{
     types::{addon::ResourceRequest, resource::MetaItemPreview},
 };
 
-use crate::{core::dispatch, model::LosangeModelField, types::item::Item};
+use crate::{
+    core::dispatch, model::LosangeModelField, models::ctx::is_addon_disabled, types::item::Item,
+};
 
 #[derive(Default)]
 pub struct DiscoverState {
@@ -24,7 +26,13 @@ pub fn update(discover: &CatalogWithFilters<MetaItemPreview>) {
     let mut state = DISCOVER_STATE.write();
 
     let types = discover.selectable.types.to_owned();
-    let catalogs = discover.selectable.catalogs.to_owned();
+    let catalogs = discover
+        .selectable
+        .catalogs
+        .iter()
+        .filter(|catalog| !is_addon_disabled(&catalog.request.base))
+        .cloned()
+        .collect_vec();
     let genres = discover
         .selectable
         .extra
@@ -14,6 +14,7 @@ use stremio_core::{
 use crate::{
     core::dispatch,
     model::LosangeModelField,
+    models::ctx::is_addon_disabled,
     types::{catalog::Catalog, item::Item},
 };
 
@@ -42,6 +43,7 @@ pub fn update(home: &CatalogsWithExtra, continue_watching: &ContinueWatchingPrev
         .flat_map(|catalog| {
             catalog
                 .iter()
+                .filter(|resource| !is_addon_disabled(&resource.request.base))
                 .map(|resource| Catalog::new(resource, &ctx.profile.addons))
                 .collect_vec()
         })
@@ -7,7 +7,9 @@ use stremio_core::{
     runtime::msg::{Action, ActionLoad},
 };
 
-use crate::{core::dispatch, model::LosangeModelField, types::addon::Addon};
+use crate::{
+    core::dispatch, model::LosangeModelField, models::ctx::is_addon_disabled, types::addon::Addon,
+};
 
 #[derive(Default)]
 pub struct InstalledAddons {
@@ -23,6 +25,11 @@ pub fn update(installed_addons: &InstalledAddonsWithFilters) {
         .catalog
         .iter()
         .map(Addon::from)
+        .map(|mut addon| {
+            addon.installed = true;
+            addon.disabled = is_addon_disabled(&addon.manifest_url);
+            addon
+        })
         .collect_vec();
 
     state.addons = addons;
@@ -14,6 +14,7 @@ use stremio_core::{
 use crate::{
     core::dispatch,
     model::LosangeModelField,
+    models::ctx::is_addon_disabled,
     types::{item::Item, stream::Stream, video::Video},
 };
 
@@ -74,6 +75,7 @@ pub fn update(meta_details: &MetaDetails, ctx: &Ctx) {
         meta_details
             .streams
             .iter()
+            .filter(|resource| !is_addon_disabled(&resource.request.base))
             .filter_map(|resource| {
                 resource
                     .content
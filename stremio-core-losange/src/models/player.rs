@@ -1,20 +1,39 @@
+use std::sync::RwLock;
+
+use futures::FutureExt;
+use lazy_static::lazy_static;
 use relm4::SharedState;
 use stremio_core::{
     models::{
         ctx::Ctx,
         player::{Player, Selected},
     },
-    runtime::msg::{Action, ActionLoad, ActionPlayer},
+    runtime::{
+        msg::{Action, ActionLoad, ActionPlayer},
+        Env,
+    },
     types::{resource::StreamSource, streams::StreamItemState},
 };
+use tracing::error;
 use url::Url;
 
 use crate::{
     core::dispatch,
+    env::LosangeEnv,
     model::LosangeModelField,
-    types::{item::Item, stream::Stream},
+    types::{
+        item::Item,
+        stream::Stream,
+        tracks::{TrackPreference, TracksBucket},
+    },
 };
 
+pub const TRACKS_STORAGE_KEY: &str = "tracks";
+
+lazy_static! {
+    static ref TRACKS: RwLock<TracksBucket> = Default::default();
+}
+
 #[derive(Default)]
 pub struct PlayerState {
     pub uri: Option<Url>,
@@ -23,6 +42,8 @@ pub struct PlayerState {
     pub time: f64,
     pub next_stream: Option<Stream>,
     pub stream_state: Option<StreamItemState>,
+    pub meta_id: Option<String>,
+    pub track_preference: Option<TrackPreference>,
     pub torrent_info: Option<(String, u16)>,
 }
 
@@ -70,6 +91,12 @@ pub fn update(player: &Player, ctx: &Ctx) {
                 })
                 .unwrap_or(item.name.to_owned())
         })
+        .or_else(|| {
+            player
+                .selected
+                .as_ref()
+                .and_then(|selected| selected.stream.name.to_owned())
+        })
         .unwrap_or_default();
 
     let image = item.as_ref().and_then(|item| item.image.to_owned());
@@ -104,6 +131,21 @@ pub fn update(player: &Player, ctx: &Ctx) {
 
     let stream_state = player.stream_state.to_owned();
 
+    let meta_id = player
+        .selected
+        .as_ref()
+        .and_then(|selected| selected.meta_request.as_ref())
+        .map(|meta_request| meta_request.path.id.to_owned());
+
+    let track_preference = meta_id.as_ref().and_then(|meta_id| {
+        TRACKS
+            .read()
+            .expect("TRACKS read failed")
+            .items
+            .get(meta_id)
+            .cloned()
+    });
+
     let torrent_info = player
         .selected
         .as_ref()
@@ -122,6 +164,8 @@ pub fn update(player: &Player, ctx: &Ctx) {
     state.time = time;
     state.next_stream = next_stream;
     state.stream_state = stream_state;
+    state.meta_id = meta_id;
+    state.track_preference = track_preference;
     state.torrent_info = torrent_info;
 }
 
@@ -185,3 +229,30 @@ pub fn update_stream_state<T: FnOnce(StreamItemState) -> StreamItemState>(update
         Some(LosangeModelField::Player),
     );
 }
+
+pub fn init_tracks(bucket: TracksBucket) {
+    *TRACKS.write().expect("TRACKS write failed") = bucket;
+}
+
+pub fn update_track_preference<T: FnOnce(TrackPreference) -> TrackPreference>(update: T) {
+    let mut state = PLAYER_STATE.write_inner();
+
+    if let Some(meta_id) = &state.meta_id {
+        let preference = update(state.track_preference.to_owned().unwrap_or_default());
+
+        let mut tracks = TRACKS.write().expect("TRACKS write failed");
+        tracks
+            .items
+            .insert(meta_id.to_owned(), preference.to_owned());
+
+        LosangeEnv::exec_concurrent(
+            LosangeEnv::set_storage(TRACKS_STORAGE_KEY, Some(&*tracks)).map(|result| {
+                if let Err(e) = result {
+                    error!("Failed to save track preferences: {e:?}");
+                }
+            }),
+        );
+
+        state.track_preference = Some(preference);
+    }
+}
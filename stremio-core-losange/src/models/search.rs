@@ -11,18 +11,58 @@ use stremio_core::{
     types::addon::ExtraValue,
 };
 
-use crate::{core::dispatch, model::LosangeModelField, types::catalog::Catalog};
+use crate::{
+    core::dispatch,
+    model::LosangeModelField,
+    models::ctx::is_addon_disabled,
+    types::{catalog::Catalog, item::Item},
+};
+
+const SEARCH_EXTRA_NAME: &str = "search";
 
 #[derive(Default)]
 pub struct SearchState {
+    pub query: Option<String>,
     pub loading: bool,
+    pub library: Vec<Item>,
     pub catalogs: Vec<Catalog>,
 }
 
 pub static SEARCH_STATE: SharedState<SearchState> = SharedState::new();
 
+/// Searches made from outside of the app, kept apart so they never replace the Search page
+pub static SHELL_SEARCH_STATE: SharedState<SearchState> = SharedState::new();
+
 pub fn update(search: &CatalogsWithExtra, ctx: &Ctx) {
-    let mut state = SEARCH_STATE.write();
+    update_state(&mut SEARCH_STATE.write(), search, ctx);
+}
+
+pub fn update_shell(search: &CatalogsWithExtra, ctx: &Ctx) {
+    update_state(&mut SHELL_SEARCH_STATE.write(), search, ctx);
+}
+
+fn update_state(state: &mut SearchState, search: &CatalogsWithExtra, ctx: &Ctx) {
+    let query = search.selected.as_ref().and_then(|selected| {
+        selected
+            .extra
+            .iter()
+            .find(|extra| extra.name == SEARCH_EXTRA_NAME)
+            .map(|extra| extra.value.to_owned())
+    });
+
+    let library = query
+        .as_ref()
+        .map(|query| query.to_lowercase())
+        .map_or(vec![], |query| {
+            ctx.library
+                .items
+                .values()
+                .filter(|item| !item.removed && !item.temp)
+                .filter(|item| item.name.to_lowercase().contains(&query))
+                .sorted_by(|a, b| b.mtime.cmp(&a.mtime))
+                .map(Item::from)
+                .collect_vec()
+        });
 
     let loading = !search.catalogs.is_empty()
         && !search.catalogs.iter().any(|catalog| {
@@ -40,6 +80,7 @@ pub fn update(search: &CatalogsWithExtra, ctx: &Ctx) {
         .flat_map(|catalog| {
             catalog
                 .iter()
+                .filter(|resource| !is_addon_disabled(&resource.request.base))
                 .filter(|resource| {
                     resource
                         .content
@@ -52,21 +93,18 @@ pub fn update(search: &CatalogsWithExtra, ctx: &Ctx) {
         })
         .collect_vec();
 
+    state.query = query;
     state.loading = loading;
+    state.library = library;
     state.catalogs = catalogs;
 }
 
 pub fn load(query: String) {
-    dispatch(
-        Action::Load(ActionLoad::CatalogsWithExtra(Selected {
-            r#type: None,
-            extra: vec![ExtraValue {
-                name: "search".to_owned(),
-                value: query,
-            }],
-        })),
-        Some(LosangeModelField::Search),
-    );
+    dispatch(load_action(query), Some(LosangeModelField::Search));
+}
+
+pub fn load_shell(query: String) {
+    dispatch(load_action(query), Some(LosangeModelField::ShellSearch));
 }
 
 pub fn load_catalog(start: usize, end: usize) {
@@ -79,3 +117,13 @@ pub fn load_catalog(start: usize, end: usize) {
 pub fn unload() {
     dispatch(Action::Unload, Some(LosangeModelField::Search));
 }
+
+fn load_action(query: String) -> Action {
+    Action::Load(ActionLoad::CatalogsWithExtra(Selected {
+        r#type: None,
+        extra: vec![ExtraValue {
+            name: SEARCH_EXTRA_NAME.to_owned(),
+            value: query,
+        }],
+    }))
+}
@@ -1,19 +1,143 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
 use relm4::SharedState;
 use stremio_core::{
     models::streaming_server::StreamingServer,
     runtime::msg::{Action, ActionStreamingServer},
-    types::streaming_server::StatisticsRequest,
+    types::streaming_server::{Settings, Statistics, StatisticsRequest},
 };
 
 use crate::{core::dispatch, model::LosangeModelField};
 
 const KB: f64 = 1024.0;
 const MB: f64 = KB * KB;
+const HISTORY_LENGTH: usize = 90;
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TorrentProfile {
+    Default,
+    Soft,
+    Fast,
+}
+
+impl TorrentProfile {
+    pub const ALL: [TorrentProfile; 3] = [Self::Default, Self::Soft, Self::Fast];
+
+    pub fn apply(self, settings: &mut Settings) {
+        let (max_connections, min_peers, soft_limit, hard_limit) = match self {
+            Self::Default => (35, 5, 2.5 * MB, 3.5 * MB),
+            Self::Soft => (35, 5, 1.6 * MB, 1.6 * MB),
+            Self::Fast => (200, 10, 4.0 * MB, 37.5 * MB),
+        };
+
+        settings.bt_max_connections = max_connections;
+        settings.bt_min_peers_for_stable = min_peers;
+        settings.bt_download_speed_soft_limit = soft_limit;
+        settings.bt_download_speed_hard_limit = hard_limit;
+        settings.bt_handshake_timeout = 20000;
+        settings.bt_request_timeout = 4000;
+    }
+
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        Self::ALL.into_iter().find(|profile| {
+            let mut expected = settings.to_owned();
+            profile.apply(&mut expected);
+            &expected == settings
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatisticsSample {
+    pub time: Instant,
+    pub peers: u64,
+    pub download_speed: f64,
+    pub upload_speed: f64,
+    pub downloaded: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TorrentStatistics {
+    pub info_hash: String,
+    pub name: String,
+    pub peers: u64,
+    pub unchoked: u64,
+    pub swarm_size: u64,
+    pub download_speed: f64,
+    pub upload_speed: f64,
+    pub downloaded: u64,
+    pub uploaded: u64,
+    pub stream_len: u64,
+    pub stream_progress: f64,
+    pub history: VecDeque<StatisticsSample>,
+}
+
+impl TorrentStatistics {
+    fn new(statistics: &Statistics) -> Self {
+        let mut torrent = Self {
+            info_hash: statistics.info_hash.to_owned(),
+            name: String::new(),
+            peers: 0,
+            unchoked: 0,
+            swarm_size: 0,
+            download_speed: 0.0,
+            upload_speed: 0.0,
+            downloaded: 0,
+            uploaded: 0,
+            stream_len: 0,
+            stream_progress: 0.0,
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+        };
+
+        torrent.update(statistics);
+        torrent
+    }
+
+    fn update(&mut self, statistics: &Statistics) {
+        self.name = statistics.name.to_owned();
+        self.peers = statistics.peers;
+        self.unchoked = statistics.unchoked;
+        self.swarm_size = statistics.swarm_size;
+        self.download_speed = statistics.download_speed;
+        self.upload_speed = statistics.upload_speed;
+        self.downloaded = statistics.downloaded;
+        self.uploaded = statistics.uploaded;
+        self.stream_len = statistics.stream_len;
+        self.stream_progress = statistics.stream_progress;
+
+        // The model also updates on settings changes, only sample at a steady pace
+        let due = self
+            .history
+            .back()
+            .is_none_or(|sample| sample.time.elapsed() >= SAMPLE_INTERVAL);
+
+        if due {
+            if self.history.len() == HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+
+            self.history.push_back(StatisticsSample {
+                time: Instant::now(),
+                peers: statistics.peers,
+                download_speed: statistics.download_speed,
+                upload_speed: statistics.upload_speed,
+                downloaded: statistics.downloaded,
+            });
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct ServerState {
     pub online: bool,
     pub torrent_progress: Option<f64>,
+    pub torrent: Option<TorrentStatistics>,
+    pub settings: Option<Settings>,
+    pub transcode_profiles: Vec<String>,
 }
 
 pub static SERVER_STATE: SharedState<ServerState> = SharedState::new();
@@ -22,6 +146,14 @@ pub fn update(server: &StreamingServer) {
     let mut state = SERVER_STATE.write();
 
     let online = server.settings.is_ready();
+    let settings = server.settings.ready().cloned();
+
+    let transcode_profiles = server
+        .device_info
+        .as_ref()
+        .and_then(|device_info| device_info.ready())
+        .map(|device_info| device_info.available_hardware_accelerations.to_owned())
+        .unwrap_or_default();
 
     let torrent_progress = server
         .statistics
@@ -44,8 +176,25 @@ pub fn update(server: &StreamingServer) {
             progress
         });
 
+    let statistics = server
+        .statistics
+        .as_ref()
+        .and_then(|statistics| statistics.ready());
+
+    // Statistics are briefly unavailable while being refreshed, keep the history until the torrent changes
+    if let Some(statistics) = statistics {
+        match &mut state.torrent {
+            Some(torrent) if torrent.info_hash == statistics.info_hash => {
+                torrent.update(statistics)
+            }
+            torrent => *torrent = Some(TorrentStatistics::new(statistics)),
+        }
+    }
+
     state.online = online;
     state.torrent_progress = torrent_progress;
+    state.settings = settings;
+    state.transcode_profiles = transcode_profiles;
 }
 
 pub fn reload() {
@@ -55,6 +204,19 @@ pub fn reload() {
     );
 }
 
+pub fn update_settings<T: FnOnce(Settings) -> Settings>(update: T) {
+    let state = SERVER_STATE.read_inner();
+
+    if let Some(settings) = state.settings.to_owned() {
+        let updated_settings = update(settings);
+
+        dispatch(
+            Action::StreamingServer(ActionStreamingServer::UpdateSettings(updated_settings)),
+            Some(LosangeModelField::Server),
+        );
+    }
+}
+
 pub fn update_statistics(info_hash: &str, file_idx: u16) {
     dispatch(
         Action::StreamingServer(ActionStreamingServer::GetStatistics(StatisticsRequest {
@@ -64,3 +226,7 @@ pub fn update_statistics(info_hash: &str, file_idx: u16) {
         Some(LosangeModelField::Server),
     );
 }
+
+pub fn clear_statistics() {
+    SERVER_STATE.write().torrent = None;
+}
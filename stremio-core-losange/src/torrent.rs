@@ -0,0 +1,393 @@
+use std::io;
+
+use serde::Deserialize;
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use url::Url;
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+pub(crate) const VIDEO_EXTENSIONS: &[&str] = &[
+    "mkv", "mp4", "avi", "mov", "wmv", "webm", "m4v", "ts", "m2ts", "mpg", "mpeg", "ogv", "flv",
+];
+const PEER_SEARCH_MIN: u32 = 40;
+const PEER_SEARCH_MAX: u32 = 200;
+/// Torrent metadata is shallow, deeper nesting only comes from malformed or hostile files
+const BENCODE_MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TorrentFile {
+    #[serde(skip)]
+    pub index: u16,
+    pub name: String,
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub length: u64,
+}
+
+impl TorrentFile {
+    pub fn is_video(&self) -> bool {
+        is_video_name(&self.name)
+    }
+}
+
+pub fn is_video_name(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        VIDEO_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
+
+#[derive(Deserialize)]
+struct CreateResponse {
+    #[serde(default)]
+    files: Vec<TorrentFile>,
+}
+
+/// Asks the streaming server to load the torrent and returns its files, indexed like `file_idx`.
+pub async fn fetch_files(
+    server_url: &Url,
+    info_hash: &[u8; 20],
+    announce: &[String],
+) -> io::Result<Vec<TorrentFile>> {
+    let info_hash = hex::encode(info_hash);
+
+    let url = server_url
+        .join(&format!("{info_hash}/create"))
+        .map_err(io::Error::other)?;
+
+    let sources = std::iter::once(format!("dht:{info_hash}"))
+        .chain(announce.iter().map(|tracker| format!("tracker:{tracker}")))
+        .collect::<Vec<_>>();
+
+    let body = json!({
+        "torrent": { "infoHash": info_hash },
+        "peerSearch": {
+            "sources": sources,
+            "min": PEER_SEARCH_MIN,
+            "max": PEER_SEARCH_MAX,
+        },
+    });
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(io::Error::other)?
+        .json::<CreateResponse>()
+        .await
+        .map_err(io::Error::other)?;
+
+    let files = response
+        .files
+        .into_iter()
+        .enumerate()
+        .map(|(index, file)| TorrentFile {
+            index: index as u16,
+            ..file
+        })
+        .collect();
+
+    Ok(files)
+}
+
+/// Asks the streaming server to stop the torrent and drop its peers.
+pub async fn remove(server_url: &Url, info_hash: &[u8; 20]) -> io::Result<()> {
+    let url = server_url
+        .join(&format!("{}/remove", hex::encode(info_hash)))
+        .map_err(io::Error::other)?;
+
+    reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(io::Error::other)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TorrentInfo {
+    pub info_hash: [u8; 20],
+    pub name: Option<String>,
+    pub announce: Vec<String>,
+}
+
+impl TorrentInfo {
+    pub fn from_magnet(url: &Url) -> Option<Self> {
+        if url.scheme() != "magnet" {
+            return None;
+        }
+
+        let mut info_hash = None;
+        let mut name = None;
+        let mut announce = vec![];
+
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "xt" => {
+                    info_hash = value
+                        .strip_prefix("urn:btih:")
+                        .and_then(decode_info_hash)
+                        .or(info_hash);
+                }
+                "dn" => name = Some(value.to_string()),
+                "tr" => announce.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            info_hash: info_hash?,
+            name,
+            announce,
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (Bencode::Dict(entries), _) = decode(bytes, 0, 0)? else {
+            return None;
+        };
+
+        let (info, raw_info) = entries
+            .iter()
+            .find(|(key, ..)| *key == b"info")
+            .map(|(_, value, raw)| (value, raw))?;
+
+        let info_hash = Sha1::digest(raw_info).into();
+
+        let name = match info {
+            Bencode::Dict(info) => info
+                .iter()
+                .find(|(key, ..)| *key == b"name")
+                .and_then(|(_, value, _)| value.as_string()),
+            _ => None,
+        };
+
+        let mut announce = entries
+            .iter()
+            .find(|(key, ..)| *key == b"announce")
+            .and_then(|(_, value, _)| value.as_string())
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        if let Some((_, Bencode::List(tiers), _)) =
+            entries.iter().find(|(key, ..)| *key == b"announce-list")
+        {
+            tiers
+                .iter()
+                .filter_map(|tier| match tier {
+                    Bencode::List(trackers) => Some(trackers),
+                    _ => None,
+                })
+                .flatten()
+                .filter_map(Bencode::as_string)
+                .for_each(|tracker| {
+                    if !announce.contains(&tracker) {
+                        announce.push(tracker);
+                    }
+                });
+        }
+
+        Some(Self {
+            info_hash,
+            name,
+            announce,
+        })
+    }
+}
+
+enum Bencode<'a> {
+    Integer(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Bencode<'a>>),
+    Dict(Vec<(&'a [u8], Bencode<'a>, &'a [u8])>),
+}
+
+impl Bencode<'_> {
+    fn as_string(&self) -> Option<String> {
+        match self {
+            Bencode::Bytes(bytes) => String::from_utf8(bytes.to_vec()).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn decode(bytes: &[u8], start: usize, depth: usize) -> Option<(Bencode<'_>, usize)> {
+    if depth > BENCODE_MAX_DEPTH {
+        return None;
+    }
+
+    match bytes.get(start)? {
+        b'i' => {
+            let end = start + 1 + bytes.get(start + 1..)?.iter().position(|&b| b == b'e')?;
+            let value = std::str::from_utf8(&bytes[start + 1..end])
+                .ok()?
+                .parse()
+                .ok()?;
+
+            Some((Bencode::Integer(value), end + 1))
+        }
+        b'l' => {
+            let mut items = vec![];
+            let mut position = start + 1;
+
+            while *bytes.get(position)? != b'e' {
+                let (item, next) = decode(bytes, position, depth + 1)?;
+                items.push(item);
+                position = next;
+            }
+
+            Some((Bencode::List(items), position + 1))
+        }
+        b'd' => {
+            let mut entries = vec![];
+            let mut position = start + 1;
+
+            while *bytes.get(position)? != b'e' {
+                let (Bencode::Bytes(key), next) = decode(bytes, position, depth + 1)? else {
+                    return None;
+                };
+
+                let (value, end) = decode(bytes, next, depth + 1)?;
+                entries.push((key, value, &bytes[next..end]));
+                position = end;
+            }
+
+            Some((Bencode::Dict(entries), position + 1))
+        }
+        b'0'..=b'9' => {
+            let colon = start + bytes[start..].iter().position(|&b| b == b':')?;
+            let length: usize = std::str::from_utf8(&bytes[start..colon])
+                .ok()?
+                .parse()
+                .ok()?;
+            let end = colon.checked_add(1)?.checked_add(length)?;
+
+            Some((Bencode::Bytes(bytes.get(colon + 1..end)?), end))
+        }
+        _ => None,
+    }
+}
+
+fn decode_info_hash(value: &str) -> Option<[u8; 20]> {
+    let bytes = match value.len() {
+        40 => hex::decode(value).ok()?,
+        32 => decode_base32(value)?,
+        _ => return None,
+    };
+
+    bytes.try_into().ok()
+}
+
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for character in value.to_ascii_uppercase().bytes() {
+        let index = BASE32_ALPHABET.iter().position(|&c| c == character)?;
+        buffer = (buffer << 5) | index as u64;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha1::{Digest, Sha1};
+    use url::Url;
+
+    use super::{decode, decode_base32, TorrentInfo};
+
+    const INFO_HASH: [u8; 20] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+    ];
+
+    #[test]
+    fn torrent_file() {
+        let info = b"d6:lengthi1e4:name9:video.mkve";
+        let torrent = [
+            b"d8:announce18:udp://t.example:8013:announce-listll18:udp://t.example:80e".as_slice(),
+            b"l17:udp://u.example:1ee4:info",
+            info,
+            b"e",
+        ]
+        .concat();
+
+        let parsed = TorrentInfo::from_bytes(&torrent).expect("Failed to parse torrent");
+
+        assert_eq!(parsed.info_hash, <[u8; 20]>::from(Sha1::digest(info)));
+        assert_eq!(parsed.name.as_deref(), Some("video.mkv"));
+        assert_eq!(parsed.announce, ["udp://t.example:80", "udp://u.example:1"]);
+    }
+
+    #[test]
+    fn bencode_rejects_malformed_input() {
+        assert!(decode(b"i12", 0, 0).is_none());
+        assert!(decode(b"5:abc", 0, 0).is_none());
+        assert!(decode(b"18446744073709551615:a", 0, 0).is_none());
+        assert!(decode(b"d3:keye", 0, 0).is_none());
+        assert!(decode(b"di1e3:fooe", 0, 0).is_none());
+    }
+
+    #[test]
+    fn bencode_limits_nesting() {
+        let nested = |depth: usize| ["l".repeat(depth), "e".repeat(depth)].concat();
+
+        assert!(decode(nested(8).as_bytes(), 0, 0).is_some());
+        assert!(decode(nested(100_000).as_bytes(), 0, 0).is_none());
+    }
+
+    #[test]
+    fn magnet_with_hex_info_hash() {
+        let url = Url::parse(
+            "magnet:?xt=urn:btih:000102030405060708090a0b0c0d0e0f10111213&dn=Video&tr=udp%3A%2F%2Ft.example%3A80",
+        )
+        .unwrap();
+
+        let parsed = TorrentInfo::from_magnet(&url).expect("Failed to parse magnet");
+
+        assert_eq!(parsed.info_hash, INFO_HASH);
+        assert_eq!(parsed.name.as_deref(), Some("Video"));
+        assert_eq!(parsed.announce, ["udp://t.example:80"]);
+    }
+
+    #[test]
+    fn magnet_with_base32_info_hash() {
+        let url = Url::parse("magnet:?xt=urn:btih:AAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQT").unwrap();
+        let parsed = TorrentInfo::from_magnet(&url).expect("Failed to parse magnet");
+
+        assert_eq!(parsed.info_hash, INFO_HASH);
+    }
+
+    #[test]
+    fn magnet_without_info_hash() {
+        let url = Url::parse("magnet:?dn=Video").unwrap();
+        assert!(TorrentInfo::from_magnet(&url).is_none());
+
+        let url = Url::parse("https://example.com/?xt=urn:btih:AAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQT")
+            .unwrap();
+        assert!(TorrentInfo::from_magnet(&url).is_none());
+    }
+
+    #[test]
+    fn base32() {
+        assert_eq!(
+            decode_base32("MZXW6YTBOI").as_deref(),
+            Some(b"foobar".as_slice())
+        );
+        assert_eq!(
+            decode_base32("mzxw6ytboi").as_deref(),
+            Some(b"foobar".as_slice())
+        );
+        assert!(decode_base32("MZXW6YTBO1").is_none());
+    }
+}
@@ -14,6 +14,7 @@ pub struct Addon {
     pub configurable: bool,
     pub configuration_required: bool,
     pub installed: bool,
+    pub disabled: bool,
 }
 
 impl From<&DescriptorPreview> for Addon {
@@ -41,6 +42,7 @@ impl From<&DescriptorPreview> for Addon {
             configurable: descriptor.manifest.behavior_hints.configurable,
             configuration_required: descriptor.manifest.behavior_hints.configuration_required,
             installed: false,
+            disabled: false,
         }
     }
 }
@@ -70,6 +72,7 @@ impl From<&Descriptor> for Addon {
             configurable: descriptor.manifest.behavior_hints.configurable,
             configuration_required: descriptor.manifest.behavior_hints.configuration_required,
             installed: false,
+            disabled: false,
         }
     }
 }
@@ -2,4 +2,5 @@ pub mod addon;
 pub mod catalog;
 pub mod item;
 pub mod stream;
+pub mod tracks;
 pub mod video;
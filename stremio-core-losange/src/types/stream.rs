@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use stremio_core::{
     constants::{META_RESOURCE_NAME, STREAM_RESOURCE_NAME},
     types::{
@@ -6,8 +8,16 @@ use stremio_core::{
         streams::StreamsItem,
     },
 };
+use url::Url;
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::{
+    in_process_addon,
+    local_addon::ParsedName,
+    local_streams::{self, LOCAL_TYPE},
+    torrent::{TorrentFile, TorrentInfo},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stream {
     pub name: String,
     pub description: String,
@@ -34,6 +44,120 @@ impl Stream {
             stream_request: stream_request.to_owned(),
         }
     }
+
+    pub fn from_url(url: &Url) -> Option<Self> {
+        match url.scheme() {
+            "magnet" => TorrentInfo::from_magnet(url).map(Self::from_torrent_info),
+            "http" | "https" | "file" => {
+                let name = url
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|segment| !segment.is_empty())
+                    .and_then(|segment| percent_decode(segment))
+                    .unwrap_or(url.to_string());
+
+                let id = hex::encode(Sha1::digest(url.as_str()));
+                let source = StreamSource::Url {
+                    url: url.to_owned(),
+                };
+
+                Some(Self::local(name, id, source))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn with_file(&self, file: &TorrentFile) -> Option<Self> {
+        let StreamSource::Torrent {
+            info_hash,
+            announce,
+            file_must_include,
+            ..
+        } = &self.source
+        else {
+            return None;
+        };
+
+        let source = StreamSource::Torrent {
+            info_hash: info_hash.to_owned(),
+            file_idx: Some(file.index),
+            announce: announce.to_owned(),
+            file_must_include: file_must_include.to_owned(),
+        };
+
+        Some(Self {
+            name: file.name.to_owned(),
+            description: self.name.to_owned(),
+            source,
+            stream_request: self.file_request(file),
+            ..self.to_owned()
+        })
+    }
+
+    /// Files of a pack are different videos, each needs its own progress. Episodes of a series
+    /// are matched by name, other files are told apart by their index.
+    fn file_request(&self, file: &TorrentFile) -> ResourceRequest {
+        let mut request = self.stream_request.to_owned();
+        let name = ParsedName::parse(&file.name);
+
+        request.path.id = match (name.season, name.episode) {
+            (Some(season), Some(episode)) if self.meta_request.path.r#type == "series" => {
+                format!("{}:{season}:{episode}", self.meta_request.path.id)
+            }
+            _ => format!("{}:{}", self.stream_request.path.id, file.index),
+        };
+
+        request
+    }
+
+    pub fn from_torrent_file(bytes: &[u8]) -> Option<Self> {
+        TorrentInfo::from_bytes(bytes).map(Self::from_torrent_info)
+    }
+
+    fn from_torrent_info(torrent: TorrentInfo) -> Self {
+        let id = hex::encode(torrent.info_hash);
+        let name = torrent.name.unwrap_or(id.to_owned());
+        let source = StreamSource::Torrent {
+            info_hash: torrent.info_hash,
+            file_idx: None,
+            announce: torrent.announce,
+            file_must_include: vec![],
+        };
+
+        Self::local(name, id, source)
+    }
+
+    /// Streams opened outside of addons are served by the local streams addon
+    fn local(name: String, id: String, source: StreamSource) -> Self {
+        let transport_url = in_process_addon::transport_url(local_streams::ADDON_ID);
+
+        let stream = Self {
+            name,
+            description: String::new(),
+            source,
+            subtitles: vec![],
+            behavior_hints: StreamBehaviorHints::default(),
+            meta_request: ResourceRequest::new(
+                transport_url.to_owned(),
+                ResourcePath::without_extra(META_RESOURCE_NAME, LOCAL_TYPE, &id),
+            ),
+            stream_request: ResourceRequest::new(
+                transport_url,
+                ResourcePath::without_extra(STREAM_RESOURCE_NAME, LOCAL_TYPE, &id),
+            ),
+        };
+
+        local_streams::add(&id, &stream);
+
+        stream
+    }
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    percent_encoding::percent_decode_str(value)
+        .decode_utf8()
+        .ok()
+        .map(|value| value.to_string())
 }
 
 impl From<&StreamsItem> for Stream {
@@ -64,8 +188,8 @@ impl From<Stream> for CoreStream {
     fn from(stream: Stream) -> Self {
         Self {
             source: stream.source,
-            name: None,
-            description: None,
+            name: Some(stream.name).filter(|name| !name.is_empty()),
+            description: Some(stream.description).filter(|description| !description.is_empty()),
             subtitles: stream.subtitles,
             thumbnail: None,
             behavior_hints: stream.behavior_hints,
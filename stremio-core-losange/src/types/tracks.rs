@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackPreference {
+    pub audio_language: Option<String>,
+    pub audio_title: Option<String>,
+    pub subtitles_language: Option<String>,
+    pub subtitles_title: Option<String>,
+    pub subtitles_disabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TracksBucket {
+    pub items: HashMap<String, TrackPreference>,
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream, UdpSocket},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::{AbortHandle, JoinHandle, JoinSet},
+    time::{interval, timeout},
+};
+use tracing::debug;
+
+use crate::types::stream::Stream;
+
+pub const DISCOVERY_PORT: u16 = 11473;
+
+const DISCOVERY_PREFIX: &str = "LOSANGE-PARTY";
+const DISCOVERY_ATTEMPTS: u32 = 5;
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+const HELLO_MAX_LENGTH: usize = 64;
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PartyMessage {
+    Ping { id: u64 },
+    Pong { id: u64 },
+    Playback { paused: bool, time: f64, rate: f64 },
+    Stream { stream: Box<Stream> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartyEvent {
+    Message(PartyMessage),
+    PeersChanged(usize),
+    Closed,
+}
+
+pub struct WatchParty {
+    pub code: String,
+    pub hosting: bool,
+    outgoing: UnboundedSender<PartyMessage>,
+    task: JoinHandle<()>,
+}
+
+impl WatchParty {
+    pub async fn host() -> io::Result<(Self, UnboundedReceiver<PartyEvent>)> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        let discovery = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)).await?;
+
+        let code = generate_code();
+        let (outgoing, outgoing_receiver) = mpsc::unbounded_channel();
+        let (events, events_receiver) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(hub(
+            code.to_owned(),
+            listener,
+            discovery,
+            outgoing_receiver,
+            events,
+        ));
+
+        let party = Self {
+            code,
+            hosting: true,
+            outgoing,
+            task,
+        };
+
+        Ok((party, events_receiver))
+    }
+
+    pub async fn join(code: &str) -> io::Result<(Self, UnboundedReceiver<PartyEvent>)> {
+        let code = code.trim().to_uppercase();
+        let address = discover(&code).await?;
+        let mut stream = TcpStream::connect(address).await?;
+
+        // The host drops connections that do not open with the party code
+        stream
+            .write_all(format!("{DISCOVERY_PREFIX} {code}\n").as_bytes())
+            .await?;
+
+        let (outgoing, outgoing_receiver) = mpsc::unbounded_channel();
+        let (events, events_receiver) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            let (incoming, mut incoming_receiver) = mpsc::unbounded_channel();
+
+            // Run in this task so aborting it also closes the connection
+            let forward = async {
+                while let Some(message) = incoming_receiver.recv().await {
+                    let _ = events.send(PartyEvent::Message(message));
+                }
+            };
+
+            tokio::select! {
+                _ = connection(stream, outgoing_receiver, incoming) => {}
+                _ = forward => {}
+            }
+
+            let _ = events.send(PartyEvent::Closed);
+        });
+
+        let party = Self {
+            code,
+            hosting: false,
+            outgoing,
+            task,
+        };
+
+        Ok((party, events_receiver))
+    }
+
+    pub fn send(&self, message: PartyMessage) {
+        let _ = self.outgoing.send(message);
+    }
+}
+
+impl Drop for WatchParty {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn hub(
+    code: String,
+    listener: TcpListener,
+    discovery: UdpSocket,
+    mut outgoing: UnboundedReceiver<PartyMessage>,
+    events: UnboundedSender<PartyEvent>,
+) {
+    let port = listener.local_addr().map_or(0, |address| address.port());
+    let (incoming, mut incoming_receiver) =
+        mpsc::unbounded_channel::<(usize, Option<PartyMessage>)>();
+
+    // Tasks in a JoinSet are aborted with it, so peers go away when the hub does
+    let mut handshakes = JoinSet::new();
+    let mut peer_tasks = JoinSet::new();
+    let mut peers: HashMap<usize, (UnboundedSender<PartyMessage>, AbortHandle)> = HashMap::new();
+    let mut next_peer_id = 0;
+    let mut buffer = [0; 64];
+    let hello = format!("{DISCOVERY_PREFIX} {code}");
+
+    loop {
+        tokio::select! {
+            Ok((mut stream, address)) = listener.accept() => {
+                let hello = hello.to_owned();
+
+                handshakes.spawn(async move {
+                    match timeout(HELLO_TIMEOUT, read_hello(&mut stream)).await {
+                        Ok(Ok(line)) if line.trim() == hello => Some((stream, address)),
+                        _ => {
+                            debug!("Rejected watch party connection from {address}");
+                            None
+                        }
+                    }
+                });
+            }
+            Some(Ok(Some((stream, address)))) = handshakes.join_next() => {
+                debug!("Watch party peer connected from {address}");
+
+                let id = next_peer_id;
+                next_peer_id += 1;
+
+                let (peer_outgoing, peer_outgoing_receiver) = mpsc::unbounded_channel();
+                let (peer_incoming, mut peer_incoming_receiver) = mpsc::unbounded_channel();
+                let incoming = incoming.clone();
+
+                let task = peer_tasks.spawn(async move {
+                    let forward = async {
+                        while let Some(message) = peer_incoming_receiver.recv().await {
+                            let _ = incoming.send((id, Some(message)));
+                        }
+                    };
+
+                    tokio::select! {
+                        _ = connection(stream, peer_outgoing_receiver, peer_incoming) => {}
+                        _ = forward => {}
+                    }
+
+                    let _ = incoming.send((id, None));
+                });
+
+                peers.insert(id, (peer_outgoing, task));
+                let _ = events.send(PartyEvent::PeersChanged(peers.len()));
+            }
+            Some(_) = peer_tasks.join_next() => {}
+            Ok((length, address)) = discovery.recv_from(&mut buffer) => {
+                let request = String::from_utf8_lossy(&buffer[..length]);
+
+                if request.trim() == format!("{DISCOVERY_PREFIX} {code}") {
+                    let response = format!("{DISCOVERY_PREFIX} {code} {port}");
+                    let _ = discovery.send_to(response.as_bytes(), address).await;
+                }
+            }
+            Some(message) = outgoing.recv() => {
+                for (sender, _) in peers.values() {
+                    let _ = sender.send(message.to_owned());
+                }
+            }
+            Some((id, message)) = incoming_receiver.recv() => match message {
+                Some(message) => {
+                    // Relay to every other peer so the host acts as the hub of the session
+                    peers
+                        .iter()
+                        .filter(|(peer_id, _)| **peer_id != id)
+                        .for_each(|(_, (sender, _))| {
+                            let _ = sender.send(message.to_owned());
+                        });
+
+                    let _ = events.send(PartyEvent::Message(message));
+                }
+                None => {
+                    if let Some((_, task)) = peers.remove(&id) {
+                        task.abort();
+                    }
+
+                    let _ = events.send(PartyEvent::PeersChanged(peers.len()));
+                }
+            },
+            else => break,
+        }
+    }
+
+    peer_tasks.abort_all();
+    let _ = events.send(PartyEvent::Closed);
+}
+
+/// Reads the first line sent by a peer without buffering what comes after it
+async fn read_hello(stream: &mut TcpStream) -> io::Result<String> {
+    let mut hello = Vec::new();
+
+    loop {
+        match stream.read_u8().await? {
+            b'\n' => break,
+            _ if hello.len() >= HELLO_MAX_LENGTH => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Hello too long"));
+            }
+            byte => hello.push(byte),
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&hello).into_owned())
+}
+
+async fn connection(
+    stream: TcpStream,
+    mut outgoing: UnboundedReceiver<PartyMessage>,
+    incoming: UnboundedSender<PartyMessage>,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut ping_interval = interval(PING_INTERVAL);
+    let mut ping: Option<(u64, Instant)> = None;
+    let mut next_ping_id = 0;
+    let mut latency = Duration::ZERO;
+
+    loop {
+        let message = tokio::select! {
+            _ = ping_interval.tick() => {
+                next_ping_id += 1;
+                ping = Some((next_ping_id, Instant::now()));
+                Some(PartyMessage::Ping { id: next_ping_id })
+            }
+            Some(message) = outgoing.recv() => Some(message),
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else {
+                    break;
+                };
+
+                match serde_json::from_str::<PartyMessage>(&line) {
+                    Ok(PartyMessage::Ping { id }) => Some(PartyMessage::Pong { id }),
+                    Ok(PartyMessage::Pong { id }) => {
+                        if let Some((_, sent)) = ping.take().filter(|(ping_id, _)| *ping_id == id) {
+                            latency = sent.elapsed() / 2;
+                        }
+
+                        None
+                    }
+                    Ok(PartyMessage::Playback { paused, time, rate }) => {
+                        // The sender kept playing while the message was in flight
+                        let time = match paused {
+                            true => time,
+                            false => time + latency.as_secs_f64() * 1000.0 * rate,
+                        };
+
+                        let _ = incoming.send(PartyMessage::Playback { paused, time, rate });
+                        None
+                    }
+                    Ok(message) => {
+                        let _ = incoming.send(message);
+                        None
+                    }
+                    Err(e) => {
+                        debug!("Invalid watch party message: {e}");
+                        None
+                    }
+                }
+            }
+        };
+
+        if let Some(message) = message {
+            let Ok(mut line) = serde_json::to_string(&message) else {
+                continue;
+            };
+
+            line.push('\n');
+
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn discover(code: &str) -> io::Result<SocketAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+
+    let request = format!("{DISCOVERY_PREFIX} {code}");
+    let mut buffer = [0; 64];
+
+    for _ in 0..DISCOVERY_ATTEMPTS {
+        socket
+            .send_to(request.as_bytes(), (Ipv4Addr::BROADCAST, DISCOVERY_PORT))
+            .await?;
+
+        // Also reach a host running on this machine when broadcasts are filtered
+        socket
+            .send_to(request.as_bytes(), (Ipv4Addr::LOCALHOST, DISCOVERY_PORT))
+            .await?;
+
+        if let Ok(Ok((length, address))) =
+            timeout(DISCOVERY_TIMEOUT, socket.recv_from(&mut buffer)).await
+        {
+            let response = String::from_utf8_lossy(&buffer[..length]);
+
+            let port = response
+                .strip_prefix(&request)
+                .and_then(|port| port.trim().parse::<u16>().ok());
+
+            if let Some(port) = port {
+                return Ok(SocketAddr::new(address.ip(), port));
+            }
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No watch party found for code {code}"),
+    ))
+}
+
+fn generate_code() -> String {
+    let mut bytes = [0; CODE_LENGTH];
+    getrandom::getrandom(&mut bytes).expect("Failed to generate random bytes");
+
+    bytes
+        .iter()
+        .map(|byte| CODE_ALPHABET[*byte as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}